@@ -0,0 +1,356 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustproject::simulation::boundary::{Boundaries, Boundary};
+use rustproject::simulation::color_map::ColorMap;
+use rustproject::simulation::gray_scott::{DEFAULT_DELTA_T, DEFAULT_DIFFUSION_A, DEFAULT_DIFFUSION_B, GrayScottGrid};
+use rustproject::simulation::integrator::Integrator;
+use rustproject::simulation::mask::Mask;
+use rustproject::simulation::parameter_map::{PEARSON_FEED_RANGE, PEARSON_KILL_RANGE, ParameterImage, ParameterMap};
+use rustproject::simulation::preset::{builtin_presets, find_preset};
+use rustproject::simulation::image::{cells_to_png, cells_to_raw};
+use rustproject::simulation::seed::Seed;
+use rustproject::simulation::stability::stability_warning;
+use rustproject::simulation::stencil::{Kernel, Stencil};
+use rustproject::simulation::state_file::{FILE_EXTENSION, SimulationState};
+
+const USAGE: &str = "usage: rd-headless [options]
+
+options:
+    --preset <name>        start from a named preset's feed, kill, diffusion rates, time step, kernel, seed and palette.
+                           options after it override it
+    --width <cells>        grid width (default 256)
+    --height <cells>       grid height (default 256)
+    --feed <rate>          feed rate (default 0.055)
+    --kill <rate>          kill rate (default 0.062)
+    --stencil <name>       laplacian kernel: 5-point, 9-point or 5x5 (default 9-point)
+    --integrator <name>    euler, rk2 or rk4 (default euler)
+    --diffusion-a <rate>   diffusion rate of a (default 1.0)
+    --diffusion-b <rate>   diffusion rate of b (default 0.5)
+    --delta-t <dt>         time step of each iteration (default 1.0)
+    --boundary <name>      edges on both axes: periodic, neumann, dirichlet or absorbing (default periodic)
+    --boundary-x <name>    left and right edges
+    --boundary-y <name>    bottom and top edges
+    --edge-value <a:b>     a and b past dirichlet edges (default 1:0)
+    --map <gradient|file>  vary feed and kill over the grid instead of using --feed and --kill everywhere.
+                           gradient sweeps feed from left to right and kill from bottom to top,
+                           a png file picks feed with its red channel and kill with its green channel
+    --mask <file>          png of walls, dark pixels block the reaction and diffusion. stretched over the grid
+    --feed-range <from:to> feed range for --map (default 0.01:0.1)
+    --kill-range <from:to> kill range for --map (default 0.045:0.07)
+    --iterations <n>       number of steps to run (default 5000)
+    --seed <u64>           seed random squares of b instead of one square in the center, or the random
+                           numbers for --pattern
+    --pattern <name>       what the field starts from: center, circle[:radius], noise[:density], circles[:count],
+                           squares[:count], text:<string> or image:<file> where luminance is b (default center)
+    --load <file>          start from a saved state file, resampled to width and height if they are given.
+                           settings given as options or by a preset replace the ones in the file
+    --palette <name>       colors for png frames: pmneila, viridis, magma or grayscale (default pmneila)
+    --scale <n>            make png frames n times larger than the grid (default 1)
+    --frames <n>           number of frames to write, evenly spaced over the run (default 1)
+    --format <png|raw|state>
+                           png is colored like the web version, raw is little endian u16 a, b pairs,
+                           state is a state file that can be loaded back in (default png)
+    --output <dir>         directory to write frames into (default .)";
+
+enum Format {
+    Png,
+    Raw,
+    State,
+}
+
+struct Options {
+    width: i32,
+    height: i32,
+    feed: f32,
+    kill: f32,
+    kernel: Kernel,
+    integrator: Integrator,
+    diffusion_a: f32,
+    diffusion_b: f32,
+    delta_t: f32,
+    boundaries: Boundaries,
+    map: Option<String>,
+    mask: Option<PathBuf>,
+    feed_range: (f32, f32),
+    kill_range: (f32, f32),
+    iterations: u32,
+    seed: Option<u64>,
+    pattern: Option<Seed>,
+    load: Option<PathBuf>,
+    size_given: bool,
+    /* the options that were given, directly or by a preset. the rest are defaults that a loaded state keeps its own values for */
+    given: HashSet<String>,
+    color_map: ColorMap,
+    scale: u32,
+    frames: u32,
+    format: Format,
+    output: PathBuf,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            width: 256,
+            height: 256,
+            feed: 0.055,
+            kill: 0.062,
+            kernel: Kernel::default(),
+            integrator: Integrator::Euler,
+            diffusion_a: DEFAULT_DIFFUSION_A,
+            diffusion_b: DEFAULT_DIFFUSION_B,
+            delta_t: DEFAULT_DELTA_T,
+            boundaries: Boundaries::periodic(),
+            map: None,
+            mask: None,
+            feed_range: PEARSON_FEED_RANGE,
+            kill_range: PEARSON_KILL_RANGE,
+            iterations: 5000,
+            seed: None,
+            pattern: None,
+            load: None,
+            size_given: false,
+            given: HashSet::new(),
+            color_map: ColorMap::pmneila(),
+            scale: 1,
+            frames: 1,
+            format: Format::Png,
+            output: PathBuf::from("."),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Err(String::new());
+            }
+
+            let value = args.next().ok_or_else(|| format!("missing value for {}", arg))?;
+            match arg.as_str() {
+                "--preset" => {
+                    let presets = builtin_presets();
+                    let preset = find_preset(&presets, value).ok_or_else(|| format!("unknown preset {}", value))?;
+                    options.feed = preset.feed;
+                    options.kill = preset.kill;
+                    options.kernel = preset.kernel().unwrap_or_default();
+                    options.diffusion_a = preset.diffusion_a.unwrap_or(DEFAULT_DIFFUSION_A);
+                    options.diffusion_b = preset.diffusion_b.unwrap_or(DEFAULT_DIFFUSION_B);
+                    options.delta_t = preset.delta_t.unwrap_or(DEFAULT_DELTA_T);
+                    if let Some(seed) = preset.seed() {
+                        options.pattern = Some(seed);
+                        options.given.insert(String::from("--pattern"));
+                    }
+                    if let Some(color_map) = preset.color_map() {
+                        options.color_map = color_map;
+                    }
+                    for preset_arg in ["--feed", "--kill", "--stencil", "--diffusion-a", "--diffusion-b", "--delta-t"] {
+                        options.given.insert(String::from(preset_arg));
+                    }
+                }
+                "--width" => {
+                    options.width = parse_value(arg, value)?;
+                    options.size_given = true;
+                }
+                "--height" => {
+                    options.height = parse_value(arg, value)?;
+                    options.size_given = true;
+                }
+                "--feed" => options.feed = parse_value(arg, value)?,
+                "--kill" => options.kill = parse_value(arg, value)?,
+                "--stencil" => options.kernel = Stencil::by_name(value).ok_or_else(|| format!("unknown stencil {}", value))?.kernel(),
+                "--integrator" => options.integrator = Integrator::by_name(value).ok_or_else(|| format!("unknown integrator {}", value))?,
+                "--diffusion-a" => options.diffusion_a = parse_value(arg, value)?,
+                "--diffusion-b" => options.diffusion_b = parse_value(arg, value)?,
+                "--delta-t" => options.delta_t = parse_value(arg, value)?,
+                "--boundary" => {
+                    let boundary = parse_boundary(value)?;
+                    options.boundaries.x = boundary;
+                    options.boundaries.y = boundary;
+                }
+                "--boundary-x" => options.boundaries.x = parse_boundary(value)?,
+                "--boundary-y" => options.boundaries.y = parse_boundary(value)?,
+                "--edge-value" => options.boundaries.edge_value = parse_range(arg, value)?,
+                "--map" => options.map = Some(value.clone()),
+                "--mask" => options.mask = Some(PathBuf::from(value)),
+                "--feed-range" => options.feed_range = parse_range(arg, value)?,
+                "--kill-range" => options.kill_range = parse_range(arg, value)?,
+                "--iterations" => options.iterations = parse_value(arg, value)?,
+                "--seed" => options.seed = Some(parse_value(arg, value)?),
+                "--pattern" => options.pattern = Some(parse_pattern(value)?),
+                "--load" => options.load = Some(PathBuf::from(value)),
+                "--palette" => options.color_map = ColorMap::by_name(value).ok_or_else(|| format!("unknown palette {}", value))?,
+                "--scale" => options.scale = parse_value(arg, value)?,
+                "--frames" => options.frames = parse_value(arg, value)?,
+                "--format" => options.format = match value.as_str() {
+                    "png" => Format::Png,
+                    "raw" => Format::Raw,
+                    "state" => Format::State,
+                    _ => return Err(format!("unknown format {}", value)),
+                },
+                "--output" => options.output = PathBuf::from(value),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+            options.given.insert(arg.clone());
+        }
+
+        if options.width <= 0 || options.height <= 0 {
+            return Err(String::from("width and height must be positive"));
+        }
+        if options.frames == 0 {
+            return Err(String::from("frames must be at least 1"));
+        }
+
+        return Ok(options);
+    }
+
+    fn given(&self, arg: &str) -> bool {
+        return self.given.contains(arg);
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    return value.parse::<T>().map_err(|_| format!("invalid value for {}: {}", arg, value));
+}
+
+fn parse_range(arg: &str, value: &str) -> Result<(f32, f32), String> {
+    let (from, to) = value.split_once(':').ok_or_else(|| format!("invalid value for {}: {}, expected from:to", arg, value))?;
+    return Ok((parse_value(arg, from)?, parse_value(arg, to)?));
+}
+
+fn parse_pattern(value: &str) -> Result<Seed, String> {
+    if let Some(path) = value.strip_prefix("image:") {
+        let bytes = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        return Seed::image_from_png(&bytes).map_err(|e| format!("could not load {}: {}", path, e));
+    }
+    return Seed::parse(value);
+}
+
+fn parse_boundary(value: &str) -> Result<Boundary, String> {
+    return Boundary::by_name(value).ok_or_else(|| format!("unknown boundary {}", value));
+}
+
+fn write_frame(options: &Options, grid: &GrayScottGrid, iterations: u64, frame: u32) -> Result<(), String> {
+    let (extension, bytes) = match options.format {
+        Format::Png => ("png", cells_to_png(grid.cells(), grid.width(), grid.height(), &options.color_map, Some(grid.mask()), options.scale)?),
+        Format::Raw => ("raw", cells_to_raw(grid.cells())),
+        Format::State => (FILE_EXTENSION, grid.to_state(iterations).to_bytes()),
+    };
+
+    let path = options.output.join(format!("frame_{:04}.{}", frame, extension));
+    std::fs::write(&path, bytes).map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+    println!("wrote {}", path.display());
+    return Ok(());
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    std::fs::create_dir_all(&options.output).map_err(|e| format!("could not create {}: {}", options.output.display(), e))?;
+
+    let mut grid = GrayScottGrid::new(options.width, options.height, options.feed, options.kill);
+    let mut start_iterations = 0;
+    if let Some(path) = options.load.as_ref() {
+        let bytes = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let mut state = SimulationState::from_bytes(&bytes).map_err(|e| format!("could not load {}: {}", path.display(), e))?;
+        if options.size_given {
+            state = state.resampled(options.width, options.height);
+        }
+        grid.load_state(&state)?;
+        start_iterations = state.iterations;
+    }
+
+    // a loaded state keeps its own settings unless they were given
+    let apply = |args: &[&str]| options.load.is_none() || args.iter().any(|arg| options.given(arg));
+    if apply(&["--feed"]) {
+        grid.set_feed(options.feed);
+    }
+    if apply(&["--kill"]) {
+        grid.set_kill(options.kill);
+    }
+    if apply(&["--stencil"]) {
+        grid.set_kernel(options.kernel.clone());
+    }
+    if apply(&["--integrator"]) {
+        grid.set_integrator(options.integrator);
+    }
+    if apply(&["--diffusion-a"]) {
+        grid.set_diffusion_a(options.diffusion_a);
+    }
+    if apply(&["--diffusion-b"]) {
+        grid.set_diffusion_b(options.diffusion_b);
+    }
+    if apply(&["--delta-t"]) {
+        grid.set_delta_t(options.delta_t);
+    }
+    let mut boundaries = grid.boundaries();
+    if apply(&["--boundary", "--boundary-x"]) {
+        boundaries.x = options.boundaries.x;
+    }
+    if apply(&["--boundary", "--boundary-y"]) {
+        boundaries.y = options.boundaries.y;
+    }
+    if apply(&["--edge-value"]) {
+        boundaries.edge_value = options.boundaries.edge_value;
+    }
+    grid.set_boundaries(boundaries);
+
+    match options.map.as_deref() {
+        Some("gradient") => grid.set_parameter_map(ParameterMap::gradient(options.feed_range, options.kill_range)),
+        Some(path) => {
+            let bytes = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+            let image = ParameterImage::from_png(&bytes).map_err(|e| format!("could not load {}: {}", path, e))?;
+            grid.set_parameter_map(ParameterMap::image(image, options.feed_range, options.kill_range));
+        }
+        None => {}
+    }
+
+    let (width, height) = (grid.width(), grid.height());
+    let pattern = match (options.pattern.as_ref(), options.seed) {
+        (Some(pattern), _) => Some(pattern.clone()),
+        (None, Some(_)) => Some(Seed::Squares { count: (width * height / 4096).max(1) }),
+        (None, None) => None,
+    };
+    if let Some(pattern) = pattern {
+        let mut rng = StdRng::seed_from_u64(options.seed.unwrap_or(0));
+        pattern.apply(grid.cells_mut(), width, height, &mut rng);
+    }
+
+    if let Some(path) = options.mask.as_ref() {
+        let bytes = std::fs::read(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        grid.set_mask(Mask::from_png(&bytes, width, height).map_err(|e| format!("could not load {}: {}", path.display(), e))?);
+    }
+
+    if let Some(warning) = stability_warning(grid.kernel(), grid.integrator(), grid.diffusion_a(), grid.diffusion_b(), grid.delta_t()) {
+        eprintln!("warning: {}", warning);
+    }
+
+    // frames are written at the end of each equal chunk of iterations, so the last frame is always the final state
+    let mut iteration = 0;
+    for frame in 0..options.frames {
+        let frame_end = (options.iterations as u64 * (frame + 1) as u64 / options.frames as u64) as u32;
+        while iteration < frame_end {
+            grid.step();
+            iteration += 1;
+        }
+        write_frame(options, &grid, start_iterations + iteration as u64, frame)?;
+    }
+
+    return Ok(());
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            std::process::exit(if message.is_empty() { 0 } else { 2 });
+        }
+    };
+
+    if let Err(message) = run(&options) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
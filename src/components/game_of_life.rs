@@ -1,9 +1,11 @@
 use std::any::Any;
 use std::collections::HashSet;
 use std::rc::Rc;
+use glam::Mat4;
 use web_sys::{WebGl2RenderingContext, WebGlBuffer, WebGlProgram, WebGlVertexArrayObject};
 use crate::{Component, GameObject};
 use crate::engine::app::App;
+use crate::engine::transform::Transform;
 
 const BUFFER_SIZE: i32 = 1024 * 1024 * 100; // 100Mib
 
@@ -107,12 +109,19 @@ impl Component for GameOfLife {
         }
     }
 
-    fn draw(&mut self, game_object: &mut GameObject, app: &App) {
+    fn draw(&mut self, game_object: &mut GameObject, app: &App, view: &Mat4, projection: &Mat4) {
         let gl = app.gl();
 
         gl.bind_vertex_array(self.vao.as_ref().as_ref());
         gl.use_program(Some(&self.program));
 
+        // point.vert has no model matrix, so where its Transform puts it goes in with the view
+        let model_view = *view * Transform::world_matrix_of(game_object);
+        let loc = gl.get_uniform_location(&self.program, "u_view");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, model_view.as_ref());
+        let loc = gl.get_uniform_location(&self.program, "u_projection");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, projection.as_ref());
+
         let vert_count = (self.vertices.len() / 2) as i32;
         gl.draw_arrays(WebGl2RenderingContext::POINTS, 0, vert_count);
     }
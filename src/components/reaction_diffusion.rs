@@ -1,76 +1,644 @@
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
-use glam::{Mat4, Quat, Vec3};
+use glam::{Mat4, Vec3};
 use rand::Rng;
-use web_sys::{WebGl2RenderingContext, WebGlFramebuffer, WebGlProgram, WebGlTexture, WebGlVertexArrayObject};
+use web_sys::{WebGl2RenderingContext, WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlTexture, WebGlVertexArrayObject};
 use crate::{Component, create_shader_program, GameObject, ReactionDiffusionUI};
+use crate::components::reaction_diffusion_ui::{PaintMode, SeedMode};
 use crate::engine::app::App;
-use crate::engine::app::input::Button::Left;
-use crate::utils::{distance, lerp};
-
+use crate::engine::app::input::{Pointer, PointerKind};
+use crate::rendering::camera::{Camera, CameraView};
+use crate::engine::transform::Transform;
+use crate::simulation::adaptive_budget::AdaptiveBudget;
+use crate::simulation::fixed_timestep::{DEFAULT_ITERATIONS_PER_SECOND, FixedTimestep};
+use crate::simulation::gray_scott::{DEFAULT_DELTA_T, DEFAULT_DIFFUSION_A, DEFAULT_DIFFUSION_B, GrayScottGrid};
+use crate::simulation::integrator::Integrator;
+use crate::simulation::boundary::Boundaries;
+use crate::simulation::brush::{Brush, Stamp};
+use crate::simulation::stroke::Stroke;
+use crate::simulation::color_map::ColorMap;
+use crate::simulation::parameter_map::{ParameterImage, ParameterMap, ParameterSource};
+use crate::simulation::mask::Mask;
+use crate::simulation::preset::Preset;
+use crate::simulation::seed::{Seed, SeedShape};
+use crate::simulation::resolution::{FitMode, Resolution};
+use crate::simulation::image::{cells_to_rgba, encode_png, flip_rows, upscale_rgba};
+use crate::simulation::stability::stability_warning;
+use crate::simulation::stencil::{Kernel, Stencil};
+use crate::simulation::state_file::{FILE_EXTENSION, SimulationState};
+use crate::utils::download_bytes;
+
+// screen pixels per cell until a different resolution is picked
 const SIMULATION_SCALE: f32 = 1.5;
 
+// the most iterations a frame the adaptive budget starts by allowing, the same as the default speed at 60 fps
+const ADAPTIVE_START_ITERATIONS: u32 = 15;
+
+// so a long frame doesn't stall the next one trying to catch up
+const MAX_ITERATIONS_PER_FRAME: u32 = 250;
+const FAST_FORWARD_MULTIPLIER: f32 = 4.0;
+
+// screen pixels a cell has to cover before the field is drawn without smoothing
+const PIXELATED_CELL_SIZE: f32 = 6.0;
+
 const FEED_START: f32 = 0.055;
 const KILL_START: f32 = 0.062;
 
-// presets from: https://pmneila.github.io/jsexp/grayscott/
-const FEED_KILL_PAIRS: &'static [f32] = &[0.055, 0.062, 0.03, 0.062, 0.025, 0.06, 0.078, 0.061, 0.039, 0.058, 0.026, 0.051, 0.014, 0.054, 0.018, 0.051, 0.014, 0.045, 0.062, 0.061];
+pub enum Backend {
+    Gpu,
+    // steps a GrayScottGrid on the cpu and uploads it every frame. for browsers where the gpu path doesn't work
+    Cpu,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SnapshotSource {
+    // exactly what the gpu drew
+    RenderTexture,
+    // the a, b field colored on the cpu
+    Cells,
+}
+
+impl SnapshotSource {
+    pub fn all() -> [SnapshotSource; 2] {
+        return [SnapshotSource::Cells, SnapshotSource::RenderTexture];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            SnapshotSource::RenderTexture => "as drawn",
+            SnapshotSource::Cells => "colored cells",
+        };
+    }
+}
 
 pub struct ReactionDiffusion {
-    quad_vao: Option<WebGlVertexArrayObject>,
+    stamp_vao: Option<WebGlVertexArrayObject>,
+    /* x, y and pressure of each stamp being drawn, one instance each */
+    stamp_buffer: Option<WebGlBuffer>,
     render_texture_vao: Option<WebGlVertexArrayObject>,
     basic_bicubic: WebGlProgram,
     reaction_diffusion: WebGlProgram,
     reaction_diffusion_render: WebGlProgram,
     basic_rg16ui: WebGlProgram,
-    unlit_color_on_rg16ui: WebGlProgram,
+    brush_stamp: WebGlProgram,
+    brush_apply: WebGlProgram,
     basic_color_on_rg16_ui: WebGlProgram,
     indices_count: i32,
     fbo: Option<Box<WebGlFramebuffer>>,
     input_texture: Option<Box<WebGlTexture>>,
     output_texture: Option<Box<WebGlTexture>>,
+    /* scratch textures for integrators with more than one stage */
+    accumulator_texture: Option<Box<WebGlTexture>>,
+    stage_texture: Option<Box<WebGlTexture>>,
+    next_stage_texture: Option<Box<WebGlTexture>>,
     render_texture: Option<Box<WebGlTexture>>,
+    parameter_texture: Option<Box<WebGlTexture>>,
+    mask_texture: Option<Box<WebGlTexture>>,
+    /* how much of each cell the brush covered this frame, see Brush */
+    brush_coverage_texture: Option<Box<WebGlTexture>>,
     width: i32,
     height: i32,
-    last_mouse_position: (i32, i32),
+    resolution: Resolution,
+    fit_mode: FitMode,
+    /* the stroke each pointer that's painting is drawing, by pointer id */
+    strokes: Vec<(i32, Stroke)>,
     reaction_diffusion_ui: Option<Weak<RefCell<ReactionDiffusionUI>>>,
-    current_feed_kill_pair_i: usize,
-    last_screen_size: (i32, i32),
+    camera: Option<Weak<RefCell<Camera>>>,
+    // copied from the camera every update so it can be used while the camera is drawing
+    camera_view: CameraView,
+    // where its Transform puts the grid, copied every update the same way
+    world_matrix: Mat4,
+    cpu_grid: Option<GrayScottGrid>,
+    feed: f32,
+    kill: f32,
+    kernel: Kernel,
+    integrator: Integrator,
+    diffusion_a: f32,
+    diffusion_b: f32,
+    delta_t: f32,
+    boundaries: Boundaries,
+    // set when the kernel, integrator, diffusion rates or time step change so the stability warning is only worked out again then
+    stability_changed: bool,
+    iteration_count: u64,
+    fixed_timestep: FixedTimestep,
+    adaptive_budget: AdaptiveBudget,
+    color_map: ColorMap,
+    parameter_map: ParameterMap,
+    // kept so switching back to the image mode doesn't need it loaded again
+    parameter_image: Option<ParameterImage>,
+    mask: Mask,
+    seed: Seed,
+    // kept so reseeding with the image mode doesn't need it loaded again
+    seed_image: Option<Seed>,
 }
 
 impl ReactionDiffusion {
-    pub fn new(app: &App) -> Self {
+    pub fn new(app: &App, backend: Backend) -> Self {
         let gl = app.gl();
 
-        let width = (app.screen().width() as f32 / SIMULATION_SCALE).round() as i32;
-        let height = (app.screen().height() as f32 / SIMULATION_SCALE).round() as i32;
+        let resolution = Resolution::Scale(SIMULATION_SCALE);
+        let (width, height) = resolution.grid_size(app.screen().size());
+
+        let cpu_grid = match backend {
+            Backend::Gpu => None,
+            Backend::Cpu => Some(GrayScottGrid::new(width, height, FEED_START, KILL_START)),
+        };
 
         return Self {
-            quad_vao: None,
+            stamp_vao: None,
+            stamp_buffer: None,
             render_texture_vao: None,
             basic_bicubic: create_shader_program(&gl, include_str!("../shaders/basic_bicubic.vert"), include_str!("../shaders/basic_bicubic.frag")),
             reaction_diffusion: create_shader_program(&gl, include_str!("../shaders/reaction_diffusion.vert"), include_str!("../shaders/reaction_diffusion.frag")),
             reaction_diffusion_render: create_shader_program(&gl, include_str!("../shaders/reaction_diffusion_render.vert"), include_str!("../shaders/reaction_diffusion_render.frag")),
             basic_rg16ui: create_shader_program(&gl, include_str!("../shaders/basic_RG16UI.vert"), include_str!("../shaders/basic_RG16UI.frag")),
-            unlit_color_on_rg16ui: create_shader_program(&gl, include_str!("../shaders/unlit_color_on_RG16UI.vert"), include_str!("../shaders/unlit_color_on_RG16UI.frag")),
+            brush_stamp: create_shader_program(&gl, include_str!("../shaders/brush_stamp.vert"), include_str!("../shaders/brush_stamp.frag")),
+            brush_apply: create_shader_program(&gl, include_str!("../shaders/basic_RG16UI.vert"), include_str!("../shaders/brush_apply.frag")),
             basic_color_on_rg16_ui: create_shader_program(&gl, include_str!("../shaders/basic_color_on_RG16UI.vert"), include_str!("../shaders/basic_color_on_RG16UI.frag")),
             indices_count: 0,
             fbo: None,
             input_texture: None,
             output_texture: None,
+            accumulator_texture: None,
+            stage_texture: None,
+            next_stage_texture: None,
             render_texture: None,
+            parameter_texture: None,
+            mask_texture: None,
+            brush_coverage_texture: None,
             width,
             height,
-            last_mouse_position: (-1, -1),
+            resolution,
+            fit_mode: FitMode::Stretch,
+            strokes: Vec::new(),
             reaction_diffusion_ui: None,
-            current_feed_kill_pair_i: 0,
-            last_screen_size: (-1, -1),
+            camera: None,
+            camera_view: CameraView::new(),
+            world_matrix: Mat4::IDENTITY,
+            cpu_grid,
+            feed: FEED_START,
+            kill: KILL_START,
+            kernel: Kernel::default(),
+            integrator: Integrator::Euler,
+            diffusion_a: DEFAULT_DIFFUSION_A,
+            diffusion_b: DEFAULT_DIFFUSION_B,
+            delta_t: DEFAULT_DELTA_T,
+            boundaries: Boundaries::periodic(),
+            stability_changed: true,
+            iteration_count: 0,
+            fixed_timestep: FixedTimestep::new(DEFAULT_ITERATIONS_PER_SECOND, MAX_ITERATIONS_PER_FRAME),
+            adaptive_budget: AdaptiveBudget::new(ADAPTIVE_START_ITERATIONS, SIMULATION_SCALE),
+            color_map: ColorMap::pmneila(),
+            parameter_map: ParameterMap::uniform(),
+            parameter_image: None,
+            mask: Mask::new(width, height),
+            seed: Seed::default(),
+            seed_image: None,
         };
     }
 }
 
 impl ReactionDiffusion {
-    fn clear(&self, gl: &WebGl2RenderingContext) {
+    // the grid is resampled to the new size on the next update
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        self.resolution = resolution;
+        if let Resolution::Scale(scale) = resolution {
+            self.adaptive_budget.set_min_scale(scale);
+        }
+    }
+
+    pub fn set_fit_mode(&mut self, fit_mode: FitMode) { self.fit_mode = fit_mode; }
+
+    // screen pixels (y down) to cells (y up) through the camera, the Transform and wherever the grid is fit on the canvas
+    fn screen_to_grid(&self, position: (f32, f32), screen_size: (i32, i32)) -> (f32, f32) {
+        let world = self.camera_view.screen_to_world(position, screen_size);
+        let local = self.world_matrix.inverse().transform_point3(Vec3::new(world.0, world.1, 0.0));
+        return self.fit_mode.screen_to_grid((local.x, local.y), (self.width, self.height), screen_size);
+    }
+
+    // how many screen pixels wide a cell is drawn
+    fn cell_screen_size(&self, screen_size: (i32, i32)) -> f32 {
+        let display_width = self.fit_mode.display_rect((self.width, self.height), screen_size).2;
+        let scale = self.world_matrix.x_axis.truncate().length();
+        return display_width as f32 / self.width as f32 * scale * self.camera_view.zoom;
+    }
+
+    fn set_feed(&mut self, gl: &WebGl2RenderingContext, feed: f32) {
+        self.feed = feed;
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "F");
+        gl.uniform1f(loc.as_ref(), feed);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_feed(feed);
+        }
+    }
+
+    fn set_kill(&mut self, gl: &WebGl2RenderingContext, kill: f32) {
+        self.kill = kill;
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "K");
+        gl.uniform1f(loc.as_ref(), kill);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_kill(kill);
+        }
+    }
+
+    fn set_diffusion_a(&mut self, gl: &WebGl2RenderingContext, diffusion_a: f32) {
+        self.diffusion_a = diffusion_a;
+        self.stability_changed = true;
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "u_diffusion_a");
+        gl.uniform1f(loc.as_ref(), diffusion_a);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_diffusion_a(diffusion_a);
+        }
+    }
+
+    fn set_diffusion_b(&mut self, gl: &WebGl2RenderingContext, diffusion_b: f32) {
+        self.diffusion_b = diffusion_b;
+        self.stability_changed = true;
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "u_diffusion_b");
+        gl.uniform1f(loc.as_ref(), diffusion_b);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_diffusion_b(diffusion_b);
+        }
+    }
+
+    fn set_delta_t(&mut self, gl: &WebGl2RenderingContext, delta_t: f32) {
+        self.delta_t = delta_t;
+        self.stability_changed = true;
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "u_delta_t");
+        gl.uniform1f(loc.as_ref(), delta_t);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_delta_t(delta_t);
+        }
+    }
+
+    // sets diffusion a, b and the time step and updates their sliders
+    fn set_diffusion(&mut self, gl: &WebGl2RenderingContext, diffusion_a: f32, diffusion_b: f32, delta_t: f32, reaction_diffusion_ui: &ReactionDiffusionUI) {
+        self.set_diffusion_a(gl, diffusion_a);
+        reaction_diffusion_ui.set_diffusion_a_slider_value(diffusion_a as f64);
+        self.set_diffusion_b(gl, diffusion_b);
+        reaction_diffusion_ui.set_diffusion_b_slider_value(diffusion_b as f64);
+        self.set_delta_t(gl, delta_t);
+        reaction_diffusion_ui.set_delta_t_slider_value(delta_t as f64);
+    }
+
+    pub fn set_color_map(&mut self, gl: &WebGl2RenderingContext, color_map: ColorMap) {
+        gl.use_program(Some(&self.reaction_diffusion_render));
+        let loc = gl.get_uniform_location(&self.reaction_diffusion_render, "u_color_stops");
+        gl.uniform4fv_with_f32_array(loc.as_ref(), &color_map.uniform_data());
+        let loc = gl.get_uniform_location(&self.reaction_diffusion_render, "u_color_stop_count");
+        gl.uniform1i(loc.as_ref(), color_map.stops().len() as i32);
+        self.color_map = color_map;
+    }
+
+    fn set_kernel(&mut self, gl: &WebGl2RenderingContext, kernel: Kernel) {
+        self.stability_changed = true;
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "u_kernel");
+        gl.uniform1fv_with_f32_array(loc.as_ref(), &kernel.uniform_data());
+        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "u_kernel_radius");
+        gl.uniform1i(loc.as_ref(), kernel.radius());
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_kernel(kernel.clone());
+        }
+        self.kernel = kernel;
+    }
+
+    fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+        self.stability_changed = true;
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_integrator(integrator);
+        }
+    }
+
+    pub fn set_boundaries(&mut self, gl: &WebGl2RenderingContext, boundaries: Boundaries) {
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_boundary");
+        gl.uniform2i(loc.as_ref(), boundaries.x.id() as i32, boundaries.y.id() as i32);
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_edge_value");
+        gl.uniform2f(loc.as_ref(), boundaries.edge_value.0, boundaries.edge_value.1);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_boundaries(boundaries);
+        }
+        self.boundaries = boundaries;
+    }
+
+    pub fn set_parameter_map(&mut self, gl: &WebGl2RenderingContext, parameter_map: ParameterMap) {
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_parameter_mode");
+        gl.uniform1i(loc.as_ref(), parameter_map.shader_mode());
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_feed_range");
+        gl.uniform2f(loc.as_ref(), parameter_map.feed_range.0, parameter_map.feed_range.1);
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_kill_range");
+        gl.uniform2f(loc.as_ref(), parameter_map.kill_range.0, parameter_map.kill_range.1);
+
+        if let ParameterSource::Image(image) = &parameter_map.source {
+            // the parameter texture lives in unit 1 so it stays bound while the simulation textures swap in unit 0
+            gl.active_texture(WebGl2RenderingContext::TEXTURE1);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.parameter_texture.as_ref().unwrap().as_ref()));
+            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                WebGl2RenderingContext::TEXTURE_2D,
+                0,
+                WebGl2RenderingContext::RGBA as i32,
+                image.width,
+                image.height,
+                0,
+                WebGl2RenderingContext::RGBA,
+                WebGl2RenderingContext::UNSIGNED_BYTE,
+                Some(&image.rgba),
+            ).unwrap();
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+        }
+
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_parameter_map(parameter_map.clone());
+        }
+        self.parameter_map = parameter_map;
+    }
+
+    // resized to the grid if it's a different size
+    pub fn set_mask(&mut self, gl: &WebGl2RenderingContext, mask: Mask) {
+        self.mask = if mask.width() == self.width && mask.height() == self.height { mask } else { mask.resized(self.width, self.height) };
+        self.mask_changed(gl);
+    }
+
+    fn mask_changed(&mut self, gl: &WebGl2RenderingContext) {
+        self.upload_mask(gl);
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.set_mask(self.mask.clone());
+        }
+    }
+
+    // the mask texture lives in unit 4 next to the parameter texture
+    fn upload_mask(&self, gl: &WebGl2RenderingContext) {
+        gl.active_texture(WebGl2RenderingContext::TEXTURE4);
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.mask_texture.as_ref().unwrap().as_ref()));
+        // rows of one byte cells aren't always 4 byte aligned
+        gl.pixel_storei(WebGl2RenderingContext::UNPACK_ALIGNMENT, 1);
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            WebGl2RenderingContext::TEXTURE_2D,
+            0,
+            WebGl2RenderingContext::R8 as i32,
+            self.mask.width(),
+            self.mask.height(),
+            0,
+            WebGl2RenderingContext::RED,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            Some(self.mask.cells()),
+        ).unwrap();
+        gl.pixel_storei(WebGl2RenderingContext::UNPACK_ALIGNMENT, 4);
+        gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+    }
+
+    // used from the next reseed, or when the component is added
+    pub fn set_seed(&mut self, seed: Seed) { self.seed = seed; }
+
+    // starts the field over from the seed. the settings and walls stay
+    pub fn reseed(&mut self, gl: &WebGl2RenderingContext) {
+        self.iteration_count = 0;
+        let cells = self.seed_cells();
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+        upload_cells(gl, &cells, self.width, self.height);
+    }
+
+    // a field made from the seed, also copied into the cpu grid
+    fn seed_cells(&mut self) -> Vec<u16> {
+        let mut cells: Vec<u16> = vec![0; (self.width * self.height * 2) as usize];
+        self.seed.apply(&mut cells, self.width, self.height, &mut rand::thread_rng());
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.cells_mut().copy_from_slice(&cells);
+        }
+        return cells;
+    }
+
+    // paints one frame's stamps into the field
+    pub fn paint(&mut self, gl: &WebGl2RenderingContext, brush: &Brush, stamps: &[Stamp]) {
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.paint(brush, stamps);
+            return;
+        }
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(self.fbo.as_ref().unwrap().as_ref()));
+        gl.viewport(0, 0, self.width, self.height);
+
+        // gather the coverage of every stamp, keeping the most any of them covers a cell
+        gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.brush_coverage_texture.as_ref().unwrap().as_ref()), 0);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
+        gl.enable(WebGl2RenderingContext::BLEND);
+        gl.blend_equation(WebGl2RenderingContext::MAX);
+        gl.blend_func(WebGl2RenderingContext::ONE, WebGl2RenderingContext::ONE);
+
+        gl.bind_vertex_array(self.stamp_vao.as_ref());
+        gl.use_program(Some(&self.brush_stamp));
+
+        let mat = Mat4::IDENTITY;
+        let loc = gl.get_uniform_location(&self.brush_stamp, "u_view");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, mat.as_ref());
+
+        // painted in grid space so it lands in the same cells however the grid is fit to the canvas
+        let mat = Mat4::orthographic_rh_gl(0.0, self.width as f32, 0.0, self.height as f32, -1.0, 1.0);
+        let loc = gl.get_uniform_location(&self.brush_stamp, "u_projection");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, mat.as_ref());
+
+        let loc = gl.get_uniform_location(&self.brush_stamp, "u_radius");
+        gl.uniform1f(loc.as_ref(), brush.radius);
+        let loc = gl.get_uniform_location(&self.brush_stamp, "u_shape");
+        gl.uniform1i(loc.as_ref(), brush.shape.id());
+
+        let stamp_data: Vec<f32> = stamps.iter().flat_map(|stamp| [stamp.center.0, stamp.center.1, stamp.pressure]).collect();
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, self.stamp_buffer.as_ref());
+        unsafe {
+            let stamp_array_buf_view = js_sys::Float32Array::view(&stamp_data);
+
+            gl.buffer_data_with_array_buffer_view(
+                WebGl2RenderingContext::ARRAY_BUFFER,
+                &stamp_array_buf_view,
+                WebGl2RenderingContext::DYNAMIC_DRAW,
+            );
+        }
+
+        // every stamp in one draw
+        gl.draw_elements_instanced_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0, stamps.len() as i32);
+
+        gl.disable(WebGl2RenderingContext::BLEND);
+
+        // integer textures can't be blended, so the field is copied into the output with the brush blended in
+        gl.bind_vertex_array(self.render_texture_vao.as_ref());
+        gl.use_program(Some(&self.brush_apply));
+        let loc = gl.get_uniform_location(&self.brush_apply, "u_chemical");
+        gl.uniform1i(loc.as_ref(), brush.chemical.id());
+        let loc = gl.get_uniform_location(&self.brush_apply, "u_blend_mode");
+        gl.uniform1i(loc.as_ref(), brush.blend_mode.id());
+        let loc = gl.get_uniform_location(&self.brush_apply, "u_strength");
+        gl.uniform1f(loc.as_ref(), brush.strength);
+
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+        gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.output_texture.as_ref().unwrap().as_ref()), 0);
+        gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0);
+
+        std::mem::swap(&mut self.input_texture, &mut self.output_texture);
+    }
+
+    // gives the brush coverage texture, bound in unit 5, empty R8 storage at the current size
+    fn allocate_brush_coverage(&self, gl: &WebGl2RenderingContext) {
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.brush_coverage_texture.as_ref().unwrap().as_ref()));
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            WebGl2RenderingContext::TEXTURE_2D,
+            0,
+            WebGl2RenderingContext::R8 as i32,
+            self.width,
+            self.height,
+            0,
+            WebGl2RenderingContext::RED,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            None,
+        ).unwrap();
+    }
+
+    pub fn apply_preset(&mut self, gl: &WebGl2RenderingContext, preset: &Preset, reaction_diffusion_ui: &ReactionDiffusionUI) {
+        self.set_feed(gl, preset.feed);
+        reaction_diffusion_ui.set_feed_slider_value(preset.feed as f64);
+        self.set_kill(gl, preset.kill);
+        reaction_diffusion_ui.set_kill_slider_value(preset.kill as f64);
+        let kernel = preset.kernel().unwrap_or_default();
+        reaction_diffusion_ui.select_stencil(Stencil::from_kernel(&kernel));
+        self.set_kernel(gl, kernel);
+        self.set_diffusion(
+            gl,
+            preset.diffusion_a.unwrap_or(DEFAULT_DIFFUSION_A),
+            preset.diffusion_b.unwrap_or(DEFAULT_DIFFUSION_B),
+            preset.delta_t.unwrap_or(DEFAULT_DELTA_T),
+            reaction_diffusion_ui,
+        );
+
+        if let Some(color_map) = preset.color_map() {
+            reaction_diffusion_ui.select_color_map(color_map.name());
+            self.set_color_map(gl, color_map);
+        }
+
+        // the seed is only a recommendation, the pattern that's there keeps going under the new parameters
+        if let Some(seed) = preset.seed().filter(|_| reaction_diffusion_ui.preset_seeds()) {
+            self.set_seed(seed);
+            self.reseed(gl);
+        }
+    }
+
+    // reads the current a, b field back from the gpu, or from the cpu grid
+    fn read_cells(&self, gl: &WebGl2RenderingContext) -> Vec<u16> {
+        if let Some(cpu_grid) = self.cpu_grid.as_ref() {
+            return cpu_grid.cells().to_vec();
+        }
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(self.fbo.as_ref().unwrap().as_ref()));
+        gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()), 0);
+
+        // RGBA_INTEGER + UNSIGNED_INT is the only combination webgl always allows for reading integer textures
+        let pixels = js_sys::Uint32Array::new_with_length((self.width * self.height * 4) as u32);
+        gl.read_pixels_with_opt_array_buffer_view(0, 0, self.width, self.height, WebGl2RenderingContext::RGBA_INTEGER, WebGl2RenderingContext::UNSIGNED_INT, Some(&pixels)).unwrap();
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+
+        let pixels = pixels.to_vec();
+        let mut cells = Vec::with_capacity((self.width * self.height * 2) as usize);
+        for pixel in pixels.chunks(4) {
+            cells.push(pixel[0] as u16);
+            cells.push(pixel[1] as u16);
+        }
+        return cells;
+    }
+
+    pub fn export_state(&self, gl: &WebGl2RenderingContext) -> SimulationState {
+        return SimulationState {
+            width: self.width,
+            height: self.height,
+            feed: self.feed,
+            kill: self.kill,
+            diffusion_a: self.diffusion_a,
+            diffusion_b: self.diffusion_b,
+            delta_t: self.delta_t,
+            kernel: self.kernel.weights().to_vec(),
+            integrator: self.integrator,
+            boundaries: self.boundaries,
+            mask: if self.mask.has_walls() { Some(self.mask.cells().to_vec()) } else { None },
+            iterations: self.iteration_count,
+            cells: self.read_cells(gl),
+        };
+    }
+
+    // a png of the field, scale times larger than the simulation
+    pub fn capture_png(&self, gl: &WebGl2RenderingContext, source: SnapshotSource, scale: u32) -> Result<Vec<u8>, String> {
+        let rgba = match source {
+            SnapshotSource::RenderTexture => {
+                gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(self.fbo.as_ref().unwrap().as_ref()));
+                gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.render_texture.as_ref().unwrap().as_ref()), 0);
+
+                let mut pixels = vec![0u8; (self.width * self.height * 4) as usize];
+                gl.read_pixels_with_opt_u8_array(0, 0, self.width, self.height, WebGl2RenderingContext::RGBA, WebGl2RenderingContext::UNSIGNED_BYTE, Some(&mut pixels)).map_err(|e| format!("{:?}", e))?;
+                gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+
+                flip_rows(&pixels, self.width, self.height)
+            }
+            SnapshotSource::Cells => cells_to_rgba(&self.read_cells(gl), self.width, self.height, &self.color_map, Some(&self.mask)),
+        };
+
+        let scale = scale.max(1);
+        let rgba = upscale_rgba(&rgba, self.width, self.height, scale);
+        return encode_png(self.width as u32 * scale, self.height as u32 * scale, &rgba);
+    }
+
+    // loads a state into the running simulation, resampling it if it was saved at a different size
+    pub fn import_state(&mut self, gl: &WebGl2RenderingContext, state: &SimulationState, reaction_diffusion_ui: &ReactionDiffusionUI) -> Result<(), String> {
+        let kernel = Kernel::new(state.kernel.clone())?;
+        let resampled;
+        let state = if state.width != self.width || state.height != self.height {
+            resampled = state.resampled(self.width, self.height);
+            &resampled
+        } else {
+            state
+        };
+
+        self.set_feed(gl, state.feed);
+        reaction_diffusion_ui.set_feed_slider_value(state.feed as f64);
+        self.set_kill(gl, state.kill);
+        reaction_diffusion_ui.set_kill_slider_value(state.kill as f64);
+        reaction_diffusion_ui.select_stencil(Stencil::from_kernel(&kernel));
+        self.set_kernel(gl, kernel);
+        reaction_diffusion_ui.select_integrator(state.integrator);
+        self.set_integrator(state.integrator);
+        self.set_diffusion(gl, state.diffusion_a, state.diffusion_b, state.delta_t, reaction_diffusion_ui);
+        reaction_diffusion_ui.select_boundaries(&state.boundaries);
+        self.set_boundaries(gl, state.boundaries);
+        self.iteration_count = state.iterations;
+
+        let mask = match state.mask.as_ref() {
+            Some(cells) => Mask::from_cells(state.width, state.height, cells.clone())?,
+            None => Mask::new(state.width, state.height),
+        };
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.load_state(state)?;
+        }
+        self.set_mask(gl, mask);
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+        upload_cells(gl, &state.cells, self.width, self.height);
+
+        return Ok(());
+    }
+
+    fn clear(&mut self, gl: &WebGl2RenderingContext) {
+        self.iteration_count = 0;
+
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.clear();
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+            upload_cells(gl, cpu_grid.cells(), self.width, self.height);
+            return;
+        }
+
         gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(self.fbo.as_ref().unwrap().as_ref()));
         gl.viewport(0, 0, self.width, self.height);
         gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()), 0);
@@ -82,8 +650,25 @@ impl ReactionDiffusion {
     }
 
     fn resize(&mut self, gl: &WebGl2RenderingContext, width: i32, height: i32) {
-        self.width = (width as f32 / SIMULATION_SCALE).round() as i32;
-        self.height = (height as f32 / SIMULATION_SCALE).round() as i32;
+        self.width = width;
+        self.height = height;
+        // their points are in cells of the old size
+        self.strokes.clear();
+
+        // the cpu grid resizes its own copy the same way
+        self.mask = self.mask.resized(width, height);
+        self.upload_mask(gl);
+
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            cpu_grid.resize(self.width, self.height);
+            self.resize_cpu_textures(gl);
+            return;
+        }
+
+        self.resize_stage_textures(gl);
+        gl.active_texture(WebGl2RenderingContext::TEXTURE5);
+        self.allocate_brush_coverage(gl);
+        gl.active_texture(WebGl2RenderingContext::TEXTURE0);
 
         gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.output_texture.as_ref().unwrap().as_ref()));
         gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
@@ -137,6 +722,77 @@ impl ReactionDiffusion {
             None,
         ).unwrap();
     }
+
+    // one step of the integrator with a draw for each stage. the fbo, vao and reaction_diffusion program need to be set up already.
+    // leaves the new state in input_texture
+    fn gpu_step(&mut self, gl: &WebGl2RenderingContext) {
+        let stages = self.integrator.stages();
+        for (i, stage) in stages.iter().enumerate() {
+            let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_next_stage_offset");
+            gl.uniform1f(loc.as_ref(), stage.next_stage_offset);
+            let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_accumulator_weight");
+            gl.uniform1f(loc.as_ref(), stage.accumulator_weight);
+
+            // the first stage starts from the state at the start of the step for everything
+            let (current, accumulator) = if i == 0 {
+                (&self.input_texture, &self.input_texture)
+            } else {
+                (&self.stage_texture, &self.accumulator_texture)
+            };
+            gl.active_texture(WebGl2RenderingContext::TEXTURE2);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+            gl.active_texture(WebGl2RenderingContext::TEXTURE3);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(accumulator.as_ref().unwrap().as_ref()));
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(current.as_ref().unwrap().as_ref()));
+
+            gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.output_texture.as_ref().unwrap().as_ref()), 0);
+            if i == stages.len() - 1 {
+                gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT1, WebGl2RenderingContext::TEXTURE_2D, None, 0);
+                gl.draw_buffers(&js_sys::Array::of1(&WebGl2RenderingContext::COLOR_ATTACHMENT0.into()));
+            } else {
+                gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT1, WebGl2RenderingContext::TEXTURE_2D, Some(self.next_stage_texture.as_ref().unwrap().as_ref()), 0);
+                gl.draw_buffers(&js_sys::Array::of2(&WebGl2RenderingContext::COLOR_ATTACHMENT0.into(), &WebGl2RenderingContext::COLOR_ATTACHMENT1.into()));
+            }
+
+            gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0);
+
+            std::mem::swap(&mut self.accumulator_texture, &mut self.output_texture);
+            std::mem::swap(&mut self.stage_texture, &mut self.next_stage_texture);
+        }
+
+        // the last accumulator is the new state
+        std::mem::swap(&mut self.input_texture, &mut self.accumulator_texture);
+    }
+
+    // (re)allocates the scratch textures at the current size
+    fn resize_stage_textures(&self, gl: &WebGl2RenderingContext) {
+        for texture in [&self.accumulator_texture, &self.stage_texture, &self.next_stage_texture] {
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(texture.as_ref().unwrap().as_ref()));
+            allocate_cells(gl, self.width, self.height);
+        }
+    }
+
+    fn resize_cpu_textures(&self, gl: &WebGl2RenderingContext) {
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.output_texture.as_ref().unwrap().as_ref()));
+        upload_cells(gl, self.cpu_grid.as_ref().unwrap().cells(), self.width, self.height);
+
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+        upload_cells(gl, self.cpu_grid.as_ref().unwrap().cells(), self.width, self.height);
+
+        gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.render_texture.as_ref().unwrap().as_ref()));
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+            WebGl2RenderingContext::TEXTURE_2D,
+            0,
+            WebGl2RenderingContext::RGBA as i32,
+            self.width,
+            self.height,
+            0,
+            WebGl2RenderingContext::RGBA,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            None,
+        ).unwrap();
+    }
 }
 
 impl Component for ReactionDiffusion {
@@ -144,47 +800,31 @@ impl Component for ReactionDiffusion {
         let gl = app.gl();
 
         let vertices = [-0.5, 0.5, 0.0, 0.5, 0.5, 0.0, 0.5, -0.5, 0.0, -0.5, -0.5, 0.0];
-        self.quad_vao = Some(init_quad(&gl, &self.unlit_color_on_rg16ui, &vertices));
+        self.stamp_vao = Some(init_quad(&gl, &self.brush_stamp, &vertices));
+        self.stamp_buffer = gl.create_buffer();
+        let stamp_attribute_location = gl.get_attrib_location(&self.brush_stamp, "a_stamp") as u32;
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, self.stamp_buffer.as_ref());
+        gl.vertex_attrib_pointer_with_i32(stamp_attribute_location, 3, WebGl2RenderingContext::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(stamp_attribute_location);
+        gl.vertex_attrib_divisor(stamp_attribute_location, 1);
 
         let vertices = [-1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, -1.0, 0.0, -1.0, -1.0, 0.0];
         self.render_texture_vao = Some(init_quad(&gl, &self.basic_bicubic, &vertices));
 
         self.indices_count = 6;
 
-        gl.use_program(Some(&self.reaction_diffusion));
-        let u_kernel_loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "u_kernel");
-        let kernel: [f32; 9] = [
-            0.05, 0.2, 0.05,
-            0.2, -1.0, 0.2,
-            0.05, 0.2, 0.05
-        ];
-        gl.uniform1fv_with_f32_array(u_kernel_loc.as_ref(), &kernel);
-
-        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "F");
-        gl.uniform1f(loc.as_ref(), FEED_START);
-
-        let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "K");
-        gl.uniform1f(loc.as_ref(), KILL_START);
+        self.set_kernel(gl, self.kernel.clone());
+        self.set_feed(gl, self.feed);
+        self.set_kill(gl, self.kill);
+        self.set_diffusion_a(gl, self.diffusion_a);
+        self.set_diffusion_b(gl, self.diffusion_b);
+        self.set_delta_t(gl, self.delta_t);
+        self.set_boundaries(gl, self.boundaries);
+        self.set_color_map(gl, self.color_map.clone());
 
         self.input_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::REPEAT).unwrap()));
-        let mut cells: Vec<u16> = vec![0; (self.width * self.height * 2) as usize];
-        init_cells(&mut cells, self.width, self.height);
-        unsafe {
-            let view = js_sys::Uint16Array::view(&cells);
-
-            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_array_buffer_view_and_src_offset(
-                WebGl2RenderingContext::TEXTURE_2D,
-                0,
-                WebGl2RenderingContext::RG16UI as i32,
-                self.width,
-                self.height,
-                0,
-                WebGl2RenderingContext::RG_INTEGER,
-                WebGl2RenderingContext::UNSIGNED_SHORT,
-                &view,
-                0,
-            ).unwrap();
-        }
+        let cells = self.seed_cells();
+        upload_cells(&gl, &cells, self.width, self.height);
 
         self.output_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::REPEAT).unwrap()));
         gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
@@ -212,116 +852,345 @@ impl Component for ReactionDiffusion {
             None,
         ).unwrap();
 
+        gl.active_texture(WebGl2RenderingContext::TEXTURE1);
+        self.parameter_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::CLAMP_TO_EDGE).unwrap()));
+        gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_parameter_texture");
+        gl.uniform1i(loc.as_ref(), 1);
+        self.set_parameter_map(gl, self.parameter_map.clone());
+
+        self.accumulator_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::REPEAT).unwrap()));
+        self.stage_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::REPEAT).unwrap()));
+        self.next_stage_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::REPEAT).unwrap()));
+        self.resize_stage_textures(gl);
+        gl.use_program(Some(&self.reaction_diffusion));
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_start_texture");
+        gl.uniform1i(loc.as_ref(), 2);
+        let loc = gl.get_uniform_location(&self.reaction_diffusion, "u_accumulator_texture");
+        gl.uniform1i(loc.as_ref(), 3);
+
+        gl.active_texture(WebGl2RenderingContext::TEXTURE4);
+        self.mask_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::CLAMP_TO_EDGE).unwrap()));
+        gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+        for program in [&self.reaction_diffusion, &self.reaction_diffusion_render] {
+            gl.use_program(Some(program));
+            let loc = gl.get_uniform_location(program, "u_mask_texture");
+            gl.uniform1i(loc.as_ref(), 4);
+        }
+        self.upload_mask(gl);
+
+        gl.active_texture(WebGl2RenderingContext::TEXTURE5);
+        self.brush_coverage_texture = Some(Box::new(create_and_bind_texture(&gl, WebGl2RenderingContext::NEAREST, WebGl2RenderingContext::CLAMP_TO_EDGE).unwrap()));
+        self.allocate_brush_coverage(gl);
+        gl.active_texture(WebGl2RenderingContext::TEXTURE0);
+        gl.use_program(Some(&self.brush_apply));
+        let loc = gl.get_uniform_location(&self.brush_apply, "u_coverage_texture");
+        gl.uniform1i(loc.as_ref(), 5);
+
         self.fbo = Some(Box::new(gl.create_framebuffer().unwrap()));
     }
 
     fn on_first_update(&mut self, game_object: &mut GameObject, app: &App) {
-        self.reaction_diffusion_ui = game_object.get_component::<ReactionDiffusionUI>();
+        self.reaction_diffusion_ui = app.find_component_in_scene::<ReactionDiffusionUI>();
+        self.camera = app.find_component_in_scene::<Camera>();
     }
 
     fn on_update(&mut self, game_object: &mut GameObject, app: &App) {
         let gl = app.gl();
 
-        if self.last_screen_size == (-1, -1) {
-            self.last_screen_size = app.screen().size();
-        }
-        if app.screen().size() != self.last_screen_size {
-            self.resize(gl, app.screen().width(), app.screen().height());
+        // everything it does is driven by the ui, so without one it waits until one is added
+        if self.reaction_diffusion_ui.as_ref().and_then(|ui| ui.upgrade()).is_none() {
+            self.reaction_diffusion_ui = app.find_component_in_scene::<ReactionDiffusionUI>();
         }
-        self.last_screen_size = app.screen().size();
-
-        let reaction_diffusion_ui = self.reaction_diffusion_ui.as_ref().unwrap().upgrade().unwrap();
+        let reaction_diffusion_ui = match self.reaction_diffusion_ui.as_ref().and_then(|ui| ui.upgrade()) {
+            Some(reaction_diffusion_ui) => reaction_diffusion_ui,
+            None => return,
+        };
         let reaction_diffusion_ui = reaction_diffusion_ui.borrow();
 
+        if let Some(camera) = self.camera.as_ref().and_then(|camera| camera.upgrade()) {
+            if reaction_diffusion_ui.reset_view_button() {
+                camera.borrow_mut().reset();
+            }
+            self.camera_view = camera.borrow().view();
+        }
+        self.world_matrix = Transform::world_matrix_of(game_object);
+
         if reaction_diffusion_ui.clear_button() {
             self.clear(gl);
         }
 
-        if reaction_diffusion_ui.random_preset_button() {
-            gl.use_program(Some(&self.reaction_diffusion));
+        let mut reseed = reaction_diffusion_ui.reseed_button();
+        if let Some(bytes) = reaction_diffusion_ui.take_loaded_seed_image() {
+            match Seed::image_from_png(&bytes) {
+                Ok(seed) => {
+                    self.seed_image = Some(seed);
+                    reaction_diffusion_ui.set_seed_mode(SeedMode::Image);
+                    reseed = true;
+                }
+                Err(message) => console_log!("could not load seed image: {}", message),
+            }
+        }
 
-            let mut i = self.current_feed_kill_pair_i;
-            while i == self.current_feed_kill_pair_i {
-                i = rand::thread_rng().gen_range(0..(FEED_KILL_PAIRS.len() / 2)) * 2;
+        if reseed {
+            let seed = match (reaction_diffusion_ui.seed_mode(), self.seed_image.as_ref()) {
+                (SeedMode::CenterSquare, _) => Seed::default(),
+                (SeedMode::CenterCircle, _) => Seed::Center { shape: SeedShape::Circle, radius: 10 },
+                (SeedMode::Noise, _) => Seed::Noise { density: reaction_diffusion_ui.seed_density() },
+                (SeedMode::Circles, _) => Seed::Circles { count: reaction_diffusion_ui.seed_count(), radius: 10 },
+                (SeedMode::Image, Some(image)) => image.clone(),
+                (SeedMode::Image, None) => {
+                    console_log!("load a seed image first");
+                    Seed::default()
+                }
+                (SeedMode::Text, _) => Seed::Text(reaction_diffusion_ui.seed_text()),
+            };
+            self.set_seed(seed);
+            self.reseed(gl);
+        }
+
+        if reaction_diffusion_ui.clear_walls_button() {
+            self.mask.clear();
+            self.mask_changed(gl);
+        }
+
+        if let Some(bytes) = reaction_diffusion_ui.take_loaded_mask_image() {
+            match Mask::from_png(&bytes, self.width, self.height) {
+                Ok(mask) => self.set_mask(gl, mask),
+                Err(message) => console_log!("could not load walls: {}", message),
             }
+        }
+
+        if reaction_diffusion_ui.resolution_changed() {
+            let resolution = reaction_diffusion_ui.resolution();
+            self.set_resolution(resolution);
+            reaction_diffusion_ui.select_resolution(resolution);
+        }
+
+        if reaction_diffusion_ui.fit_mode_changed() {
+            self.set_fit_mode(reaction_diffusion_ui.fit_mode());
+        }
 
-            let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "F");
-            gl.uniform1f(loc.as_ref(), FEED_KILL_PAIRS[i]);
-            reaction_diffusion_ui.set_feed_slider_value(FEED_KILL_PAIRS[i] as f64);
+        if reaction_diffusion_ui.random_preset_button() {
+            let current = reaction_diffusion_ui.preset_index();
+            let mut i = current;
+            while i == current && reaction_diffusion_ui.presets().len() > 1 {
+                i = rand::thread_rng().gen_range(0..reaction_diffusion_ui.presets().len());
+            }
+            reaction_diffusion_ui.set_preset_index(i);
+        }
 
-            let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "K");
-            gl.uniform1f(loc.as_ref(), FEED_KILL_PAIRS[i + 1]);
-            reaction_diffusion_ui.set_kill_slider_value(FEED_KILL_PAIRS[i + 1] as f64);
+        if reaction_diffusion_ui.preset_changed() {
+            let preset = reaction_diffusion_ui.presets()[reaction_diffusion_ui.preset_index()].clone();
+            self.apply_preset(gl, &preset, &reaction_diffusion_ui);
         }
 
         if reaction_diffusion_ui.feed_slider_value_changed() {
-            gl.use_program(Some(&self.reaction_diffusion));
-            let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "F");
-            gl.uniform1f(loc.as_ref(), reaction_diffusion_ui.feed_slider_value() as f32);
+            self.set_feed(gl, reaction_diffusion_ui.feed_slider_value() as f32);
         }
 
         if reaction_diffusion_ui.kill_slider_value_changed() {
-            gl.use_program(Some(&self.reaction_diffusion));
-            let loc = gl.get_uniform_location(self.reaction_diffusion.as_ref(), "K");
-            gl.uniform1f(loc.as_ref(), reaction_diffusion_ui.kill_slider_value() as f32);
+            self.set_kill(gl, reaction_diffusion_ui.kill_slider_value() as f32);
         }
 
-        if app.input().get_button_down(Left) || app.input().get_button(Left) && app.input().mouse_delta_position() != (0, 0) {
-            gl.bind_vertex_array(self.quad_vao.as_ref());
-            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(self.fbo.as_ref().unwrap().as_ref()));
-            gl.viewport(0, 0, self.width, self.height);
-            gl.use_program(Some(&self.unlit_color_on_rg16ui));
+        if reaction_diffusion_ui.diffusion_a_slider_value_changed() {
+            self.set_diffusion_a(gl, reaction_diffusion_ui.diffusion_a_slider_value() as f32);
+        }
 
-            let mouse_position = app.input().mouse_position();
+        if reaction_diffusion_ui.diffusion_b_slider_value_changed() {
+            self.set_diffusion_b(gl, reaction_diffusion_ui.diffusion_b_slider_value() as f32);
+        }
+
+        if reaction_diffusion_ui.delta_t_slider_value_changed() {
+            self.set_delta_t(gl, reaction_diffusion_ui.delta_t_slider_value() as f32);
+        }
+
+        if reaction_diffusion_ui.stencil_changed() {
+            self.set_kernel(gl, reaction_diffusion_ui.stencil().kernel());
+        }
 
-            let mat = Mat4::IDENTITY;
-            let loc = gl.get_uniform_location(&self.unlit_color_on_rg16ui, "u_view");
-            gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, mat.as_ref());
+        if reaction_diffusion_ui.integrator_changed() {
+            self.set_integrator(reaction_diffusion_ui.integrator());
+        }
+
+        if reaction_diffusion_ui.boundaries_changed() {
+            self.set_boundaries(gl, reaction_diffusion_ui.boundaries());
+        }
+
+        if self.stability_changed {
+            reaction_diffusion_ui.set_stability_warning(stability_warning(&self.kernel, self.integrator, self.diffusion_a, self.diffusion_b, self.delta_t).as_deref());
+            self.stability_changed = false;
+        }
 
-            let mat = Mat4::orthographic_rh_gl(0.0, app.screen().width() as f32, app.screen().height() as f32, 0.0, -1.0, 1.0);
-            let loc = gl.get_uniform_location(&self.unlit_color_on_rg16ui, "u_projection");
-            gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, mat.as_ref());
+        if reaction_diffusion_ui.color_map_changed() {
+            self.set_color_map(gl, reaction_diffusion_ui.color_map());
+        }
 
-            gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()), 0);
+        if reaction_diffusion_ui.save_state_button() {
+            let state = self.export_state(gl);
+            download_bytes(app.document(), &format!("reaction-diffusion.{}", FILE_EXTENSION), &state.to_bytes(), "application/octet-stream");
+        }
 
-            if self.last_mouse_position == (-1, -1) {
-                self.last_mouse_position = mouse_position;
+        if reaction_diffusion_ui.save_png_button() {
+            match self.capture_png(gl, reaction_diffusion_ui.png_source(), reaction_diffusion_ui.png_scale()) {
+                Ok(bytes) => download_bytes(app.document(), "reaction-diffusion.png", &bytes, "image/png"),
+                Err(message) => console_log!("could not save png: {}", message),
             }
+        }
 
-            let distance = distance(self.last_mouse_position, mouse_position);
-            let num_circles = distance.round().clamp(1.0, f32::MAX) as i32;
+        if let Some(bytes) = reaction_diffusion_ui.take_loaded_state_file() {
+            let result = SimulationState::from_bytes(&bytes).and_then(|state| self.import_state(gl, &state, &reaction_diffusion_ui));
+            if let Err(message) = result {
+                console_log!("could not load state file: {}", message);
+            }
+        }
 
-            for i in 0..=num_circles {
-                let t = i as f32 / num_circles as f32;
-                let x = lerp(self.last_mouse_position.0 as f32, mouse_position.0 as f32, t);
-                let y = lerp(self.last_mouse_position.1 as f32, mouse_position.1 as f32, t);
+        let mut parameter_image_loaded = false;
+        if let Some(bytes) = reaction_diffusion_ui.take_loaded_parameter_image() {
+            match ParameterImage::from_png(&bytes) {
+                Ok(image) => {
+                    reaction_diffusion_ui.select_parameter_source(&ParameterSource::Image(image.clone()));
+                    self.parameter_image = Some(image);
+                    parameter_image_loaded = true;
+                }
+                Err(message) => console_log!("could not load parameter image: {}", message),
+            }
+        }
+
+        if parameter_image_loaded || reaction_diffusion_ui.parameter_source_changed() || reaction_diffusion_ui.parameter_ranges_changed() {
+            let feed_range = reaction_diffusion_ui.feed_range();
+            let kill_range = reaction_diffusion_ui.kill_range();
+            let parameter_map = match reaction_diffusion_ui.parameter_source(self.parameter_image.as_ref()) {
+                Some(source) => ParameterMap { source, feed_range, kill_range },
+                None => {
+                    console_log!("load a parameter image first");
+                    ParameterMap::uniform()
+                }
+            };
+            self.set_parameter_map(gl, parameter_map);
+        }
+
+        if let ParameterSource::Uniform = self.parameter_map.source {
+            reaction_diffusion_ui.set_parameter_readout(None);
+        } else {
+            // the cell under the mouse
+            let mouse_position = app.input().mouse_position();
+            let (x, y) = self.screen_to_grid((mouse_position.0 as f32, mouse_position.1 as f32), app.screen().size());
+            let x = (x.floor() as i32).clamp(0, self.width - 1);
+            let y = (y.floor() as i32).clamp(0, self.height - 1);
+            reaction_diffusion_ui.set_parameter_readout(Some(self.parameter_map.feed_kill(x, y, self.width, self.height, self.feed, self.kill)));
+        }
 
-                let mat = Mat4::from_scale_rotation_translation(Vec3::new(10.0, 10.0, 1.0), Quat::IDENTITY, Vec3::new(x, y, 0.0));
-                let loc = gl.get_uniform_location(&self.unlit_color_on_rg16ui, "u_model");
-                gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, mat.as_ref());
+        // every finger, pen or mouse button held down paints its own stroke, except two fingers move the camera
+        let two_finger_gesture = app.input().pointers_down().filter(|pointer| pointer.kind == PointerKind::Touch).count() == 2;
+        let painting: Vec<Pointer> = app.input().pointers_down()
+            .filter(|pointer| pointer.buttons & 1 != 0 && !(two_finger_gesture && pointer.kind == PointerKind::Touch))
+            .copied()
+            .collect();
+        self.strokes.retain(|(id, _)| painting.iter().any(|pointer| pointer.id == *id));
+        if !painting.is_empty() {
+            // the brush size is in screen pixels, so zooming in paints finer
+            let mut brush = reaction_diffusion_ui.brush();
+            brush.radius /= self.cell_screen_size(app.screen().size());
+
+            let mut stamps = Vec::new();
+            for pointer in painting.iter() {
+                let position = self.screen_to_grid((pointer.position.0 as f32, pointer.position.1 as f32), app.screen().size());
+                let i = match self.strokes.iter().position(|(id, _)| *id == pointer.id) {
+                    Some(i) => i,
+                    None => {
+                        self.strokes.push((pointer.id, Stroke::new()));
+                        self.strokes.len() - 1
+                    }
+                };
+                stamps.extend(self.strokes[i].1.add_point(position, pointer.pressure, brush.radius));
+            }
 
-                gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0);
+            let paint_mode = reaction_diffusion_ui.paint_mode();
+            if !stamps.is_empty() && paint_mode != PaintMode::Brush {
+                for stamp in stamps.iter() {
+                    self.mask.paint_circle(stamp.center, brush.radius, paint_mode == PaintMode::Wall);
+                }
+                self.mask_changed(gl);
+            } else if !stamps.is_empty() {
+                self.paint(gl, &brush, &stamps);
             }
+        }
 
-            self.last_mouse_position = mouse_position;
+        if reaction_diffusion_ui.speed_slider_value_changed() {
+            self.fixed_timestep.set_iterations_per_second(reaction_diffusion_ui.speed_slider_value() as f32);
+        }
+
+        let adaptive = reaction_diffusion_ui.adaptive();
+        if adaptive {
+            if self.adaptive_budget.target_fps() != reaction_diffusion_ui.target_fps() {
+                self.adaptive_budget.set_target_fps(reaction_diffusion_ui.target_fps());
+            }
+            self.adaptive_budget.set_adapt_scale(reaction_diffusion_ui.adapt_resolution());
+        }
+
+        // the adaptive budget only changes a scaled resolution, a fixed grid size stays what it was set to
+        let resolution = match self.resolution {
+            Resolution::Scale(_) if adaptive && self.adaptive_budget.adapt_scale() => Resolution::Scale(self.adaptive_budget.scale()),
+            resolution => resolution,
+        };
+        let grid_size = resolution.grid_size(app.screen().size());
+        if grid_size != (self.width, self.height) {
+            self.resize(gl, grid_size.0, grid_size.1);
+        }
+
+        // the adaptive budget scales the speed and caps the iterations, they still go through the fixed timestep so
+        // the speed doesn't change with the frame rate
+        let max_iterations = if adaptive { self.adaptive_budget.iterations().min(MAX_ITERATIONS_PER_FRAME) } else { MAX_ITERATIONS_PER_FRAME };
+        self.fixed_timestep.set_max_iterations_per_frame(max_iterations);
+        let iterations = if reaction_diffusion_ui.paused() {
+            self.fixed_timestep.reset();
+            if reaction_diffusion_ui.step_button() { 1 } else { 0 }
         } else {
-            self.last_mouse_position = (-1, -1);
+            let mut speed = if reaction_diffusion_ui.fast_forward() { FAST_FORWARD_MULTIPLIER } else { 1.0 };
+            if adaptive {
+                speed *= self.adaptive_budget.speed();
+            }
+            self.fixed_timestep.iterations(app.time().delta_time() * speed)
+        };
+
+        if adaptive && !reaction_diffusion_ui.paused() {
+            self.adaptive_budget.update(app.time().unscaled_time(), app.time().delta_time(), iterations);
+        }
+        if adaptive {
+            let status = format!("{:.2}x speed, at most {} iterations/frame\n{}x{} cells\n{:.0} fps", self.adaptive_budget.speed(), self.adaptive_budget.iterations(), self.width, self.height, self.adaptive_budget.fps());
+            reaction_diffusion_ui.set_budget_status(Some(&status));
+        } else {
+            reaction_diffusion_ui.set_budget_status(None);
         }
 
         // do the reaction diffusion with a shader for the computation
-        let iterations = 15;
         gl.bind_vertex_array(self.render_texture_vao.as_ref());
         gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(self.fbo.as_ref().unwrap().as_ref()));
         gl.viewport(0, 0, self.width, self.height);
         gl.use_program(Some(&self.reaction_diffusion));
-        for _ in 0..iterations {
+        if let Some(cpu_grid) = self.cpu_grid.as_mut() {
+            for _ in 0..iterations {
+                cpu_grid.step();
+            }
+            self.iteration_count += iterations as u64;
             gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(self.input_texture.as_ref().unwrap().as_ref()));
+            upload_cells(gl, cpu_grid.cells(), self.width, self.height);
+        } else {
+            self.iteration_count += iterations as u64;
+            for _ in 0..iterations {
+                self.gpu_step(gl);
+            }
 
-            gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT0, WebGl2RenderingContext::TEXTURE_2D, Some(self.output_texture.as_ref().unwrap().as_ref()), 0);
-
-            gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0);
-
-            std::mem::swap(&mut self.input_texture, &mut self.output_texture);
+            // so the textures can be attached again without a feedback loop
+            gl.framebuffer_texture_2d(WebGl2RenderingContext::FRAMEBUFFER, WebGl2RenderingContext::COLOR_ATTACHMENT1, WebGl2RenderingContext::TEXTURE_2D, None, 0);
+            gl.draw_buffers(&js_sys::Array::of1(&WebGl2RenderingContext::COLOR_ATTACHMENT0.into()));
+            for unit in [WebGl2RenderingContext::TEXTURE2, WebGl2RenderingContext::TEXTURE3] {
+                gl.active_texture(unit);
+                gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+            }
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0);
         }
 
         // rerender special texture into a regular RGBA UNSIGNED_BYTE texture
@@ -339,36 +1208,68 @@ impl Component for ReactionDiffusion {
         gl.viewport(0, 0, app.screen().width(), app.screen().height());
     }
 
-    fn draw(&mut self, game_object: &mut GameObject, app: &App) {
+    fn draw(&mut self, game_object: &mut GameObject, app: &App, view: &Mat4, projection: &Mat4) {
         let gl = app.gl();
+        let screen_size = app.screen().size();
+
+        // the quad is -1 to 1 with y up, moved onto where the grid is fit on the screen without the camera, with y down
+        let (x, y, width, height) = self.fit_mode.display_rect((self.width, self.height), screen_size);
+        let top = (screen_size.1 - y - height) as f32;
+        let fit = Mat4::from_translation(Vec3::new(x as f32 + width as f32 * 0.5, top + height as f32 * 0.5, 0.0))
+            * Mat4::from_scale(Vec3::new(width as f32 * 0.5, -height as f32 * 0.5, 1.0));
+        let model = Transform::world_matrix_of(game_object) * fit;
 
+        gl.viewport(0, 0, app.screen().width(), app.screen().height());
         gl.bind_vertex_array(self.render_texture_vao.as_ref());
         gl.use_program(Some(&self.basic_bicubic));
+        let loc = gl.get_uniform_location(&self.basic_bicubic, "u_model");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, model.as_ref());
+        let loc = gl.get_uniform_location(&self.basic_bicubic, "u_view");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, view.as_ref());
+        let loc = gl.get_uniform_location(&self.basic_bicubic, "u_projection");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, projection.as_ref());
+        // zoomed in far enough to see single cells, they're drawn as sharp squares instead of smoothed over
+        let loc = gl.get_uniform_location(&self.basic_bicubic, "u_pixelated");
+        gl.uniform1i(loc.as_ref(), (self.cell_screen_size(screen_size) >= PIXELATED_CELL_SIZE) as i32);
 
         gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0);
-
-        self.reaction_diffusion_ui.as_ref().unwrap().upgrade().unwrap().borrow_mut().draw(game_object, app);
     }
-}
-
-fn u16float_to_float(value: u16) -> f32 {
-    return value as f32 / u16::MAX as f32;
-}
 
-fn float_to_u16float(value: f32) -> u16 {
-    return (value * u16::MAX as f32).round() as u16;
-}
+    fn on_destroy(&mut self, game_object: &mut GameObject, app: &App) {
+        let gl = app.gl();
 
-fn cell_xy_to_index(x: i32, y: i32, width: i32, height: i32) -> usize {
-    let mut x = x;
-    let mut y = y;
-    if x < 0 {
-        x += width
-    }
-    if y < 0 {
-        y += height;
+        for texture in [
+            self.input_texture.take(),
+            self.output_texture.take(),
+            self.accumulator_texture.take(),
+            self.stage_texture.take(),
+            self.next_stage_texture.take(),
+            self.render_texture.take(),
+            self.parameter_texture.take(),
+            self.mask_texture.take(),
+            self.brush_coverage_texture.take(),
+        ] {
+            gl.delete_texture(texture.as_deref());
+        }
+        gl.delete_framebuffer(self.fbo.take().as_deref());
+
+        // init_quad doesn't keep its buffers, the browser frees them once the vaos are gone
+        gl.delete_vertex_array(self.stamp_vao.take().as_ref());
+        gl.delete_vertex_array(self.render_texture_vao.take().as_ref());
+        gl.delete_buffer(self.stamp_buffer.take().as_ref());
+
+        for program in [
+            &self.basic_bicubic,
+            &self.reaction_diffusion,
+            &self.reaction_diffusion_render,
+            &self.basic_rg16ui,
+            &self.brush_stamp,
+            &self.brush_apply,
+            &self.basic_color_on_rg16_ui,
+        ] {
+            gl.delete_program(Some(program));
+        }
     }
-    return (((x % width) + (y % height) * width) * 2) as usize;
 }
 
 fn create_and_bind_texture(gl: &WebGl2RenderingContext, filter_mode: u32, wrap_mode: u32) -> Option<WebGlTexture> {
@@ -442,16 +1343,37 @@ fn init_quad(gl: &WebGl2RenderingContext, program: &WebGlProgram, vertices: &[f3
     return vao.unwrap();
 }
 
-fn init_cells(cells: &mut Vec<u16>, width: i32, height: i32) {
-    for i in (0..cells.len()).step_by(2) {
-        cells[i] = float_to_u16float(1.0);
-        cells[i + 1] = float_to_u16float(0.0);
-    }
+// gives the currently bound texture empty RG16UI storage
+fn allocate_cells(gl: &WebGl2RenderingContext, width: i32, height: i32) {
+    gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+        WebGl2RenderingContext::TEXTURE_2D,
+        0,
+        WebGl2RenderingContext::RG16UI as i32,
+        width,
+        height,
+        0,
+        WebGl2RenderingContext::RG_INTEGER,
+        WebGl2RenderingContext::UNSIGNED_SHORT,
+        None,
+    ).unwrap();
+}
 
-    for x in (width / 2 - 10)..(width / 2 + 10) {
-        for y in (height / 2 - 10)..(height / 2 + 10) {
-            let i = cell_xy_to_index(x, y, width, height);
-            cells[i + 1] = float_to_u16float(1.0);
-        }
+// uploads interleaved a, b pairs into the currently bound RG16UI texture
+fn upload_cells(gl: &WebGl2RenderingContext, cells: &[u16], width: i32, height: i32) {
+    unsafe {
+        let view = js_sys::Uint16Array::view(cells);
+
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_array_buffer_view_and_src_offset(
+            WebGl2RenderingContext::TEXTURE_2D,
+            0,
+            WebGl2RenderingContext::RG16UI as i32,
+            width,
+            height,
+            0,
+            WebGl2RenderingContext::RG_INTEGER,
+            WebGl2RenderingContext::UNSIGNED_SHORT,
+            &view,
+            0,
+        ).unwrap();
     }
 }
@@ -5,32 +5,304 @@ use crate::{Component, GameObject};
 use crate::engine::app::App;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{Event, HtmlElement, HtmlInputElement};
+use web_sys::{Document, Event, HtmlElement, HtmlInputElement, HtmlSelectElement};
+use crate::utils::read_file_bytes;
+use crate::simulation::boundary::{Boundaries, Boundary};
+use crate::simulation::brush::{BlendMode, Brush, BrushChemical, BrushShape};
+use crate::simulation::color_map::ColorMap;
+use crate::simulation::fixed_timestep::DEFAULT_ITERATIONS_PER_SECOND;
+use crate::simulation::gray_scott::{DEFAULT_DELTA_T, DEFAULT_DIFFUSION_A, DEFAULT_DIFFUSION_B};
+use crate::simulation::integrator::Integrator;
+use crate::simulation::stencil::Stencil;
+use crate::simulation::parameter_map::{PEARSON_FEED_RANGE, PEARSON_KILL_RANGE, ParameterImage, ParameterSource};
+use crate::simulation::preset::{builtin_presets, Preset};
+use crate::simulation::resolution::{FitMode, Resolution};
+use crate::simulation::state_file::FILE_EXTENSION;
+use crate::components::reaction_diffusion::SnapshotSource;
+use crate::simulation::shortcuts::{Action, Shortcuts};
+
+// screen pixels
+const BRUSH_SIZE_RANGE: (f64, f64, f64) = (1.0, 50.0, 1.0);
+// how much the brush size shortcuts grow or shrink it
+const BRUSH_SIZE_STEP: f64 = 1.25;
+
+const PNG_SCALES: [u32; 3] = [1, 2, 4];
+const TARGET_FPS: [f32; 3] = [30.0, 60.0, 120.0];
+const RESOLUTIONS: [Resolution; 7] = [
+    Resolution::Scale(1.0),
+    Resolution::Scale(1.5),
+    Resolution::Scale(2.0),
+    Resolution::Scale(3.0),
+    Resolution::Fixed(512, 512),
+    Resolution::Fixed(1024, 1024),
+    Resolution::Fixed(2048, 1024),
+];
+
+// what dragging the mouse does
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaintMode {
+    Brush,
+    Wall,
+    Erase,
+}
+
+impl PaintMode {
+    pub fn all() -> [PaintMode; 3] {
+        return [PaintMode::Brush, PaintMode::Wall, PaintMode::Erase];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            PaintMode::Brush => "brush",
+            PaintMode::Wall => "draw walls",
+            PaintMode::Erase => "erase walls",
+        };
+    }
+}
+
+// what reseed starts the field from
+#[derive(Clone, Copy, PartialEq)]
+pub enum SeedMode {
+    CenterSquare,
+    CenterCircle,
+    Noise,
+    Circles,
+    // the last seed image that was loaded
+    Image,
+    Text,
+}
+
+impl SeedMode {
+    pub fn all() -> [SeedMode; 6] {
+        return [SeedMode::CenterSquare, SeedMode::CenterCircle, SeedMode::Noise, SeedMode::Circles, SeedMode::Image, SeedMode::Text];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            SeedMode::CenterSquare => "center square",
+            SeedMode::CenterCircle => "center circle",
+            SeedMode::Noise => "noise",
+            SeedMode::Circles => "circles",
+            SeedMode::Image => "image",
+            SeedMode::Text => "text",
+        };
+    }
+}
 
 pub struct ReactionDiffusionUI {
+    /* the div everything else is in, so it can be hidden or taken off the page */
+    root: Option<HtmlElement>,
     callbacks: Vec<Closure<dyn FnMut()>>,
     clear_button: Rc<RefCell<bool>>,
+    reseed_button: Rc<RefCell<bool>>,
+    seed_mode_select: Rc<RefCell<usize>>,
+    seed_mode_select_element: Option<Rc<HtmlSelectElement>>,
+    seed_density: Rc<RefCell<f64>>,
+    seed_count: Rc<RefCell<f64>>,
+    seed_text: Rc<RefCell<String>>,
+    loaded_seed_image: Rc<RefCell<Option<Vec<u8>>>>,
+    paint_mode_select: Rc<RefCell<usize>>,
+    brush_shape_select: Rc<RefCell<usize>>,
+    brush_chemical_select: Rc<RefCell<usize>>,
+    brush_blend_mode_select: Rc<RefCell<usize>>,
+    brush_size: Rc<RefCell<f64>>,
+    brush_size_slider: Option<Rc<HtmlInputElement>>,
+    brush_strength: Rc<RefCell<f64>>,
+    clear_walls_button: Rc<RefCell<bool>>,
+    loaded_mask_image: Rc<RefCell<Option<Vec<u8>>>>,
+    paused: Rc<RefCell<bool>>,
+    pause_button: Option<HtmlElement>,
+    step_button: Rc<RefCell<bool>>,
+    fast_forward: Rc<RefCell<bool>>,
+    speed_slider_value: Rc<RefCell<f64>>,
+    last_speed_slider_value: Rc<RefCell<f64>>,
+    adaptive: Rc<RefCell<bool>>,
+    adapt_resolution: Rc<RefCell<bool>>,
+    target_fps_select: Rc<RefCell<usize>>,
+    budget_status: Option<HtmlElement>,
+    resolution_select: Rc<RefCell<usize>>,
+    resolution_select_element: Option<Rc<HtmlSelectElement>>,
+    last_resolution_select: usize,
+    /* a size that isn't one of RESOLUTIONS, used when set size is pressed */
+    grid_width: Rc<RefCell<f64>>,
+    grid_width_input: Option<Rc<HtmlInputElement>>,
+    grid_height: Rc<RefCell<f64>>,
+    grid_height_input: Option<Rc<HtmlInputElement>>,
+    grid_size_edited: Rc<RefCell<bool>>,
+    set_grid_size_button: Rc<RefCell<bool>>,
+    fit_mode_select: Rc<RefCell<usize>>,
+    last_fit_mode_select: usize,
     random_preset_button: Rc<RefCell<bool>>,
+    preset_seeds: Rc<RefCell<bool>>,
+    reset_view_button: Rc<RefCell<bool>>,
     feed_slider: Option<Rc<HtmlInputElement>>,
     feed_slider_value: Rc<RefCell<f64>>,
     last_feed_slider_value: Rc<RefCell<f64>>,
     kill_slider: Option<Rc<HtmlInputElement>>,
     kill_slider_value: Rc<RefCell<f64>>,
     last_kill_slider_value: Rc<RefCell<f64>>,
+    diffusion_a_slider: Option<Rc<HtmlInputElement>>,
+    diffusion_a_slider_value: Rc<RefCell<f64>>,
+    last_diffusion_a_slider_value: Rc<RefCell<f64>>,
+    diffusion_b_slider: Option<Rc<HtmlInputElement>>,
+    diffusion_b_slider_value: Rc<RefCell<f64>>,
+    last_diffusion_b_slider_value: Rc<RefCell<f64>>,
+    delta_t_slider: Option<Rc<HtmlInputElement>>,
+    delta_t_slider_value: Rc<RefCell<f64>>,
+    last_delta_t_slider_value: Rc<RefCell<f64>>,
+    stability_warning: Option<HtmlElement>,
+    stencil_select: Rc<RefCell<usize>>,
+    stencil_select_element: Option<Rc<HtmlSelectElement>>,
+    last_stencil_select: usize,
+    integrator_select: Rc<RefCell<usize>>,
+    integrator_select_element: Option<Rc<HtmlSelectElement>>,
+    last_integrator_select: usize,
+    boundary_x_select: Rc<RefCell<usize>>,
+    boundary_x_select_element: Option<Rc<HtmlSelectElement>>,
+    last_boundary_x_select: usize,
+    boundary_y_select: Rc<RefCell<usize>>,
+    boundary_y_select_element: Option<Rc<HtmlSelectElement>>,
+    last_boundary_y_select: usize,
+    edge_a: Rc<RefCell<f64>>,
+    edge_a_input: Option<Rc<HtmlInputElement>>,
+    edge_b: Rc<RefCell<f64>>,
+    edge_b_input: Option<Rc<HtmlInputElement>>,
+    edge_value_changed: Rc<RefCell<bool>>,
+    save_state_button: Rc<RefCell<bool>>,
+    loaded_state_file: Rc<RefCell<Option<Vec<u8>>>>,
+    save_png_button: Rc<RefCell<bool>>,
+    png_scale_select: Rc<RefCell<usize>>,
+    png_source_select: Rc<RefCell<usize>>,
+    color_maps: Vec<ColorMap>,
+    color_map_select: Rc<RefCell<usize>>,
+    color_map_select_element: Option<Rc<HtmlSelectElement>>,
+    last_color_map_select: usize,
+    presets: Vec<Preset>,
+    preset_select: Rc<RefCell<usize>>,
+    preset_select_element: Option<Rc<HtmlSelectElement>>,
+    last_preset_select: usize,
+    parameter_mode_select: Rc<RefCell<usize>>,
+    parameter_mode_select_element: Option<Rc<HtmlSelectElement>>,
+    last_parameter_mode_select: usize,
+    feed_range_from: Rc<RefCell<f64>>,
+    feed_range_to: Rc<RefCell<f64>>,
+    kill_range_from: Rc<RefCell<f64>>,
+    kill_range_to: Rc<RefCell<f64>>,
+    parameter_ranges_changed: Rc<RefCell<bool>>,
+    loaded_parameter_image: Rc<RefCell<Option<Vec<u8>>>>,
+    parameter_readout: Option<HtmlElement>,
+    shortcuts: Shortcuts,
+    /* the button of each action in the shortcut list, showing its key */
+    shortcut_buttons: Vec<(Action, HtmlElement)>,
+    // set by clicking an action in the shortcut list, the next key pressed is bound to it
+    rebinding: Rc<RefCell<Option<Action>>>,
 }
 
 impl ReactionDiffusionUI {
     pub fn new() -> Self {
         return Self {
+            root: None,
             callbacks: Vec::new(),
             clear_button: Rc::new(RefCell::new(false)),
+            reseed_button: Rc::new(RefCell::new(false)),
+            seed_mode_select: Rc::new(RefCell::new(0)),
+            seed_mode_select_element: None,
+            seed_density: Rc::new(RefCell::new(0.05)),
+            seed_count: Rc::new(RefCell::new(12.0)),
+            seed_text: Rc::new(RefCell::new(String::from("hello"))),
+            loaded_seed_image: Rc::new(RefCell::new(None)),
+            paint_mode_select: Rc::new(RefCell::new(0)),
+            brush_shape_select: Rc::new(RefCell::new(0)),
+            brush_chemical_select: Rc::new(RefCell::new(1)),
+            brush_blend_mode_select: Rc::new(RefCell::new(2)),
+            brush_size: Rc::new(RefCell::new(5.0)),
+            brush_size_slider: None,
+            brush_strength: Rc::new(RefCell::new(1.0)),
+            clear_walls_button: Rc::new(RefCell::new(false)),
+            loaded_mask_image: Rc::new(RefCell::new(None)),
+            paused: Rc::new(RefCell::new(false)),
+            pause_button: None,
+            step_button: Rc::new(RefCell::new(false)),
+            fast_forward: Rc::new(RefCell::new(false)),
+            speed_slider_value: Rc::new(RefCell::new(0.0)),
+            last_speed_slider_value: Rc::new(RefCell::new(0.0)),
+            adaptive: Rc::new(RefCell::new(false)),
+            adapt_resolution: Rc::new(RefCell::new(false)),
+            target_fps_select: Rc::new(RefCell::new(1)),
+            budget_status: None,
+            resolution_select: Rc::new(RefCell::new(1)),
+            resolution_select_element: None,
+            last_resolution_select: 1,
+            grid_width: Rc::new(RefCell::new(1024.0)),
+            grid_width_input: None,
+            grid_height: Rc::new(RefCell::new(1024.0)),
+            grid_height_input: None,
+            grid_size_edited: Rc::new(RefCell::new(false)),
+            set_grid_size_button: Rc::new(RefCell::new(false)),
+            fit_mode_select: Rc::new(RefCell::new(0)),
+            last_fit_mode_select: 0,
             random_preset_button: Rc::new(RefCell::new(false)),
+            preset_seeds: Rc::new(RefCell::new(false)),
+            reset_view_button: Rc::new(RefCell::new(false)),
             feed_slider: None,
             feed_slider_value: Rc::new(RefCell::new(0.0)),
             last_feed_slider_value: Rc::new(RefCell::new(0.0)),
             kill_slider: None,
             kill_slider_value: Rc::new(RefCell::new(0.0)),
             last_kill_slider_value: Rc::new(RefCell::new(0.0)),
+            diffusion_a_slider: None,
+            diffusion_a_slider_value: Rc::new(RefCell::new(0.0)),
+            last_diffusion_a_slider_value: Rc::new(RefCell::new(0.0)),
+            diffusion_b_slider: None,
+            diffusion_b_slider_value: Rc::new(RefCell::new(0.0)),
+            last_diffusion_b_slider_value: Rc::new(RefCell::new(0.0)),
+            delta_t_slider: None,
+            delta_t_slider_value: Rc::new(RefCell::new(0.0)),
+            last_delta_t_slider_value: Rc::new(RefCell::new(0.0)),
+            stability_warning: None,
+            stencil_select: Rc::new(RefCell::new(1)),
+            stencil_select_element: None,
+            last_stencil_select: 1,
+            integrator_select: Rc::new(RefCell::new(0)),
+            integrator_select_element: None,
+            last_integrator_select: 0,
+            boundary_x_select: Rc::new(RefCell::new(0)),
+            boundary_x_select_element: None,
+            last_boundary_x_select: 0,
+            boundary_y_select: Rc::new(RefCell::new(0)),
+            boundary_y_select_element: None,
+            last_boundary_y_select: 0,
+            edge_a: Rc::new(RefCell::new(Boundaries::periodic().edge_value.0 as f64)),
+            edge_a_input: None,
+            edge_b: Rc::new(RefCell::new(Boundaries::periodic().edge_value.1 as f64)),
+            edge_b_input: None,
+            edge_value_changed: Rc::new(RefCell::new(false)),
+            save_state_button: Rc::new(RefCell::new(false)),
+            loaded_state_file: Rc::new(RefCell::new(None)),
+            save_png_button: Rc::new(RefCell::new(false)),
+            png_scale_select: Rc::new(RefCell::new(0)),
+            png_source_select: Rc::new(RefCell::new(0)),
+            color_maps: ColorMap::named(),
+            color_map_select: Rc::new(RefCell::new(0)),
+            color_map_select_element: None,
+            last_color_map_select: 0,
+            presets: builtin_presets(),
+            preset_select: Rc::new(RefCell::new(0)),
+            preset_select_element: None,
+            last_preset_select: 0,
+            parameter_mode_select: Rc::new(RefCell::new(0)),
+            parameter_mode_select_element: None,
+            last_parameter_mode_select: 0,
+            feed_range_from: Rc::new(RefCell::new(PEARSON_FEED_RANGE.0 as f64)),
+            feed_range_to: Rc::new(RefCell::new(PEARSON_FEED_RANGE.1 as f64)),
+            kill_range_from: Rc::new(RefCell::new(PEARSON_KILL_RANGE.0 as f64)),
+            kill_range_to: Rc::new(RefCell::new(PEARSON_KILL_RANGE.1 as f64)),
+            parameter_ranges_changed: Rc::new(RefCell::new(false)),
+            loaded_parameter_image: Rc::new(RefCell::new(None)),
+            parameter_readout: None,
+            shortcuts: Shortcuts::new(),
+            shortcut_buttons: Vec::new(),
+            rebinding: Rc::new(RefCell::new(None)),
         };
     }
 }
@@ -40,10 +312,108 @@ impl ReactionDiffusionUI {
         return *self.clear_button.borrow();
     }
 
+    pub fn reseed_button(&self) -> bool { *self.reseed_button.borrow() }
+
+    pub fn seed_mode(&self) -> SeedMode { SeedMode::all()[*self.seed_mode_select.borrow()] }
+
+    pub fn set_seed_mode(&self, seed_mode: SeedMode) {
+        let i = SeedMode::all().iter().position(|mode| *mode == seed_mode).unwrap();
+        *self.seed_mode_select.borrow_mut() = i;
+        self.seed_mode_select_element.as_ref().unwrap().set_selected_index(i as i32);
+    }
+
+    pub fn seed_density(&self) -> f32 { *self.seed_density.borrow() as f32 }
+    pub fn seed_count(&self) -> i32 { *self.seed_count.borrow() as i32 }
+    pub fn seed_text(&self) -> String { self.seed_text.borrow().clone() }
+
+    // the bytes of a seed png the user picked, only returned once
+    pub fn take_loaded_seed_image(&self) -> Option<Vec<u8>> {
+        return self.loaded_seed_image.borrow_mut().take();
+    }
+
+    pub fn paint_mode(&self) -> PaintMode { PaintMode::all()[*self.paint_mode_select.borrow()] }
+
+    // rounded and kept inside the slider's range
+    pub fn set_brush_size(&self, size: f64) {
+        let size = size.round().clamp(BRUSH_SIZE_RANGE.0, BRUSH_SIZE_RANGE.1);
+        *self.brush_size.borrow_mut() = size;
+        self.brush_size_slider.as_ref().unwrap().set_value_as_number(size);
+    }
+
+    // with the radius in screen pixels
+    pub fn brush(&self) -> Brush {
+        return Brush {
+            shape: BrushShape::all()[*self.brush_shape_select.borrow()],
+            radius: *self.brush_size.borrow() as f32,
+            chemical: BrushChemical::all()[*self.brush_chemical_select.borrow()],
+            strength: *self.brush_strength.borrow() as f32,
+            blend_mode: BlendMode::all()[*self.brush_blend_mode_select.borrow()],
+        };
+    }
+
+    pub fn clear_walls_button(&self) -> bool { *self.clear_walls_button.borrow() }
+
+    // the bytes of a mask png the user picked, only returned once
+    pub fn take_loaded_mask_image(&self) -> Option<Vec<u8>> {
+        return self.loaded_mask_image.borrow_mut().take();
+    }
+
+    pub fn paused(&self) -> bool { *self.paused.borrow() }
+    pub fn step_button(&self) -> bool { *self.step_button.borrow() }
+    pub fn fast_forward(&self) -> bool { *self.fast_forward.borrow() }
+
+    // iterations per second
+    pub fn speed_slider_value(&self) -> f64 { *self.speed_slider_value.borrow() }
+    pub fn speed_slider_value_changed(&self) -> bool { *self.speed_slider_value.borrow() != *self.last_speed_slider_value.borrow() }
+
+    // run as many iterations as the frame rate allows instead of a fixed speed
+    pub fn adaptive(&self) -> bool { *self.adaptive.borrow() }
+    pub fn adapt_resolution(&self) -> bool { *self.adapt_resolution.borrow() }
+    pub fn target_fps(&self) -> f32 { TARGET_FPS[*self.target_fps_select.borrow()] }
+
+    pub fn set_budget_status(&self, status: Option<&str>) {
+        self.budget_status.as_ref().unwrap().set_inner_text(status.unwrap_or(""));
+    }
+
+    // the width and height boxes when set size was pressed, otherwise the dropdown
+    pub fn resolution(&self) -> Resolution {
+        if *self.set_grid_size_button.borrow() {
+            let width = (*self.grid_width.borrow()).round().max(1.0) as i32;
+            let height = (*self.grid_height.borrow()).round().max(1.0) as i32;
+            return Resolution::Fixed(width, height);
+        }
+        return RESOLUTIONS[*self.resolution_select.borrow()];
+    }
+    pub fn resolution_changed(&self) -> bool {
+        return *self.resolution_select.borrow() != self.last_resolution_select || *self.set_grid_size_button.borrow();
+    }
+
+    // shows resolution in the dropdown if it's one of the options, and a fixed size in the width and height boxes
+    pub fn select_resolution(&self, resolution: Resolution) {
+        if let Some(i) = RESOLUTIONS.iter().position(|r| *r == resolution) {
+            *self.resolution_select.borrow_mut() = i;
+            self.resolution_select_element.as_ref().unwrap().set_selected_index(i as i32);
+        }
+        if let Resolution::Fixed(width, height) = resolution {
+            *self.grid_width.borrow_mut() = width as f64;
+            self.grid_width_input.as_ref().unwrap().set_value_as_number(width as f64);
+            *self.grid_height.borrow_mut() = height as f64;
+            self.grid_height_input.as_ref().unwrap().set_value_as_number(height as f64);
+        }
+    }
+
+    pub fn fit_mode(&self) -> FitMode { FitMode::all()[*self.fit_mode_select.borrow()] }
+    pub fn fit_mode_changed(&self) -> bool { *self.fit_mode_select.borrow() != self.last_fit_mode_select }
+
+    pub fn reset_view_button(&self) -> bool { *self.reset_view_button.borrow() }
+
     pub fn random_preset_button(&self) -> bool {
         return *self.random_preset_button.borrow();
     }
 
+    // start over from a preset's recommended seed when it's picked, instead of letting the current pattern change
+    pub fn preset_seeds(&self) -> bool { *self.preset_seeds.borrow() }
+
     pub fn feed_slider_value(&self) -> f64 { *self.feed_slider_value.borrow() }
     pub fn set_feed_slider_value(&self, value: f64) { self.feed_slider.as_ref().unwrap().set_value_as_number(value); }
     pub fn feed_slider_value_changed(&self) -> bool { *self.feed_slider_value.borrow() !=  *self.last_feed_slider_value.borrow() }
@@ -51,6 +421,138 @@ impl ReactionDiffusionUI {
     pub fn kill_slider_value(&self) -> f64 { *self.kill_slider_value.borrow() }
     pub fn set_kill_slider_value(&self, value: f64) { self.kill_slider.as_ref().unwrap().set_value_as_number(value); }
     pub fn kill_slider_value_changed(&self) -> bool { *self.kill_slider_value.borrow() !=  *self.last_kill_slider_value.borrow() }
+
+    pub fn diffusion_a_slider_value(&self) -> f64 { *self.diffusion_a_slider_value.borrow() }
+    pub fn set_diffusion_a_slider_value(&self, value: f64) { self.diffusion_a_slider.as_ref().unwrap().set_value_as_number(value); }
+    pub fn diffusion_a_slider_value_changed(&self) -> bool { *self.diffusion_a_slider_value.borrow() != *self.last_diffusion_a_slider_value.borrow() }
+
+    pub fn diffusion_b_slider_value(&self) -> f64 { *self.diffusion_b_slider_value.borrow() }
+    pub fn set_diffusion_b_slider_value(&self, value: f64) { self.diffusion_b_slider.as_ref().unwrap().set_value_as_number(value); }
+    pub fn diffusion_b_slider_value_changed(&self) -> bool { *self.diffusion_b_slider_value.borrow() != *self.last_diffusion_b_slider_value.borrow() }
+
+    pub fn delta_t_slider_value(&self) -> f64 { *self.delta_t_slider_value.borrow() }
+    pub fn set_delta_t_slider_value(&self, value: f64) { self.delta_t_slider.as_ref().unwrap().set_value_as_number(value); }
+    pub fn delta_t_slider_value_changed(&self) -> bool { *self.delta_t_slider_value.borrow() != *self.last_delta_t_slider_value.borrow() }
+
+    pub fn set_stability_warning(&self, warning: Option<&str>) {
+        self.stability_warning.as_ref().unwrap().set_inner_text(warning.unwrap_or(""));
+    }
+
+    pub fn stencil(&self) -> Stencil { Stencil::all()[*self.stencil_select.borrow()] }
+    pub fn stencil_changed(&self) -> bool { *self.stencil_select.borrow() != self.last_stencil_select }
+
+    // shows which stencil the kernel is. kernels that aren't one of them leave the dropdown as it is
+    pub fn select_stencil(&self, stencil: Option<Stencil>) {
+        if let Some(i) = stencil.and_then(|stencil| Stencil::all().iter().position(|s| *s == stencil)) {
+            *self.stencil_select.borrow_mut() = i;
+            self.stencil_select_element.as_ref().unwrap().set_selected_index(i as i32);
+        }
+    }
+
+    pub fn integrator(&self) -> Integrator { Integrator::all()[*self.integrator_select.borrow()] }
+    pub fn integrator_changed(&self) -> bool { *self.integrator_select.borrow() != self.last_integrator_select }
+
+    pub fn select_integrator(&self, integrator: Integrator) {
+        let i = Integrator::all().iter().position(|i| *i == integrator).unwrap();
+        *self.integrator_select.borrow_mut() = i;
+        self.integrator_select_element.as_ref().unwrap().set_selected_index(i as i32);
+    }
+
+    pub fn boundaries(&self) -> Boundaries {
+        let mut boundaries = Boundaries::new(Boundary::all()[*self.boundary_x_select.borrow()], Boundary::all()[*self.boundary_y_select.borrow()]);
+        boundaries.edge_value = (*self.edge_a.borrow() as f32, *self.edge_b.borrow() as f32);
+        return boundaries;
+    }
+
+    pub fn boundaries_changed(&self) -> bool {
+        return *self.boundary_x_select.borrow() != self.last_boundary_x_select
+            || *self.boundary_y_select.borrow() != self.last_boundary_y_select
+            || *self.edge_value_changed.borrow();
+    }
+
+    pub fn select_boundaries(&self, boundaries: &Boundaries) {
+        let x = Boundary::all().iter().position(|b| *b == boundaries.x).unwrap();
+        *self.boundary_x_select.borrow_mut() = x;
+        self.boundary_x_select_element.as_ref().unwrap().set_selected_index(x as i32);
+        let y = Boundary::all().iter().position(|b| *b == boundaries.y).unwrap();
+        *self.boundary_y_select.borrow_mut() = y;
+        self.boundary_y_select_element.as_ref().unwrap().set_selected_index(y as i32);
+        *self.edge_a.borrow_mut() = boundaries.edge_value.0 as f64;
+        self.edge_a_input.as_ref().unwrap().set_value_as_number(boundaries.edge_value.0 as f64);
+        *self.edge_b.borrow_mut() = boundaries.edge_value.1 as f64;
+        self.edge_b_input.as_ref().unwrap().set_value_as_number(boundaries.edge_value.1 as f64);
+    }
+
+    pub fn save_state_button(&self) -> bool {
+        return *self.save_state_button.borrow();
+    }
+
+    pub fn save_png_button(&self) -> bool {
+        return *self.save_png_button.borrow();
+    }
+
+    pub fn png_scale(&self) -> u32 {
+        return PNG_SCALES[*self.png_scale_select.borrow()];
+    }
+
+    pub fn png_source(&self) -> SnapshotSource {
+        return SnapshotSource::all()[*self.png_source_select.borrow()];
+    }
+
+    pub fn color_map(&self) -> ColorMap { self.color_maps[*self.color_map_select.borrow()].clone() }
+    pub fn color_map_changed(&self) -> bool { *self.color_map_select.borrow() != self.last_color_map_select }
+
+    pub fn select_color_map(&self, name: &str) {
+        if let Some(i) = self.color_maps.iter().position(|color_map| color_map.name() == name) {
+            *self.color_map_select.borrow_mut() = i;
+            self.color_map_select_element.as_ref().unwrap().set_selected_index(i as i32);
+        }
+    }
+
+    pub fn presets(&self) -> &[Preset] { &self.presets }
+    pub fn preset_index(&self) -> usize { *self.preset_select.borrow() }
+    pub fn preset_changed(&self) -> bool { *self.preset_select.borrow() != self.last_preset_select }
+
+    pub fn set_preset_index(&self, i: usize) {
+        *self.preset_select.borrow_mut() = i;
+        self.preset_select_element.as_ref().unwrap().set_selected_index(i as i32);
+    }
+
+    // the bytes of a state file the user picked, only returned once
+    pub fn take_loaded_state_file(&self) -> Option<Vec<u8>> {
+        return self.loaded_state_file.borrow_mut().take();
+    }
+
+    // the picked source, with image as the image source. None if the image source is picked but there isn't one
+    pub fn parameter_source(&self, image: Option<&ParameterImage>) -> Option<ParameterSource> {
+        // by ParameterSource::index
+        let sources = [Some(ParameterSource::Uniform), Some(ParameterSource::Gradient), image.cloned().map(ParameterSource::Image)];
+        return sources[*self.parameter_mode_select.borrow()].clone();
+    }
+    pub fn parameter_source_changed(&self) -> bool { *self.parameter_mode_select.borrow() != self.last_parameter_mode_select }
+
+    pub fn select_parameter_source(&self, source: &ParameterSource) {
+        *self.parameter_mode_select.borrow_mut() = source.index();
+        self.parameter_mode_select_element.as_ref().unwrap().set_selected_index(source.index() as i32);
+    }
+
+    pub fn feed_range(&self) -> (f32, f32) { (*self.feed_range_from.borrow() as f32, *self.feed_range_to.borrow() as f32) }
+    pub fn kill_range(&self) -> (f32, f32) { (*self.kill_range_from.borrow() as f32, *self.kill_range_to.borrow() as f32) }
+    pub fn parameter_ranges_changed(&self) -> bool { *self.parameter_ranges_changed.borrow() }
+
+    // the bytes of a parameter png the user picked, only returned once
+    pub fn take_loaded_parameter_image(&self) -> Option<Vec<u8>> {
+        return self.loaded_parameter_image.borrow_mut().take();
+    }
+
+    // feed and kill under the mouse, or None to hide the readout
+    pub fn set_parameter_readout(&self, feed_kill: Option<(f32, f32)>) {
+        let readout = self.parameter_readout.as_ref().unwrap();
+        match feed_kill {
+            Some((feed, kill)) => readout.set_inner_text(&format!("feed {:.4}\nkill {:.4}", feed, kill)),
+            None => readout.set_inner_text(""),
+        }
+    }
 }
 
 impl Component for ReactionDiffusionUI {
@@ -67,6 +569,7 @@ impl Component for ReactionDiffusionUI {
         style.set_property("-webkit-user-select", "none").unwrap();
         style.set_property("user-select", "none").unwrap();
         app.body().append_child(&ui_div_parent).unwrap();
+        self.root = Some(ui_div_parent.clone());
 
         let ui_div = app.document().create_element("div").unwrap().dyn_into::<HtmlElement>().unwrap();
         let style = ui_div.style();
@@ -83,93 +586,436 @@ impl Component for ReactionDiffusionUI {
         style.set_property("margin", "10px").unwrap();
         ui_div.append_child(&controls).unwrap();
 
-        let label = app.document().create_element("label").unwrap().dyn_into::<HtmlElement>().unwrap();
-        label.set_attribute("for", "feed-input").unwrap();
-        label.set_inner_text("Feed rate");
-        controls.append_child(&label).unwrap();
-
-        let feed_slider = app.document().create_element("input").unwrap().dyn_into::<HtmlInputElement>().unwrap();
-        feed_slider.set_id("feed-input");
-        feed_slider.set_attribute("type", "range").unwrap();
-        feed_slider.set_attribute("min", "0").unwrap();
-        feed_slider.set_attribute("max", "0.1").unwrap();
-        feed_slider.set_attribute("step", "0.001").unwrap();
-        feed_slider.set_attribute("value", "0.055").unwrap();
-        feed_slider.style().set_property("width", "calc(100% - 5px)").unwrap();
-        controls.append_child(&feed_slider).unwrap();
-
-        let feed_slider = Rc::new(feed_slider);
-        let feed_slider_inner = Rc::clone(&feed_slider);
-        self.feed_slider = Some(Rc::clone(&feed_slider));
-
-        let feed_slider_value = Rc::clone(&self.feed_slider_value);
-        *feed_slider_value.borrow_mut() = feed_slider.value_as_number();
-        let callback = Closure::<dyn FnMut()>::new(move || {
-            *feed_slider_value.borrow_mut() = feed_slider_inner.value_as_number();
-        });
-        feed_slider.add_event_listener_with_callback("input", callback.as_ref().unchecked_ref()).unwrap();
-        self.callbacks.push(callback);
-
-        let label = app.document().create_element("label").unwrap().dyn_into::<HtmlElement>().unwrap();
-        label.set_attribute("for", "kill-input").unwrap();
-        label.set_inner_text("Kill rate");
-        controls.append_child(&label).unwrap();
-
-        let kill_slider = app.document().create_element("input").unwrap().dyn_into::<HtmlInputElement>().unwrap();
-        kill_slider.set_id("kill-input");
-        kill_slider.set_attribute("type", "range").unwrap();
-        kill_slider.set_attribute("min", "0").unwrap();
-        kill_slider.set_attribute("max", "0.1").unwrap();
-        kill_slider.set_attribute("step", "0.001").unwrap();
-        kill_slider.set_attribute("value", "0.062").unwrap();
-        kill_slider.style().set_property("width", "calc(100% - 5px)").unwrap();
-        controls.append_child(&kill_slider).unwrap();
-
-        let kill_slider = Rc::new(kill_slider);
-        let kill_slider_inner = Rc::clone(&kill_slider);
-        self.kill_slider = Some(Rc::clone(&kill_slider));
-
-        let kill_slider_value = Rc::clone(&self.kill_slider_value);
-        *kill_slider_value.borrow_mut() = kill_slider.value_as_number();
-        let callback = Closure::<dyn FnMut()>::new(move || {
-            *kill_slider_value.borrow_mut() = kill_slider_inner.value_as_number();
-        });
-        kill_slider.add_event_listener_with_callback("input", callback.as_ref().unchecked_ref()).unwrap();
-        self.callbacks.push(callback);
-
-        let button = app.document().create_element("button").unwrap().dyn_into::<HtmlElement>().unwrap();
-        button.set_attribute("type", "button").unwrap();
-        button.set_inner_text("clear");
-        button.style().set_property("display", "block").unwrap();
-        button.style().set_property("margin", "5px").unwrap();
-        controls.append_child(&button).unwrap();
-
-        let clear_button = Rc::clone(&self.clear_button);
-        let callback = Closure::<dyn FnMut()>::new(move || {
-            *clear_button.borrow_mut() = true;
-        });
-        button.add_event_listener_with_callback("click", callback.as_ref().unchecked_ref()).unwrap();
-        self.callbacks.push(callback);
-
-        let button = app.document().create_element("button").unwrap().dyn_into::<HtmlElement>().unwrap();
-        button.set_attribute("type", "button").unwrap();
-        button.set_inner_text("random preset");
-        button.style().set_property("display", "block").unwrap();
-        button.style().set_property("margin", "5px").unwrap();
-        controls.append_child(&button).unwrap();
-
-        let random_preset_button = Rc::clone(&self.random_preset_button);
-        let callback = Closure::<dyn FnMut()>::new(move || {
-            *random_preset_button.borrow_mut() = true;
-        });
-        button.add_event_listener_with_callback("click", callback.as_ref().unchecked_ref()).unwrap();
+        let (_, callback) = create_slider(app.document(), &controls, "Iterations per second", "speed-input", (0.0, 3000.0, 10.0), DEFAULT_ITERATIONS_PER_SECOND as f64, &self.speed_slider_value);
+        self.callbacks.push(callback);
+        let (button, callback) = create_toggle_button(app.document(), &controls, "pause", "play", &self.paused);
+        self.pause_button = Some(button);
+        self.callbacks.push(callback);
+        self.callbacks.push(create_button(app.document(), &controls, "step", &self.step_button));
+        self.callbacks.push(create_toggle_button(app.document(), &controls, "fast forward", "normal speed", &self.fast_forward).1);
+        self.callbacks.push(create_toggle_button(app.document(), &controls, "adaptive speed", "fixed speed", &self.adaptive).1);
+        self.callbacks.push(create_toggle_button(app.document(), &controls, "adapt resolution", "fixed resolution", &self.adapt_resolution).1);
+        let target_fps_names: Vec<String> = TARGET_FPS.iter().map(|fps| format!("{} fps", fps)).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "target", &target_fps_names, &self.target_fps_select).1);
+
+        let budget_status = app.document().create_element("div").unwrap().dyn_into::<HtmlElement>().unwrap();
+        budget_status.style().set_property("font-family", "monospace").unwrap();
+        controls.append_child(&budget_status).unwrap();
+        self.budget_status = Some(budget_status);
+
+        let resolution_names: Vec<String> = RESOLUTIONS.iter().map(|resolution| resolution.name()).collect();
+        let (select, callback) = create_select(app.document(), &controls, "resolution", &resolution_names, &self.resolution_select);
+        self.resolution_select_element = Some(select);
+        self.callbacks.push(callback);
+        let (input, callback) = create_number_input(app.document(), &controls, "grid width", &self.grid_width, &self.grid_size_edited);
+        input.set_attribute("step", "1").unwrap();
+        self.grid_width_input = Some(input);
+        self.callbacks.push(callback);
+        let (input, callback) = create_number_input(app.document(), &controls, "grid height", &self.grid_height, &self.grid_size_edited);
+        input.set_attribute("step", "1").unwrap();
+        self.grid_height_input = Some(input);
+        self.callbacks.push(callback);
+        self.callbacks.push(create_button(app.document(), &controls, "set size", &self.set_grid_size_button));
+        let fit_mode_names: Vec<String> = FitMode::all().iter().map(|fit_mode| String::from(fit_mode.name())).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "fit", &fit_mode_names, &self.fit_mode_select).1);
+        self.callbacks.push(create_button(app.document(), &controls, "reset view", &self.reset_view_button));
+
+        let (slider, callback) = create_slider(app.document(), &controls, "Feed rate", "feed-input", (0.0, 0.1, 0.001), 0.055, &self.feed_slider_value);
+        self.feed_slider = Some(slider);
         self.callbacks.push(callback);
+
+        let (slider, callback) = create_slider(app.document(), &controls, "Kill rate", "kill-input", (0.0, 0.1, 0.001), 0.062, &self.kill_slider_value);
+        self.kill_slider = Some(slider);
+        self.callbacks.push(callback);
+
+        let (slider, callback) = create_slider(app.document(), &controls, "Diffusion a", "diffusion-a-input", (0.0, 2.0, 0.01), DEFAULT_DIFFUSION_A as f64, &self.diffusion_a_slider_value);
+        self.diffusion_a_slider = Some(slider);
+        self.callbacks.push(callback);
+
+        let (slider, callback) = create_slider(app.document(), &controls, "Diffusion b", "diffusion-b-input", (0.0, 2.0, 0.01), DEFAULT_DIFFUSION_B as f64, &self.diffusion_b_slider_value);
+        self.diffusion_b_slider = Some(slider);
+        self.callbacks.push(callback);
+
+        let (slider, callback) = create_slider(app.document(), &controls, "Time step", "delta-t-input", (0.05, 2.0, 0.01), DEFAULT_DELTA_T as f64, &self.delta_t_slider_value);
+        self.delta_t_slider = Some(slider);
+        self.callbacks.push(callback);
+
+        let stencil_names: Vec<String> = Stencil::all().iter().map(|stencil| String::from(stencil.name())).collect();
+        let (select, callback) = create_select(app.document(), &controls, "stencil", &stencil_names, &self.stencil_select);
+        self.stencil_select_element = Some(select);
+        self.callbacks.push(callback);
+
+        let integrator_names: Vec<String> = Integrator::all().iter().map(|integrator| String::from(integrator.name())).collect();
+        let (select, callback) = create_select(app.document(), &controls, "integrator", &integrator_names, &self.integrator_select);
+        self.integrator_select_element = Some(select);
+        self.callbacks.push(callback);
+
+        let stability_warning = app.document().create_element("div").unwrap().dyn_into::<HtmlElement>().unwrap();
+        stability_warning.style().set_property("color", "red").unwrap();
+        controls.append_child(&stability_warning).unwrap();
+        self.stability_warning = Some(stability_warning);
+
+        let boundary_names: Vec<String> = Boundary::all().iter().map(|boundary| String::from(boundary.name())).collect();
+        let (select, callback) = create_select(app.document(), &controls, "x edges", &boundary_names, &self.boundary_x_select);
+        self.boundary_x_select_element = Some(select);
+        self.callbacks.push(callback);
+        let (select, callback) = create_select(app.document(), &controls, "y edges", &boundary_names, &self.boundary_y_select);
+        self.boundary_y_select_element = Some(select);
+        self.callbacks.push(callback);
+        let (input, callback) = create_number_input(app.document(), &controls, "dirichlet a", &self.edge_a, &self.edge_value_changed);
+        self.edge_a_input = Some(input);
+        self.callbacks.push(callback);
+        let (input, callback) = create_number_input(app.document(), &controls, "dirichlet b", &self.edge_b, &self.edge_value_changed);
+        self.edge_b_input = Some(input);
+        self.callbacks.push(callback);
+
+        let color_map_names: Vec<String> = self.color_maps.iter().map(|color_map| String::from(color_map.name())).collect();
+        let (select, callback) = create_select(app.document(), &controls, "colors", &color_map_names, &self.color_map_select);
+        self.color_map_select_element = Some(select);
+        self.callbacks.push(callback);
+
+        let preset_names: Vec<String> = self.presets.iter().map(|preset| preset.name.clone()).collect();
+        let (select, callback) = create_select(app.document(), &controls, "preset", &preset_names, &self.preset_select);
+        self.preset_select_element = Some(select);
+        self.callbacks.push(callback);
+
+        self.callbacks.push(create_button(app.document(), &controls, "clear", &self.clear_button));
+        self.callbacks.push(create_button(app.document(), &controls, "random preset", &self.random_preset_button));
+        self.callbacks.push(create_toggle_button(app.document(), &controls, "reseed on preset", "keep pattern on preset", &self.preset_seeds).1);
+
+        let seed_mode_names: Vec<String> = SeedMode::all().iter().map(|mode| String::from(mode.name())).collect();
+        let (select, callback) = create_select(app.document(), &controls, "seed", &seed_mode_names, &self.seed_mode_select);
+        self.seed_mode_select_element = Some(select);
+        self.callbacks.push(callback);
+        // the seed settings are only read when reseed is pressed
+        let seed_changed = Rc::new(RefCell::new(false));
+        self.callbacks.push(create_number_input(app.document(), &controls, "noise density", &self.seed_density, &seed_changed).1);
+        self.callbacks.push(create_number_input(app.document(), &controls, "circle count", &self.seed_count, &seed_changed).1);
+        self.callbacks.push(create_text_input(app.document(), &controls, "seed text", &self.seed_text));
+        self.callbacks.extend(create_file_button(app.document(), &controls, "load seed image", "image/png", &self.loaded_seed_image));
+        self.callbacks.push(create_button(app.document(), &controls, "reseed", &self.reseed_button));
+
+        let paint_mode_names: Vec<String> = PaintMode::all().iter().map(|mode| String::from(mode.name())).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "mouse", &paint_mode_names, &self.paint_mode_select).1);
+        let brush_shape_names: Vec<String> = BrushShape::all().iter().map(|shape| String::from(shape.name())).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "brush shape", &brush_shape_names, &self.brush_shape_select).1);
+        let brush_chemical_names: Vec<String> = BrushChemical::all().iter().map(|chemical| String::from(chemical.name())).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "brush paints", &brush_chemical_names, &self.brush_chemical_select).1);
+        let blend_mode_names: Vec<String> = BlendMode::all().iter().map(|blend_mode| String::from(blend_mode.name())).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "brush blend", &blend_mode_names, &self.brush_blend_mode_select).1);
+        let (slider, callback) = create_slider(app.document(), &controls, "Brush size", "brush-size-input", BRUSH_SIZE_RANGE, *self.brush_size.borrow(), &self.brush_size);
+        self.brush_size_slider = Some(slider);
+        self.callbacks.push(callback);
+        self.callbacks.push(create_slider(app.document(), &controls, "Brush strength", "brush-strength-input", (0.0, 1.0, 0.01), *self.brush_strength.borrow(), &self.brush_strength).1);
+        self.callbacks.push(create_button(app.document(), &controls, "clear walls", &self.clear_walls_button));
+        self.callbacks.extend(create_file_button(app.document(), &controls, "load walls", "image/png", &self.loaded_mask_image));
+
+        self.callbacks.push(create_button(app.document(), &controls, "save state", &self.save_state_button));
+        self.callbacks.extend(create_file_button(app.document(), &controls, "load state", &format!(".{}", FILE_EXTENSION), &self.loaded_state_file));
+
+        self.callbacks.push(create_button(app.document(), &controls, "save png", &self.save_png_button));
+        let png_scale_names: Vec<String> = PNG_SCALES.iter().map(|scale| format!("{}x", scale)).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "png size", &png_scale_names, &self.png_scale_select).1);
+        let png_source_names: Vec<String> = SnapshotSource::all().iter().map(|source| String::from(source.name())).collect();
+        self.callbacks.push(create_select(app.document(), &controls, "png colors", &png_source_names, &self.png_source_select).1);
+
+        let parameter_mode_names: Vec<String> = ParameterSource::names().iter().map(|name| String::from(*name)).collect();
+        let (select, callback) = create_select(app.document(), &controls, "parameters", &parameter_mode_names, &self.parameter_mode_select);
+        self.parameter_mode_select_element = Some(select);
+        self.callbacks.push(callback);
+
+        self.callbacks.push(create_number_input(app.document(), &controls, "feed from", &self.feed_range_from, &self.parameter_ranges_changed).1);
+        self.callbacks.push(create_number_input(app.document(), &controls, "feed to", &self.feed_range_to, &self.parameter_ranges_changed).1);
+        self.callbacks.push(create_number_input(app.document(), &controls, "kill from", &self.kill_range_from, &self.parameter_ranges_changed).1);
+        self.callbacks.push(create_number_input(app.document(), &controls, "kill to", &self.kill_range_to, &self.parameter_ranges_changed).1);
+        self.callbacks.extend(create_file_button(app.document(), &controls, "load parameter image", "image/png", &self.loaded_parameter_image));
+
+        let parameter_readout = app.document().create_element("div").unwrap().dyn_into::<HtmlElement>().unwrap();
+        parameter_readout.style().set_property("font-family", "monospace").unwrap();
+        controls.append_child(&parameter_readout).unwrap();
+        self.parameter_readout = Some(parameter_readout);
+
+        // click an action, then press the key for it
+        self.shortcuts = Shortcuts::load();
+        let shortcuts_label = app.document().create_element("div").unwrap().dyn_into::<HtmlElement>().unwrap();
+        shortcuts_label.set_inner_text("shortcuts");
+        controls.append_child(&shortcuts_label).unwrap();
+        for action in Action::all() {
+            let button = create_button_element(app.document(), &controls, &shortcut_text(action, self.shortcuts.key(action)));
+            let rebinding = Rc::clone(&self.rebinding);
+            let button_inner = button.clone();
+            let callback = Closure::<dyn FnMut()>::new(move || {
+                *rebinding.borrow_mut() = Some(action);
+                button_inner.set_inner_text(&format!("{}: press a key", action.name()));
+            });
+            button.add_event_listener_with_callback("click", callback.as_ref().unchecked_ref()).unwrap();
+            self.callbacks.push(callback);
+            self.shortcut_buttons.push((action, button));
+        }
+    }
+
+    fn on_update(&mut self, game_object: &mut GameObject, app: &App) {
+        let rebinding = *self.rebinding.borrow();
+        if let Some(action) = rebinding {
+            if let Some(key) = app.input().keys_down().next() {
+                self.shortcuts.rebind(action, key);
+                self.shortcuts.save();
+                *self.rebinding.borrow_mut() = None;
+                for (action, button) in self.shortcut_buttons.iter() {
+                    button.set_inner_text(&shortcut_text(*action, self.shortcuts.key(*action)));
+                }
+            }
+            return;
+        }
+
+        for key in app.input().keys_down() {
+            match self.shortcuts.action(key) {
+                Some(Action::Pause) => self.pause_button.as_ref().unwrap().click(),
+                Some(Action::Clear) => *self.clear_button.borrow_mut() = true,
+                Some(Action::RandomPreset) => *self.random_preset_button.borrow_mut() = true,
+                Some(Action::Preset(i)) if i < self.presets.len() => self.set_preset_index(i),
+                // at least a pixel so small sizes still change after rounding
+                Some(Action::SmallerBrush) => {
+                    let size = *self.brush_size.borrow();
+                    self.set_brush_size((size / BRUSH_SIZE_STEP).min(size - 1.0));
+                }
+                Some(Action::BiggerBrush) => {
+                    let size = *self.brush_size.borrow();
+                    self.set_brush_size((size * BRUSH_SIZE_STEP).max(size + 1.0));
+                }
+                _ => {}
+            }
+        }
     }
 
     fn on_late_update(&mut self, game_object: &mut GameObject, app: &App) {
         *self.last_feed_slider_value.borrow_mut() = *self.feed_slider_value.borrow_mut();
         *self.last_kill_slider_value.borrow_mut() = *self.kill_slider_value.borrow_mut();
+        *self.last_diffusion_a_slider_value.borrow_mut() = *self.diffusion_a_slider_value.borrow_mut();
+        *self.last_diffusion_b_slider_value.borrow_mut() = *self.diffusion_b_slider_value.borrow_mut();
+        *self.last_delta_t_slider_value.borrow_mut() = *self.delta_t_slider_value.borrow_mut();
+        self.last_color_map_select = *self.color_map_select.borrow();
+        self.last_preset_select = *self.preset_select.borrow();
+        self.last_stencil_select = *self.stencil_select.borrow();
+        self.last_integrator_select = *self.integrator_select.borrow();
+        self.last_parameter_mode_select = *self.parameter_mode_select.borrow();
+        self.last_resolution_select = *self.resolution_select.borrow();
+        *self.grid_size_edited.borrow_mut() = false;
+        *self.set_grid_size_button.borrow_mut() = false;
+        self.last_fit_mode_select = *self.fit_mode_select.borrow();
+        *self.parameter_ranges_changed.borrow_mut() = false;
+        self.last_boundary_x_select = *self.boundary_x_select.borrow();
+        self.last_boundary_y_select = *self.boundary_y_select.borrow();
+        *self.edge_value_changed.borrow_mut() = false;
+        *self.last_speed_slider_value.borrow_mut() = *self.speed_slider_value.borrow();
         *self.clear_button.borrow_mut() = false;
+        *self.reseed_button.borrow_mut() = false;
+        *self.clear_walls_button.borrow_mut() = false;
+        *self.step_button.borrow_mut() = false;
         *self.random_preset_button.borrow_mut() = false;
+        *self.reset_view_button.borrow_mut() = false;
+        *self.save_state_button.borrow_mut() = false;
+        *self.save_png_button.borrow_mut() = false;
+    }
+
+    fn on_enable(&mut self, game_object: &mut GameObject, app: &App) {
+        if let Some(root) = self.root.as_ref() {
+            root.style().remove_property("display").unwrap();
+        }
+    }
+
+    fn on_disable(&mut self, game_object: &mut GameObject, app: &App) {
+        if let Some(root) = self.root.as_ref() {
+            root.style().set_property("display", "none").unwrap();
+        }
+    }
+
+    fn on_destroy(&mut self, game_object: &mut GameObject, app: &App) {
+        if let Some(root) = self.root.take() {
+            root.remove();
+        }
+        // the elements calling them are gone
+        self.callbacks.clear();
     }
 }
+
+fn shortcut_text(action: Action, key: &str) -> String {
+    return format!("{}: {}", action.name(), if key.is_empty() { "none" } else { key });
+}
+
+fn create_button_element(document: &Document, parent: &HtmlElement, text: &str) -> HtmlElement {
+    let button = document.create_element("button").unwrap().dyn_into::<HtmlElement>().unwrap();
+    button.set_attribute("type", "button").unwrap();
+    button.set_inner_text(text);
+    button.style().set_property("display", "block").unwrap();
+    button.style().set_property("margin", "5px").unwrap();
+    parent.append_child(&button).unwrap();
+    return button;
+}
+
+// a button that sets pressed to true when clicked. pressed is reset in on_late_update
+fn create_button(document: &Document, parent: &HtmlElement, text: &str, pressed: &Rc<RefCell<bool>>) -> Closure<dyn FnMut()> {
+    let button = create_button_element(document, parent, text);
+
+    let pressed = Rc::clone(pressed);
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        *pressed.borrow_mut() = true;
+    });
+    button.add_event_listener_with_callback("click", callback.as_ref().unchecked_ref()).unwrap();
+    return callback;
+}
+
+// a range input that keeps value up to date while it's dragged. range is min, max, step
+fn create_slider(document: &Document, parent: &HtmlElement, label_text: &str, id: &str, range: (f64, f64, f64), initial: f64, value: &Rc<RefCell<f64>>) -> (Rc<HtmlInputElement>, Closure<dyn FnMut()>) {
+    let label = document.create_element("label").unwrap().dyn_into::<HtmlElement>().unwrap();
+    label.set_attribute("for", id).unwrap();
+    label.set_inner_text(label_text);
+    parent.append_child(&label).unwrap();
+
+    let slider = document.create_element("input").unwrap().dyn_into::<HtmlInputElement>().unwrap();
+    slider.set_id(id);
+    slider.set_attribute("type", "range").unwrap();
+    slider.set_attribute("min", &range.0.to_string()).unwrap();
+    slider.set_attribute("max", &range.1.to_string()).unwrap();
+    slider.set_attribute("step", &range.2.to_string()).unwrap();
+    slider.set_attribute("value", &initial.to_string()).unwrap();
+    slider.style().set_property("width", "calc(100% - 5px)").unwrap();
+    parent.append_child(&slider).unwrap();
+
+    let slider = Rc::new(slider);
+    let slider_inner = Rc::clone(&slider);
+    let value = Rc::clone(value);
+    *value.borrow_mut() = slider.value_as_number();
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        *value.borrow_mut() = slider_inner.value_as_number();
+    });
+    slider.add_event_listener_with_callback("input", callback.as_ref().unchecked_ref()).unwrap();
+    return (slider, callback);
+}
+
+// a button that opens a file picker. the bytes of the picked file end up in loaded
+fn create_file_button(document: &Document, parent: &HtmlElement, text: &str, accept: &str, loaded: &Rc<RefCell<Option<Vec<u8>>>>) -> Vec<Closure<dyn FnMut()>> {
+    let file_input = document.create_element("input").unwrap().dyn_into::<HtmlInputElement>().unwrap();
+    file_input.set_attribute("type", "file").unwrap();
+    file_input.set_attribute("accept", accept).unwrap();
+    file_input.style().set_property("display", "none").unwrap();
+    parent.append_child(&file_input).unwrap();
+
+    let file_input = Rc::new(file_input);
+    let file_input_inner = Rc::clone(&file_input);
+    let loaded = Rc::clone(loaded);
+    let change_callback = Closure::<dyn FnMut()>::new(move || {
+        if let Some(file) = file_input_inner.files().and_then(|files| files.get(0)) {
+            let loaded = Rc::clone(&loaded);
+            read_file_bytes(&file, move |bytes| {
+                *loaded.borrow_mut() = Some(bytes);
+            });
+        }
+        // so picking the same file again still fires change
+        file_input_inner.set_value("");
+    });
+    file_input.add_event_listener_with_callback("change", change_callback.as_ref().unchecked_ref()).unwrap();
+
+    let button = create_button_element(document, parent, text);
+    let click_callback = Closure::<dyn FnMut()>::new(move || {
+        file_input.click();
+    });
+    button.add_event_listener_with_callback("click", click_callback.as_ref().unchecked_ref()).unwrap();
+
+    return vec![change_callback, click_callback];
+}
+
+// a number box that keeps value up to date and sets changed to true when edited. changed is reset in on_late_update
+fn create_number_input(document: &Document, parent: &HtmlElement, label_text: &str, value: &Rc<RefCell<f64>>, changed: &Rc<RefCell<bool>>) -> (Rc<HtmlInputElement>, Closure<dyn FnMut()>) {
+    let label = document.create_element("label").unwrap().dyn_into::<HtmlElement>().unwrap();
+    label.set_inner_text(label_text);
+    label.style().set_property("display", "block").unwrap();
+    parent.append_child(&label).unwrap();
+
+    let input = document.create_element("input").unwrap().dyn_into::<HtmlInputElement>().unwrap();
+    input.set_attribute("type", "number").unwrap();
+    input.set_attribute("step", "0.001").unwrap();
+    input.style().set_property("width", "calc(100% - 10px)").unwrap();
+    input.set_value_as_number(*value.borrow());
+    label.append_child(&input).unwrap();
+
+    let input = Rc::new(input);
+    let input_inner = Rc::clone(&input);
+    let value = Rc::clone(value);
+    let changed = Rc::clone(changed);
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        let new_value = input_inner.value_as_number();
+        if !new_value.is_nan() {
+            *value.borrow_mut() = new_value;
+            *changed.borrow_mut() = true;
+        }
+    });
+    input.add_event_listener_with_callback("input", callback.as_ref().unchecked_ref()).unwrap();
+    return (input, callback);
+}
+
+fn create_text_input(document: &Document, parent: &HtmlElement, label_text: &str, value: &Rc<RefCell<String>>) -> Closure<dyn FnMut()> {
+    let label = document.create_element("label").unwrap().dyn_into::<HtmlElement>().unwrap();
+    label.set_inner_text(label_text);
+    label.style().set_property("display", "block").unwrap();
+    parent.append_child(&label).unwrap();
+
+    let input = document.create_element("input").unwrap().dyn_into::<HtmlInputElement>().unwrap();
+    input.set_attribute("type", "text").unwrap();
+    input.style().set_property("width", "calc(100% - 10px)").unwrap();
+    input.set_value(&value.borrow());
+    label.append_child(&input).unwrap();
+
+    let input = Rc::new(input);
+    let input_inner = Rc::clone(&input);
+    let value = Rc::clone(value);
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        *value.borrow_mut() = input_inner.value();
+    });
+    input.add_event_listener_with_callback("input", callback.as_ref().unchecked_ref()).unwrap();
+    return callback;
+}
+
+// a button that flips on every click and shows on_text while on is true
+fn create_toggle_button(document: &Document, parent: &HtmlElement, off_text: &str, on_text: &str, on: &Rc<RefCell<bool>>) -> (HtmlElement, Closure<dyn FnMut()>) {
+    let button = create_button_element(document, parent, if *on.borrow() { on_text } else { off_text });
+
+    let button_inner = button.clone();
+    let off_text = String::from(off_text);
+    let on_text = String::from(on_text);
+    let on = Rc::clone(on);
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        let value = !*on.borrow();
+        *on.borrow_mut() = value;
+        button_inner.set_inner_text(if value { &on_text } else { &off_text });
+    });
+    button.add_event_listener_with_callback("click", callback.as_ref().unchecked_ref()).unwrap();
+    return (button, callback);
+}
+
+// a dropdown that keeps selected up to date with the index of the chosen option
+fn create_select(document: &Document, parent: &HtmlElement, label_text: &str, options: &[String], selected: &Rc<RefCell<usize>>) -> (Rc<HtmlSelectElement>, Closure<dyn FnMut()>) {
+    let label = document.create_element("label").unwrap().dyn_into::<HtmlElement>().unwrap();
+    label.set_inner_text(label_text);
+    label.style().set_property("display", "block").unwrap();
+    parent.append_child(&label).unwrap();
+
+    let select = document.create_element("select").unwrap().dyn_into::<HtmlSelectElement>().unwrap();
+    select.style().set_property("width", "calc(100% - 5px)").unwrap();
+    for option in options {
+        let option_element = document.create_element("option").unwrap().dyn_into::<HtmlElement>().unwrap();
+        option_element.set_inner_text(option);
+        select.append_child(&option_element).unwrap();
+    }
+    select.set_selected_index(*selected.borrow() as i32);
+    label.append_child(&select).unwrap();
+
+    let select = Rc::new(select);
+    let select_inner = Rc::clone(&select);
+    let selected = Rc::clone(selected);
+    let callback = Closure::<dyn FnMut()>::new(move || {
+        *selected.borrow_mut() = select_inner.selected_index().max(0) as usize;
+    });
+    select.add_event_listener_with_callback("change", callback.as_ref().unchecked_ref()).unwrap();
+    return (select, callback);
+}
@@ -1,8 +1,10 @@
 use std::any::Any;
 use std::rc::Rc;
+use glam::Mat4;
 use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlVertexArrayObject};
 use crate::{Component, GameObject};
 use crate::engine::app::App;
+use crate::engine::transform::Transform;
 
 pub struct Square {
     vao: Option<WebGlVertexArrayObject>,
@@ -63,12 +65,19 @@ impl Component for Square {
         }
     }
 
-    fn draw(&mut self, game_object: &mut GameObject, app: &App) {
+    fn draw(&mut self, game_object: &mut GameObject, app: &App, view: &Mat4, projection: &Mat4) {
         let gl = app.gl();
 
         gl.bind_vertex_array(self.vao.as_ref());
         gl.use_program(Some(&self.program));
 
+        // u_view moves it from its own space to the camera's, the shader has no separate model matrix
+        let model_view = *view * Transform::world_matrix_of(game_object);
+        let loc = gl.get_uniform_location(&self.program, "u_view");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, model_view.as_ref());
+        let loc = gl.get_uniform_location(&self.program, "u_projection");
+        gl.uniform_matrix4fv_with_f32_array(loc.as_ref(), false, projection.as_ref());
+
         gl.draw_elements_with_i32(WebGl2RenderingContext::TRIANGLES, self.indices_count, WebGl2RenderingContext::UNSIGNED_SHORT, 0);
     }
 }
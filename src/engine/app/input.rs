@@ -1,8 +1,19 @@
+use crate::utils::distance;
+
 pub struct Input {
     mouse_position: (i32, i32),
     last_mouse_position: (i32, i32),
     buttons: u16,
     last_buttons: u16,
+    pressure: f32,
+    /* every pointer that's down, plus the mouse or a hovering pen while they're over the canvas */
+    pointers: Vec<Pointer>,
+    last_pointers: Vec<Pointer>,
+    /* names of the keys held down, see key_name */
+    keys: Vec<String>,
+    last_keys: Vec<String>,
+    // pixels scrolled this frame, positive is down
+    wheel_delta: f32,
 }
 
 pub enum Button {
@@ -11,6 +22,46 @@ pub enum Button {
     Right,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PointerKind {
+    Mouse,
+    Pen,
+    Touch,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pointer {
+    // stays the same from the pointer going down until it goes up, a new touch gets a new id
+    pub id: i32,
+    pub kind: PointerKind,
+    pub position: (i32, i32),
+    // the same bits as Input::buttons. a finger or pen touching the screen is the left button
+    pub buttons: u16,
+    // 0 to 1, always 1 for a mouse or devices that can't tell
+    pub pressure: f32,
+    // degrees the pen leans along x and y, 0 when it's upright or isn't a pen
+    pub tilt: (f32, f32),
+    // the mouse, or the first finger down. this is the one the mouse_position and buttons queries follow
+    pub primary: bool,
+}
+
+impl Pointer {
+    pub fn is_down(&self) -> bool {
+        return self.buttons != 0;
+    }
+}
+
+// two fingers moving together, from the last frame to this one
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pinch {
+    // halfway between the fingers in screen pixels
+    pub center: (f32, f32),
+    // how far the center moved
+    pub delta: (f32, f32),
+    // how much further apart the fingers are, above 1 spreading out and below 1 pinching in
+    pub scale: f32,
+}
+
 impl Input {
     pub fn new() -> Self {
         return Self {
@@ -18,6 +69,12 @@ impl Input {
             last_mouse_position: (-1, -1),
             buttons: 0,
             last_buttons: 0,
+            pressure: 1.0,
+            pointers: Vec::new(),
+            last_pointers: Vec::new(),
+            keys: Vec::new(),
+            last_keys: Vec::new(),
+            wheel_delta: 0.0,
         }
     }
 }
@@ -31,6 +88,10 @@ impl Input {
         self.last_buttons = buttons;
     }
 
+    pub fn set_pressure(&mut self, pressure: f32) {
+        self.pressure = pressure;
+    }
+
     pub fn set_mouse_position(&mut self, mouse_position: (i32, i32)) {
         self.mouse_position = mouse_position;
     }
@@ -39,6 +100,55 @@ impl Input {
         self.last_mouse_position = mouse_position;
     }
 
+    // adds the pointer or replaces the one with the same id. the primary one also moves the mouse
+    pub fn set_pointer(&mut self, pointer: Pointer) {
+        if pointer.primary {
+            self.set_buttons(pointer.buttons);
+            self.set_mouse_position(pointer.position);
+            self.set_pressure(pointer.pressure);
+        }
+        match self.pointers.iter_mut().find(|p| p.id == pointer.id) {
+            Some(p) => *p = pointer,
+            None => self.pointers.push(pointer),
+        }
+    }
+
+    // a finger lifted or a pen moved out of range, it isn't tracked any more
+    pub fn remove_pointer(&mut self, id: i32) {
+        if let Some(pointer) = self.pointers.iter().find(|p| p.id == id) {
+            if pointer.primary {
+                self.set_buttons(0);
+            }
+        }
+        self.pointers.retain(|p| p.id != id);
+    }
+
+    pub fn set_last_pointers(&mut self, pointers: Vec<Pointer>) {
+        self.last_pointers = pointers;
+    }
+
+    pub fn set_key(&mut self, key: &str, down: bool) {
+        let held = self.keys.iter().any(|k| k == key);
+        if down && !held {
+            self.keys.push(String::from(key));
+        } else if !down {
+            self.keys.retain(|k| k != key);
+        }
+    }
+
+    // the window lost focus, so the key ups won't come
+    pub fn release_keys(&mut self) {
+        self.keys.clear();
+    }
+
+    pub fn set_last_keys(&mut self, keys: Vec<String>) {
+        self.last_keys = keys;
+    }
+
+    pub fn set_wheel_delta(&mut self, wheel_delta: f32) {
+        self.wheel_delta = wheel_delta;
+    }
+
     pub fn get_button(&self, button: Button) -> bool {
         return match button {
             Button::Left => self.buttons & (1u16 << 0) > 0,
@@ -59,6 +169,11 @@ impl Input {
         return self.buttons;
     }
 
+    // of the primary pointer
+    pub fn pressure(&self) -> f32 {
+        return self.pressure;
+    }
+
     pub fn mouse_position(&self) -> (i32, i32) {
         return self.mouse_position;
     }
@@ -70,4 +185,96 @@ impl Input {
 
         return (self.last_mouse_position.0 - self.mouse_position.0, self.last_mouse_position.1 - self.mouse_position.1);
     }
+
+    pub fn wheel_delta(&self) -> f32 {
+        return self.wheel_delta;
+    }
+
+    pub fn keys(&self) -> &[String] {
+        return &self.keys;
+    }
+
+    // held down, by key_name
+    pub fn get_key(&self, key: &str) -> bool {
+        return self.keys.iter().any(|k| k == key);
+    }
+
+    // pressed this frame
+    pub fn get_key_down(&self, key: &str) -> bool {
+        return self.get_key(key) && !self.last_keys.iter().any(|k| k == key);
+    }
+
+    // released this frame
+    pub fn get_key_up(&self, key: &str) -> bool {
+        return !self.get_key(key) && self.last_keys.iter().any(|k| k == key);
+    }
+
+    // every key pressed this frame
+    pub fn keys_down(&self) -> impl Iterator<Item = &str> {
+        return self.keys.iter().filter(|k| !self.last_keys.contains(k)).map(|k| k.as_str());
+    }
+
+    pub fn pointers(&self) -> &[Pointer] {
+        return &self.pointers;
+    }
+
+    pub fn get_pointer(&self, id: i32) -> Option<&Pointer> {
+        return self.pointers.iter().find(|p| p.id == id);
+    }
+
+    // where the pointer was at the end of the last frame
+    pub fn get_last_pointer(&self, id: i32) -> Option<&Pointer> {
+        return self.last_pointers.iter().find(|p| p.id == id);
+    }
+
+    // down now but not last frame
+    pub fn get_pointer_down(&self, id: i32) -> bool {
+        let down = self.get_pointer(id).map_or(false, |p| p.is_down());
+        let was_down = self.get_last_pointer(id).map_or(false, |p| p.is_down());
+        return down && !was_down;
+    }
+
+    // down last frame but not now, including fingers that have been lifted and aren't tracked any more
+    pub fn get_pointer_up(&self, id: i32) -> bool {
+        let down = self.get_pointer(id).map_or(false, |p| p.is_down());
+        let was_down = self.get_last_pointer(id).map_or(false, |p| p.is_down());
+        return !down && was_down;
+    }
+
+    pub fn pointers_down(&self) -> impl Iterator<Item = &Pointer> {
+        return self.pointers.iter().filter(|p| p.is_down());
+    }
+
+    // only while exactly two fingers are touching, and both were last frame as well
+    pub fn pinch(&self) -> Option<Pinch> {
+        let touches: Vec<&Pointer> = self.pointers_down().filter(|p| p.kind == PointerKind::Touch).collect();
+        if touches.len() != 2 {
+            return None;
+        }
+        let last_a = self.get_last_pointer(touches[0].id).filter(|p| p.is_down())?;
+        let last_b = self.get_last_pointer(touches[1].id).filter(|p| p.is_down())?;
+
+        let center = midpoint(touches[0].position, touches[1].position);
+        let last_center = midpoint(last_a.position, last_b.position);
+        let spread = distance(touches[0].position, touches[1].position);
+        let last_spread = distance(last_a.position, last_b.position);
+        return Some(Pinch {
+            center,
+            delta: (center.0 - last_center.0, center.1 - last_center.1),
+            scale: if last_spread > 0.0 { spread / last_spread } else { 1.0 },
+        });
+    }
+}
+
+fn midpoint(a: (i32, i32), b: (i32, i32)) -> (f32, f32) {
+    return ((a.0 + b.0) as f32 * 0.5, (a.1 + b.1) as f32 * 0.5);
+}
+
+// what a KeyboardEvent.key is called in Input. letters are lower case so shift doesn't change them, and the space bar
+// is "space" instead of " "
+pub fn key_name(key: &str) -> String {
+    if key == " " {
+        return String::from("space");
+    }
+    return key.to_lowercase();
 }
@@ -1,107 +1,46 @@
-use std::cell::{Ref, RefCell};
-use std::rc::Rc;
-use wasm_bindgen::JsCast;
-use wasm_bindgen::prelude::Closure;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use glam::Mat4;
 use web_sys::{Document, HtmlCanvasElement, HtmlElement, WebGl2RenderingContext};
 use crate::{Component, GameObject};
+use crate::engine::game_object::GameObjectId;
+use crate::engine::scheduler::Scheduler;
+use crate::engine::transform::Transform;
 use crate::engine::app::input::Input;
 use crate::engine::app::screen::Screen;
 use crate::engine::app::time::Time;
+use crate::engine::platform::{Platform, PlatformEvent};
 
 pub mod input;
 pub mod screen;
 pub mod time;
 
 pub struct App {
-    canvas: HtmlCanvasElement,
-    gl: Option<WebGl2RenderingContext>,
-    game_objects: RefCell<Vec<Rc<RefCell<GameObject>>>>,
-    game_objects_to_be_added: RefCell<Vec<GameObject>>,
-    document: Document,
-    body: HtmlElement,
+    platform: Box<dyn Platform>,
+    scheduler: Scheduler,
     input: Input,
     screen: Screen,
     time: Time,
 }
 
 impl App {
-    pub fn new() -> Rc<RefCell<App>> {
-        let window = Rc::new(web_sys::window().expect("no global `window` exists"));
-        let document = window.document().unwrap();
-        let body = document.body().expect("document should have a body");
-
-        let width = window.inner_width().unwrap().as_f64().unwrap() as i32;
-        let height = window.inner_height().unwrap().as_f64().unwrap() as i32;
-
-        let canvas = document.create_element("canvas").unwrap();
-        canvas.set_id("main_canvas");
-        canvas.set_attribute("width", &width.to_string()).unwrap();
-        canvas.set_attribute("height", &height.to_string()).unwrap();
-        body.append_child(&canvas).unwrap();
-        let canvas: HtmlCanvasElement = canvas.dyn_into::<HtmlCanvasElement>().unwrap();
-
+    pub fn new(platform: Box<dyn Platform>) -> Rc<RefCell<App>> {
         let app = App {
-            canvas,
-            gl: None,
-            game_objects: RefCell::new(Vec::new()),
-            game_objects_to_be_added: RefCell::new(Vec::new()),
-            document,
-            body,
+            screen: Screen::new(platform.surface_size()),
+            platform,
+            scheduler: Scheduler::new(),
             input: Input::new(),
-            screen: Screen::new((width, height)),
             time: Time::new(),
         };
         let app = Rc::new(RefCell::new(app));
 
-        let window_outer = Rc::clone(&window);
-        let app_outer = Rc::clone(&app);
-        let event_closure = Closure::<dyn FnMut()>::new(move || {
-            let mut app = app.borrow_mut();
-
-            let width = window.inner_width().unwrap().as_f64().unwrap() as i32;
-            let height = window.inner_height().unwrap().as_f64().unwrap() as i32;
-            app.screen.set_size((width, height));
-
-            let canvas = app.canvas();
-            canvas.set_attribute("width", &width.to_string()).unwrap();
-            canvas.set_attribute("height", &height.to_string()).unwrap();
-        });
-        let window = window_outer;
-        let app = app_outer;
-        window.add_event_listener_with_callback("resize", event_closure.as_ref().unchecked_ref()).unwrap();
-        event_closure.forget();
-
-        let app_outer = Rc::clone(&app);
-        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
-            app.borrow_mut().input.set_buttons(event.buttons());
-        });
-        let app = app_outer;
-        app.borrow().canvas().add_event_listener_with_callback("mousedown", event_closure.as_ref().unchecked_ref()).unwrap();
-        app.borrow().canvas().add_event_listener_with_callback("mouseup", event_closure.as_ref().unchecked_ref()).unwrap();
-        event_closure.forget();
-
-        let app_outer = Rc::clone(&app);
-        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
-            app.borrow_mut().input.set_buttons(event.buttons());
-            app.borrow_mut().input.set_mouse_position((event.offset_x(), event.offset_y()));
-        });
-        let app = app_outer;
-        app.borrow().canvas().add_event_listener_with_callback("mousemove", event_closure.as_ref().unchecked_ref()).unwrap();
-        event_closure.forget();
-
-        let animation_loop_closure = Rc::new(RefCell::new(None::<Closure<dyn FnMut(_)>>));
-        let animation_loop_closure_outer = animation_loop_closure.clone();
-
-        let window = Rc::new(window);
-        let window_outer = Rc::clone(&window);
-
         let mut start_time = -1.0;
         let mut last_unscaled_time = 0.0;
 
-        let app_outer = Rc::clone(&app);
-        *animation_loop_closure_outer.borrow_mut() = Some(Closure::<dyn FnMut(_)>::new(move |now: f64| {
+        let app_inner = Rc::clone(&app);
+        app.borrow_mut().platform.start(Box::new(move |now: f64| {
+            let app = &app_inner;
             {
-                let now = now * 0.001;
                 if start_time < 0.0 {
                     start_time = now;
                 }
@@ -113,62 +52,11 @@ impl App {
                     let mut app_mut = app.borrow_mut();
                     app_mut.time.set_delta_time(delta_time as f32);
                     app_mut.time.set_unscaled_time(unscaled_time as f32);
+                    app_mut.handle_platform_events();
                 }
 
                 let app = app.borrow();
-
-                {
-                    let mut game_objects_to_be_added = app.game_objects_to_be_added.borrow_mut();
-                    let mut game_objects = app.game_objects.borrow_mut();
-                    while game_objects_to_be_added.len() > 0 {
-                        game_objects.push(Rc::new(RefCell::new(game_objects_to_be_added.pop().unwrap())));
-                    }
-                }
-
-                let game_objects_len = app.game_objects.borrow().len();
-
-                for i in 0..game_objects_len {
-                    let components = app.game_objects.borrow()[i].borrow().components();
-                    for component in components.borrow().iter() {
-                        let game_object = &app.game_objects.borrow()[i];
-                        if !component.had_first_update() {
-                            component.component().borrow_mut().on_first_update(&mut game_object.borrow_mut(), &app);
-                            component.set_had_first_update();
-                        }
-                    }
-                }
-                
-                for i in 0..game_objects_len {
-                    let components = app.game_objects.borrow()[i].borrow().components();
-                    for component in components.borrow().iter() {
-                        let game_object = &app.game_objects.borrow()[i];
-                        component.component().borrow_mut().on_update(&mut game_object.borrow_mut(), &app);
-                    }
-                }
-                
-                for i in 0..game_objects_len {
-                    let components = app.game_objects.borrow()[i].borrow().components();
-                    for component in components.borrow().iter() {
-                        let game_object = &app.game_objects.borrow()[i];
-                        component.component().borrow_mut().on_pre_render(&mut game_object.borrow_mut(), &app);
-                    }
-                }
-                
-                for i in 0..game_objects_len {
-                    let components = app.game_objects.borrow()[i].borrow().components();
-                    for component in components.borrow().iter() {
-                        let game_object = &app.game_objects.borrow()[i];
-                        component.component().borrow_mut().on_render(&mut game_object.borrow_mut(), &app);
-                    }
-                }
-                
-                for i in 0..game_objects_len {
-                    let components = app.game_objects.borrow()[i].borrow().components();
-                    for component in components.borrow().iter() {
-                        let game_object = &app.game_objects.borrow()[i];
-                        component.component().borrow_mut().on_late_update(&mut game_object.borrow_mut(), &app);
-                    }
-                }
+                app.scheduler.run_frame(&app);
             }
 
             {
@@ -179,46 +67,106 @@ impl App {
 
                 let buttons = app.input.buttons();
                 app.input.set_last_buttons(buttons);
-            }
 
-            window.request_animation_frame(animation_loop_closure.borrow().as_ref().unwrap().as_ref().unchecked_ref()).expect("request_animation_frame failed");
+                let pointers = app.input.pointers().to_vec();
+                app.input.set_last_pointers(pointers);
+
+                let keys = app.input.keys().to_vec();
+                app.input.set_last_keys(keys);
+
+                app.input.set_wheel_delta(0.0);
+            }
         }));
-        let window = window_outer;
-        window.request_animation_frame(animation_loop_closure_outer.borrow().as_ref().unwrap().as_ref().unchecked_ref()).expect("request_animation_frame failed");
-        let app = app_outer;
 
         return app;
     }
+
+    fn handle_platform_events(&mut self) {
+        for event in self.platform.poll_events() {
+            match event {
+                PlatformEvent::Resize(size) => self.screen.set_size(size),
+                PlatformEvent::Pointer(pointer) => self.input.set_pointer(pointer),
+                PlatformEvent::PointerLeave(id) => self.input.remove_pointer(id),
+                PlatformEvent::Wheel(delta) => {
+                    let wheel_delta = self.input.wheel_delta();
+                    self.input.set_wheel_delta(wheel_delta + delta);
+                }
+                PlatformEvent::KeyDown(key) => self.input.set_key(&key, true),
+                PlatformEvent::KeyUp(key) => self.input.set_key(&key, false),
+                PlatformEvent::Blur => self.input.release_keys(),
+            }
+        }
+    }
 }
 
 impl App {
-    pub fn add_game_object(&self, game_object: GameObject) {
-        self.game_objects_to_be_added.borrow_mut().push(game_object);
+    // it joins before the next phase, or at the end of the frame if it's added during late_update
+    pub fn add_game_object(&self, game_object: GameObject) -> GameObjectId {
+        return self.scheduler.add_game_object(game_object);
+    }
+
+    // before the next phase. its components get on_disable and on_destroy and the game object is dropped
+    pub fn destroy_game_object(&self, id: GameObjectId) {
+        self.scheduler.destroy_game_object(id);
+    }
+
+    // draws every component with these matrices, after every component has had on_render
+    pub fn draw_scene(&self, view: Mat4, projection: Mat4) {
+        self.scheduler.draw(view, projection);
+    }
+
+    pub fn get_game_object(&self, id: GameObjectId) -> Option<Rc<RefCell<GameObject>>> {
+        return self.scheduler.get_game_object(id);
+    }
+
+    // the first game object with the name. game objects added since the last phase started aren't found yet
+    pub fn find_by_name(&self, name: &str) -> Option<GameObjectId> {
+        return self.scheduler.find_by_name(name);
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Vec<GameObjectId> {
+        return self.scheduler.find_by_tag(tag);
+    }
+
+    // works while the game object is borrowed, unlike going through get_game_object
+    pub fn get_component<T: 'static>(&self, id: GameObjectId) -> Option<Weak<RefCell<T>>> {
+        return self.scheduler.get_component::<T>(id);
+    }
+
+    // parents the child game object's Transform to the parent's, or unparents it with None. both need a Transform
+    pub fn set_parent(&self, child: GameObjectId, parent: Option<GameObjectId>) -> Result<(), String> {
+        let transform = |id: GameObjectId| {
+            return self.get_component::<Transform>(id).and_then(|transform| transform.upgrade())
+                .ok_or(format!("game object {:?} has no transform", id));
+        };
+        let child = transform(child)?;
+        return match parent {
+            Some(parent) => Transform::set_parent(&child, Some(&transform(parent)?)),
+            None => Transform::set_parent(&child, None),
+        };
+    }
+
+    // the first component of type T on any game object, including ones that are borrowed because they're updating
+    pub fn find_component_in_scene<T: 'static>(&self) -> Option<Weak<RefCell<T>>> {
+        return self.scheduler.find_component_in_scene::<T>();
     }
 
     pub fn input(&self) -> &Input { &self.input }
     pub fn screen(&self) -> &Screen { &self.screen }
     pub fn time(&self) -> &Time { &self.time }
 
-    pub fn game_objects(&self) -> Ref<Vec<Rc<RefCell<GameObject>>>> { self.game_objects.borrow() }
+    pub fn game_objects(&self) -> Vec<Rc<RefCell<GameObject>>> { self.scheduler.game_objects() }
+
+    pub fn platform(&self) -> &dyn Platform { self.platform.as_ref() }
 
     pub fn canvas(&self) -> &HtmlCanvasElement {
-        return &self.canvas;
+        return self.platform.canvas().expect("platform has no canvas");
     }
 
-    pub fn document(&self) -> &Document { return &self.document; }
-    pub fn body(&self) -> &HtmlElement { return &self.body; }
-
-    pub fn init_gl(&mut self) {
-        if self.gl == None {
-            self.gl = Some(self.canvas
-                .get_context("webgl2").unwrap()
-                .unwrap()
-                .dyn_into::<WebGl2RenderingContext>().unwrap());
-        }
-    }
+    pub fn document(&self) -> &Document { return self.platform.document().expect("platform has no document"); }
+    pub fn body(&self) -> &HtmlElement { return self.platform.body().expect("platform has no body"); }
 
     pub fn gl(&self) -> &WebGl2RenderingContext {
-        return self.gl.as_ref().unwrap();
+        return self.platform.gl().expect("platform has no gl context");
     }
 }
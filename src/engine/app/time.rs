@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 pub struct Time {
     delta_time: f32,
     unscaled_time: f32,
@@ -19,3 +21,50 @@ impl Time {
     pub fn unscaled_time(&self) -> f32 { self.unscaled_time }
     pub fn set_unscaled_time(&mut self, unscaled_time: f32) { self.unscaled_time = unscaled_time; }
 }
+
+// the frames from the last window_length seconds, for measuring frame rate
+pub struct FrameTimes {
+    window_length: f32,
+    frames: VecDeque<(f32, f32)>,
+}
+
+impl FrameTimes {
+    pub fn new(window_length: f32) -> Self {
+        return Self {
+            window_length,
+            frames: VecDeque::new(),
+        };
+    }
+}
+
+impl FrameTimes {
+    pub fn push(&mut self, unscaled_time: f32, delta_time: f32) {
+        self.frames.push_back((unscaled_time, delta_time));
+        while self.frames.len() > 0 && unscaled_time > self.frames[0].0 + self.window_length {
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    // seconds between the oldest and newest frame
+    pub fn duration(&self) -> f32 {
+        if self.frames.len() == 0 {
+            return 0.0;
+        }
+        return self.frames[self.frames.len() - 1].0 - self.frames[0].0;
+    }
+
+    pub fn average_delta_time(&self) -> f32 {
+        return self.frames.iter().map(|e| e.1).sum::<f32>() / self.frames.len() as f32;
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.frames.len() == 0 {
+            return 0.0;
+        }
+        return 1.0 / self.average_delta_time();
+    }
+}
@@ -1,13 +1,20 @@
+use glam::Mat4;
 use crate::engine::app::App;
 use crate::GameObject;
 
 pub trait Component {
     fn on_add_to_game_object(&mut self, game_object: &mut GameObject, app: &App) {}
+    // before the first update, and whenever the component or its game object is enabled again
+    fn on_enable(&mut self, game_object: &mut GameObject, app: &App) {}
     fn on_first_update(&mut self, game_object: &mut GameObject, app: &App) {}
     fn on_update(&mut self, game_object: &mut GameObject, app: &App) {}
     fn on_pre_render(&mut self, game_object: &mut GameObject, app: &App) {}
     fn on_render(&mut self, game_object: &mut GameObject, app: &App) {}
     fn on_late_update(&mut self, game_object: &mut GameObject, app: &App) {}
+    fn on_disable(&mut self, game_object: &mut GameObject, app: &App) {}
+    // the last call a component gets, after on_disable. gl objects and dom elements should be let go of here
+    fn on_destroy(&mut self, game_object: &mut GameObject, app: &App) {}
 
-    fn draw(&mut self, game_object: &mut GameObject, app: &App) {}
+    // view takes world space to the camera's, and projection that to clip space
+    fn draw(&mut self, game_object: &mut GameObject, app: &App, view: &Mat4, projection: &Mat4) {}
 }
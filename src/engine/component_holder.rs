@@ -1,24 +1,65 @@
+use std::any::Any;
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use crate::Component;
 
+struct ComponentState {
+    had_first_update: bool,
+    enabled: bool,
+    // whether on_enable was the last of on_enable and on_disable it was given
+    was_active: bool,
+    // removed, and waiting for the app to call on_destroy and drop it
+    removed: bool,
+}
+
+/*
+    A component and what the app knows about it. Clones share everything, so the scheduler can copy a game object's
+    list of them and let go of it before calling into any of them
+ */
+#[derive(Clone)]
 pub struct ComponentHolder {
-    component: Box<Rc<RefCell<dyn Component>>>,
-    had_first_update: RefCell<bool>,
+    component: Rc<RefCell<dyn Component>>,
+    /* the same component as an Rc<RefCell<T>>, so it can be found by type */
+    component_as_any: Rc<dyn Any>,
+    state: Rc<RefCell<ComponentState>>,
 }
 
 impl ComponentHolder {
-    pub fn new(component: Box<Rc<RefCell<dyn Component>>>) -> Self {
+    pub fn new<T: Component + 'static>(component: T) -> Self {
+        let component = Rc::new(RefCell::new(component));
         return Self {
-            component,
-            had_first_update: RefCell::new(false),
+            component: Rc::clone(&component) as Rc<RefCell<dyn Component>>,
+            component_as_any: Rc::new(component),
+            state: Rc::new(RefCell::new(ComponentState {
+                had_first_update: false,
+                enabled: true,
+                was_active: false,
+                removed: false,
+            })),
         };
     }
 }
 
  impl ComponentHolder {
-     pub fn had_first_update(&self) -> bool { *self.had_first_update.borrow() }
-     pub fn set_had_first_update(&self) { *self.had_first_update.borrow_mut() = true; }
+     pub fn had_first_update(&self) -> bool { self.state.borrow().had_first_update }
+     pub fn set_had_first_update(&self) { self.state.borrow_mut().had_first_update = true; }
+
+     pub fn enabled(&self) -> bool { self.state.borrow().enabled }
+     pub fn set_enabled(&self, enabled: bool) { self.state.borrow_mut().enabled = enabled; }
+
+     pub fn was_active(&self) -> bool { self.state.borrow().was_active }
+     pub fn set_was_active(&self, was_active: bool) { self.state.borrow_mut().was_active = was_active; }
+
+     pub fn removed(&self) -> bool { self.state.borrow().removed }
+     pub fn set_removed(&self) { self.state.borrow_mut().removed = true; }
+
+     // enabled, not removed and given on_enable, so it should be updated and drawn if its game object is enabled
+     pub fn active(&self) -> bool { self.enabled() && !self.removed() && self.was_active() }
+
+     pub fn component(&self) -> &Rc<RefCell<dyn Component>> { &self.component }
 
-     pub fn component(&self) -> &Box<Rc<RefCell<dyn Component>>> { &self.component }
+     // None if the component isn't a T
+     pub fn downcast<T: 'static>(&self) -> Option<Weak<RefCell<T>>> {
+         return self.component_as_any.downcast_ref::<Rc<RefCell<T>>>().map(Rc::downgrade);
+     }
  }
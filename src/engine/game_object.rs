@@ -1,45 +1,305 @@
-use std::any::Any;
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
 use crate::Component;
 use crate::engine::app::App;
 use crate::engine::component_holder::ComponentHolder;
 
+static NEXT_GAME_OBJECT_ID: AtomicU64 = AtomicU64::new(1);
+
+// names a game object for as long as the app runs. ids are never used again, even after the object is destroyed
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GameObjectId(u64);
+
 pub struct GameObject {
+    id: GameObjectId,
+    /* shared with the app so it can search them while the game object is borrowed */
+    name: Rc<RefCell<String>>,
+    // "" for none
+    tag: Rc<RefCell<String>>,
     components: Rc<RefCell<Vec<ComponentHolder>>>,
-    components_as_any: Vec<Box<dyn Any>>,
+    enabled: bool,
 }
 
 impl GameObject {
     pub fn new() -> Self {
         return Self {
+            id: GameObjectId(NEXT_GAME_OBJECT_ID.fetch_add(1, Ordering::Relaxed)),
+            name: Rc::new(RefCell::new(String::new())),
+            tag: Rc::new(RefCell::new(String::new())),
             components: Rc::new(RefCell::new(Vec::new())),
-            components_as_any: Vec::new(),
+            enabled: true,
         };
     }
 }
 
 impl GameObject {
+    pub fn id(&self) -> GameObjectId { self.id }
+
+    pub fn name(&self) -> String { self.name.borrow().clone() }
+    pub fn set_name(&mut self, name: &str) { *self.name.borrow_mut() = String::from(name); }
+    pub fn name_rc(&self) -> Rc<RefCell<String>> { Rc::clone(&self.name) }
+
+    pub fn tag(&self) -> String { self.tag.borrow().clone() }
+    pub fn set_tag(&mut self, tag: &str) { *self.tag.borrow_mut() = String::from(tag); }
+    pub fn tag_rc(&self) -> Rc<RefCell<String>> { Rc::clone(&self.tag) }
+
+    // it gets on_enable before the next phase, so it takes part in the rest of the frame
     pub fn add_component(&mut self, mut component: impl Component + 'static, app: &App) {
         component.on_add_to_game_object(self, &app);
+        self.components.borrow_mut().push(ComponentHolder::new(component));
+    }
 
-        let component_rc_1 = Rc::new(RefCell::new(component));
-        let component_rc_2 = Rc::clone(&component_rc_1);
+    pub fn get_component<T: 'static>(&self) -> Option<Weak<RefCell<T>>> {
+        return self.get_components::<T>().into_iter().next();
+    }
 
-        self.components_as_any.push(Box::new(component_rc_1));
-        self.components.borrow_mut().push(ComponentHolder::new(Box::new(component_rc_2)));
+    // every component of type T, in the order they were added
+    pub fn get_components<T: 'static>(&self) -> Vec<Weak<RefCell<T>>> {
+        return self.components.borrow().iter()
+            .filter(|component| !component.removed())
+            .filter_map(|component| component.downcast::<T>())
+            .collect();
     }
 
-    pub fn get_component<T: 'static>(&self) -> Option<Weak<RefCell<T>>> {
-        for component in self.components_as_any.iter() {
-            if let Some(component) = component.downcast_ref::<Rc<RefCell<T>>>() {
-                return Some(Rc::downgrade(component));
+    // removes the first component of type T. it stops getting updates straight away, and gets on_disable and
+    // on_destroy before the next phase. false if there wasn't one
+    pub fn remove_component<T: 'static>(&mut self) -> bool {
+        return match self.index_of::<T>() {
+            Some(i) => {
+                self.components.borrow()[i].set_removed();
+                true
             }
+            None => false,
+        };
+    }
+
+    // removes every component, for when the whole game object is destroyed
+    pub fn remove_all_components(&mut self) {
+        for component in self.components.borrow().iter() {
+            component.set_removed();
         }
-        return None;
     }
 
+    // a disabled component gets no updates and isn't drawn, but keeps its state. on_enable and on_disable are called
+    // before the next phase. false if there isn't a component of type T
+    pub fn set_component_enabled<T: 'static>(&mut self, enabled: bool) -> bool {
+        return match self.index_of::<T>() {
+            Some(i) => {
+                self.components.borrow()[i].set_enabled(enabled);
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn is_component_enabled<T: 'static>(&self) -> bool {
+        return match self.index_of::<T>() {
+            Some(i) => self.components.borrow()[i].enabled(),
+            None => false,
+        };
+    }
+
+    pub fn enabled(&self) -> bool { self.enabled }
+    // the same as disabling every component on it, without changing their own enabled flags
+    pub fn set_enabled(&mut self, enabled: bool) { self.enabled = enabled; }
+
     pub fn components(&self) -> Rc<RefCell<Vec<ComponentHolder>>> {
         return Rc::clone(&self.components);
     }
+
+    // calls on_enable and on_disable for components that have changed since last time, then on_destroy for removed
+    // ones and drops them. the scheduler calls this before every phase, when none of the components are borrowed
+    pub fn update_lifecycle(&mut self, app: &App) {
+        let mut i = 0;
+        while i < self.components.borrow().len() {
+            let (component, active, was_active, removed) = {
+                let components = self.components.borrow();
+                let holder = &components[i];
+                let active = self.enabled && holder.enabled() && !holder.removed();
+                (Rc::clone(holder.component()), active, holder.was_active(), holder.removed())
+            };
+
+            if active != was_active {
+                self.components.borrow()[i].set_was_active(active);
+                if active {
+                    component.borrow_mut().on_enable(self, app);
+                } else {
+                    component.borrow_mut().on_disable(self, app);
+                }
+            }
+
+            if removed {
+                component.borrow_mut().on_destroy(self, app);
+                self.components.borrow_mut().remove(i);
+                continue;
+            }
+            i += 1;
+        }
+    }
+
+    // the first component of type T that hasn't been removed
+    fn index_of<T: 'static>(&self) -> Option<usize> {
+        return self.components.borrow().iter()
+            .position(|component| !component.removed() && component.downcast::<T>().is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::Component;
+    use crate::engine::app::App;
+    use crate::engine::game_object::GameObject;
+    use crate::engine::platform::mock::MockPlatform;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    // writes "<name> <callback>" to the log for on_enable, on_disable and on_destroy
+    struct Recorder {
+        name: &'static str,
+        log: Log,
+    }
+
+    impl Component for Recorder {
+        fn on_enable(&mut self, _: &mut GameObject, _: &App) { self.log.borrow_mut().push(format!("{} enable", self.name)); }
+        fn on_disable(&mut self, _: &mut GameObject, _: &App) { self.log.borrow_mut().push(format!("{} disable", self.name)); }
+        fn on_destroy(&mut self, _: &mut GameObject, _: &App) { self.log.borrow_mut().push(format!("{} destroy", self.name)); }
+    }
+
+    struct Other;
+
+    impl Component for Other {}
+
+    fn new_app() -> Rc<RefCell<App>> {
+        return App::new(Box::new(MockPlatform::new((100, 100)).0));
+    }
+
+    fn taken(log: &Log) -> Vec<String> {
+        return log.borrow_mut().drain(..).collect();
+    }
+
+    // a game object with recorders a and b, that have had on_enable
+    fn two_recorders(app: &App, log: &Log) -> GameObject {
+        let mut game_object = GameObject::new();
+        game_object.add_component(Recorder { name: "a", log: Rc::clone(log) }, app);
+        game_object.add_component(Other, app);
+        game_object.add_component(Recorder { name: "b", log: Rc::clone(log) }, app);
+        game_object.update_lifecycle(app);
+        assert_eq!(taken(log), ["a enable", "b enable"]);
+        return game_object;
+    }
+
+    #[test]
+    fn get_components_finds_every_one_in_order() {
+        let app = new_app();
+        let app = app.borrow();
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let game_object = two_recorders(&app, &log);
+
+        let recorders = game_object.get_components::<Recorder>();
+        let names: Vec<&str> = recorders.iter().map(|recorder| recorder.upgrade().unwrap().borrow().name).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(game_object.get_components::<Other>().len(), 1);
+        assert!(game_object.get_components::<String>().is_empty());
+        assert_eq!(game_object.get_component::<Recorder>().unwrap().upgrade().unwrap().borrow().name, "a");
+    }
+
+    #[test]
+    fn removed_components_are_gone_straight_away_and_destroyed_at_the_next_lifecycle_update() {
+        let app = new_app();
+        let app = app.borrow();
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let mut game_object = two_recorders(&app, &log);
+
+        assert!(game_object.remove_component::<Recorder>());
+        let recorders = game_object.get_components::<Recorder>();
+        assert_eq!(recorders.len(), 1);
+        assert_eq!(recorders[0].upgrade().unwrap().borrow().name, "b");
+        assert!(taken(&log).is_empty());
+
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a disable", "a destroy"]);
+        assert_eq!(game_object.components().borrow().len(), 2);
+
+        assert!(game_object.remove_component::<Recorder>());
+        assert!(!game_object.remove_component::<Recorder>());
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["b disable", "b destroy"]);
+    }
+
+    #[test]
+    fn disabling_and_enabling_a_component() {
+        let app = new_app();
+        let app = app.borrow();
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let mut game_object = two_recorders(&app, &log);
+
+        assert!(game_object.set_component_enabled::<Recorder>(false));
+        assert!(!game_object.is_component_enabled::<Recorder>());
+        // still found, disabled components keep their state
+        assert_eq!(game_object.get_components::<Recorder>().len(), 2);
+        assert!(!game_object.components().borrow()[0].active());
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a disable"]);
+
+        // nothing changed, nothing is called
+        game_object.update_lifecycle(&app);
+        assert!(taken(&log).is_empty());
+
+        game_object.set_component_enabled::<Recorder>(true);
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a enable"]);
+        assert!(game_object.is_component_enabled::<Recorder>());
+        assert!(!game_object.set_component_enabled::<String>(false));
+    }
+
+    #[test]
+    fn removing_a_disabled_component_only_destroys_it() {
+        let app = new_app();
+        let app = app.borrow();
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let mut game_object = two_recorders(&app, &log);
+
+        game_object.set_component_enabled::<Recorder>(false);
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a disable"]);
+
+        game_object.remove_component::<Recorder>();
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a destroy"]);
+    }
+
+    #[test]
+    fn disabling_the_game_object_disables_every_component() {
+        let app = new_app();
+        let app = app.borrow();
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let mut game_object = two_recorders(&app, &log);
+
+        game_object.set_enabled(false);
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a disable", "b disable"]);
+        // their own flags don't change
+        assert!(game_object.is_component_enabled::<Recorder>());
+
+        game_object.set_enabled(true);
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a enable", "b enable"]);
+    }
+
+    #[test]
+    fn remove_all_components_destroys_every_one() {
+        let app = new_app();
+        let app = app.borrow();
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let mut game_object = two_recorders(&app, &log);
+
+        game_object.remove_all_components();
+        assert!(game_object.get_components::<Recorder>().is_empty());
+        game_object.update_lifecycle(&app);
+        assert_eq!(taken(&log), ["a disable", "a destroy", "b disable", "b destroy"]);
+        assert!(game_object.components().borrow().is_empty());
+    }
 }
@@ -2,3 +2,6 @@ pub mod component;
 pub mod game_object;
 pub mod app;
 pub mod component_holder;
+pub mod transform;
+pub mod scheduler;
+pub mod platform;
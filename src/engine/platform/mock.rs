@@ -0,0 +1,245 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::{Document, HtmlCanvasElement, HtmlElement, WebGl2RenderingContext};
+use crate::engine::platform::{Platform, PlatformEvent};
+
+pub struct MockFrame {
+    pub time: f64,
+    pub events: Vec<PlatformEvent>,
+}
+
+impl MockFrame {
+    pub fn new(time: f64) -> Self {
+        return Self {
+            time,
+            events: Vec::new(),
+        };
+    }
+
+    pub fn with_event(mut self, event: PlatformEvent) -> Self {
+        self.events.push(event);
+        return self;
+    }
+}
+
+struct MockState {
+    surface_size: (i32, i32),
+    events: Vec<PlatformEvent>,
+    frame: Option<Box<dyn FnMut(f64)>>,
+}
+
+/*
+    A platform without a browser. There is no gl context or dom so only components that don't use them can run.
+    Frames only happen when the MockPlatformHandle is told to run them
+ */
+pub struct MockPlatform {
+    state: Rc<RefCell<MockState>>,
+}
+
+pub struct MockPlatformHandle {
+    state: Rc<RefCell<MockState>>,
+}
+
+impl MockPlatform {
+    pub fn new(surface_size: (i32, i32)) -> (Self, MockPlatformHandle) {
+        let state = Rc::new(RefCell::new(MockState {
+            surface_size,
+            events: Vec::new(),
+            frame: None,
+        }));
+
+        return (Self { state: Rc::clone(&state) }, MockPlatformHandle { state });
+    }
+}
+
+impl MockPlatformHandle {
+    pub fn run_frame(&self, frame: MockFrame) {
+        {
+            let mut state = self.state.borrow_mut();
+            for event in frame.events {
+                if let PlatformEvent::Resize(size) = event {
+                    state.surface_size = size;
+                }
+                state.events.push(event);
+            }
+        }
+
+        // taken out while it runs because the app will poll events from the same state
+        let mut frame_fn = self.state.borrow_mut().frame.take().expect("App has not started the platform");
+        frame_fn(frame.time);
+        self.state.borrow_mut().frame = Some(frame_fn);
+    }
+
+    pub fn run_frames(&self, frames: impl IntoIterator<Item = MockFrame>) {
+        for frame in frames {
+            self.run_frame(frame);
+        }
+    }
+}
+
+impl Platform for MockPlatform {
+    fn start(&mut self, frame: Box<dyn FnMut(f64)>) {
+        self.state.borrow_mut().frame = Some(frame);
+    }
+
+    fn surface_size(&self) -> (i32, i32) {
+        return self.state.borrow().surface_size;
+    }
+
+    fn poll_events(&mut self) -> Vec<PlatformEvent> {
+        return self.state.borrow_mut().events.drain(..).collect();
+    }
+
+    fn gl(&self) -> Option<&WebGl2RenderingContext> { None }
+    fn canvas(&self) -> Option<&HtmlCanvasElement> { None }
+    fn document(&self) -> Option<&Document> { None }
+    fn body(&self) -> Option<&HtmlElement> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::Component;
+    use crate::engine::app::App;
+    use crate::engine::app::input::{Button, Pointer, PointerKind};
+    use crate::engine::game_object::GameObject;
+    use crate::engine::platform::PlatformEvent;
+    use crate::engine::platform::mock::{MockFrame, MockPlatform, MockPlatformHandle};
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    // writes what look sees of the app to the log in every phase of every frame
+    struct Watcher {
+        log: Log,
+        look: fn(&App) -> String,
+    }
+
+    impl Watcher {
+        fn record(&self, phase: &str, app: &App) {
+            self.log.borrow_mut().push(format!("{} {}", phase, (self.look)(app)));
+        }
+    }
+
+    impl Component for Watcher {
+        fn on_update(&mut self, _: &mut GameObject, app: &App) { self.record("update", app); }
+        fn on_pre_render(&mut self, _: &mut GameObject, app: &App) { self.record("pre_render", app); }
+        fn on_render(&mut self, _: &mut GameObject, app: &App) { self.record("render", app); }
+        fn on_late_update(&mut self, _: &mut GameObject, app: &App) { self.record("late_update", app); }
+    }
+
+    fn new_app(look: fn(&App) -> String) -> (Rc<RefCell<App>>, MockPlatformHandle, Log) {
+        let (platform, handle) = MockPlatform::new((100, 50));
+        let app = App::new(Box::new(platform));
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.add_component(Watcher { log: Rc::clone(&log), look }, &app);
+            app.add_game_object(game_object);
+        }
+        return (app, handle, log);
+    }
+
+    // only what was seen during update, one entry per frame
+    fn updates(log: &Log) -> Vec<String> {
+        return log.borrow_mut().drain(..)
+            .filter_map(|entry| entry.strip_prefix("update ").map(String::from))
+            .collect();
+    }
+
+    fn mouse(position: (i32, i32), buttons: u16) -> PlatformEvent {
+        return PlatformEvent::Pointer(Pointer {
+            id: 1,
+            kind: PointerKind::Mouse,
+            position,
+            buttons,
+            pressure: 1.0,
+            tilt: (0.0, 0.0),
+            primary: true,
+        });
+    }
+
+    #[test]
+    fn every_frame_runs_the_phases_in_order() {
+        let (_app, handle, log) = new_app(|_| String::new());
+        handle.run_frames([MockFrame::new(0.0), MockFrame::new(1.0)]);
+
+        let phases: Vec<String> = log.borrow().iter().map(|entry| entry.trim().to_string()).collect();
+        assert_eq!(phases, ["update", "pre_render", "render", "late_update", "update", "pre_render", "render", "late_update"]);
+    }
+
+    #[test]
+    fn time_starts_at_the_first_frame() {
+        let (_app, handle, log) = new_app(|app| format!("{} {}", app.time().unscaled_time(), app.time().delta_time()));
+        handle.run_frames([MockFrame::new(10.0), MockFrame::new(10.5), MockFrame::new(11.25)]);
+
+        assert_eq!(updates(&log), ["0 0", "0.5 0.5", "1.25 0.75"]);
+    }
+
+    #[test]
+    fn keys_go_down_are_held_and_come_up() {
+        let (_app, handle, log) = new_app(|app| {
+            let input = app.input();
+            return format!("{} {} {}", input.get_key_down("a"), input.get_key("a"), input.get_key_up("a"));
+        });
+        handle.run_frames([
+            MockFrame::new(0.0).with_event(PlatformEvent::KeyDown(String::from("a"))),
+            MockFrame::new(0.1),
+            MockFrame::new(0.2).with_event(PlatformEvent::KeyUp(String::from("a"))),
+            MockFrame::new(0.3),
+        ]);
+
+        assert_eq!(updates(&log), ["true true false", "false true false", "false false true", "false false false"]);
+    }
+
+    #[test]
+    fn blur_releases_held_keys() {
+        let (_app, handle, log) = new_app(|app| format!("{}", app.input().get_key("a")));
+        handle.run_frames([
+            MockFrame::new(0.0).with_event(PlatformEvent::KeyDown(String::from("a"))),
+            MockFrame::new(0.1).with_event(PlatformEvent::Blur),
+        ]);
+
+        assert_eq!(updates(&log), ["true", "false"]);
+    }
+
+    #[test]
+    fn wheel_adds_up_within_a_frame_and_resets_after_it() {
+        let (_app, handle, log) = new_app(|app| format!("{}", app.input().wheel_delta()));
+        handle.run_frames([
+            MockFrame::new(0.0).with_event(PlatformEvent::Wheel(3.0)).with_event(PlatformEvent::Wheel(-1.0)),
+            MockFrame::new(0.1),
+        ]);
+
+        assert_eq!(updates(&log), ["2", "0"]);
+    }
+
+    #[test]
+    fn primary_pointer_moves_the_mouse_and_presses_buttons() {
+        let (_app, handle, log) = new_app(|app| {
+            let input = app.input();
+            return format!("{:?} {} {}", input.mouse_position(), input.get_button_down(Button::Left), input.get_button(Button::Left));
+        });
+        handle.run_frames([
+            MockFrame::new(0.0).with_event(mouse((10, 20), 0)),
+            MockFrame::new(0.1).with_event(mouse((12, 24), 1)),
+            MockFrame::new(0.2),
+            MockFrame::new(0.3).with_event(PlatformEvent::PointerLeave(1)),
+        ]);
+
+        assert_eq!(updates(&log), ["(10, 20) false false", "(12, 24) true true", "(12, 24) false true", "(12, 24) false false"]);
+    }
+
+    #[test]
+    fn resize_changes_the_screen_size() {
+        let (app, handle, log) = new_app(|app| format!("{:?}", app.screen().size()));
+        handle.run_frames([
+            MockFrame::new(0.0),
+            MockFrame::new(0.1).with_event(PlatformEvent::Resize((200, 80))),
+        ]);
+
+        assert_eq!(updates(&log), ["(100, 50)", "(200, 80)"]);
+        assert_eq!(app.borrow().platform().surface_size(), (200, 80));
+    }
+}
@@ -0,0 +1,39 @@
+use web_sys::{Document, HtmlCanvasElement, HtmlElement, WebGl2RenderingContext};
+use crate::engine::app::input::Pointer;
+
+pub mod web;
+pub mod mock;
+
+pub enum PlatformEvent {
+    Resize((i32, i32)),
+    // a pointer went down, moved or went up
+    Pointer(Pointer),
+    // a pointer left the canvas, was lifted off the screen or was cancelled, by the id it had
+    PointerLeave(i32),
+    // pixels scrolled, positive is down
+    Wheel(f32),
+    // by key_name
+    KeyDown(String),
+    KeyUp(String),
+    // the page lost focus, whatever was held won't get a key up
+    Blur,
+}
+
+/*
+    Everything App needs from the outside world. The browser is one implementation, MockPlatform lets tests
+    script frames and input without one. Platforms that don't have a gl context or a dom return None
+ */
+pub trait Platform {
+    // called once by App. the platform should call frame every frame with the current time in seconds
+    fn start(&mut self, frame: Box<dyn FnMut(f64)>);
+
+    fn surface_size(&self) -> (i32, i32);
+
+    // input and resize events since the last call
+    fn poll_events(&mut self) -> Vec<PlatformEvent>;
+
+    fn gl(&self) -> Option<&WebGl2RenderingContext>;
+    fn canvas(&self) -> Option<&HtmlCanvasElement>;
+    fn document(&self) -> Option<&Document>;
+    fn body(&self) -> Option<&HtmlElement>;
+}
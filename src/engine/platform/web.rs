@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::Closure;
+use web_sys::{Document, HtmlCanvasElement, HtmlElement, WebGl2RenderingContext, Window};
+use crate::engine::app::input::{key_name, Pointer, PointerKind};
+use crate::engine::platform::{Platform, PlatformEvent};
+
+pub struct WebPlatform {
+    window: Rc<Window>,
+    canvas: HtmlCanvasElement,
+    gl: WebGl2RenderingContext,
+    document: Document,
+    body: HtmlElement,
+    events: Rc<RefCell<Vec<PlatformEvent>>>,
+}
+
+impl WebPlatform {
+    pub fn new() -> Self {
+        let window = Rc::new(web_sys::window().expect("no global `window` exists"));
+        let document = window.document().unwrap();
+        let body = document.body().expect("document should have a body");
+
+        let (width, height) = window_size(&window);
+
+        let canvas = document.create_element("canvas").unwrap();
+        canvas.set_id("main_canvas");
+        canvas.set_attribute("width", &width.to_string()).unwrap();
+        canvas.set_attribute("height", &height.to_string()).unwrap();
+        body.append_child(&canvas).unwrap();
+        let canvas: HtmlCanvasElement = canvas.dyn_into::<HtmlCanvasElement>().unwrap();
+
+        let gl = canvas
+            .get_context("webgl2").unwrap()
+            .unwrap()
+            .dyn_into::<WebGl2RenderingContext>().unwrap();
+
+        return Self {
+            window,
+            canvas,
+            gl,
+            document,
+            body,
+            events: Rc::new(RefCell::new(Vec::new())),
+        };
+    }
+}
+
+impl Platform for WebPlatform {
+    fn start(&mut self, mut frame: Box<dyn FnMut(f64)>) {
+        let window = Rc::clone(&self.window);
+        let canvas = self.canvas.clone();
+        let events = Rc::clone(&self.events);
+        let event_closure = Closure::<dyn FnMut()>::new(move || {
+            let (width, height) = window_size(&window);
+            canvas.set_attribute("width", &width.to_string()).unwrap();
+            canvas.set_attribute("height", &height.to_string()).unwrap();
+            events.borrow_mut().push(PlatformEvent::Resize((width, height)));
+        });
+        self.window.add_event_listener_with_callback("resize", event_closure.as_ref().unchecked_ref()).unwrap();
+        event_closure.forget();
+
+        // pointer events so pens and touch work as well as the mouse
+        self.canvas.style().set_property("touch-action", "none").unwrap();
+
+        let events = Rc::clone(&self.events);
+        let canvas = self.canvas.clone();
+        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::PointerEvent| {
+            // so a drag that goes off the canvas still gets its move and up events
+            if event.type_() == "pointerdown" {
+                canvas.set_pointer_capture(event.pointer_id()).ok();
+            }
+            events.borrow_mut().push(PlatformEvent::Pointer(pointer_from_event(&event)));
+        });
+        for event_type in ["pointerdown", "pointermove", "pointerup"] {
+            self.canvas.add_event_listener_with_callback(event_type, event_closure.as_ref().unchecked_ref()).unwrap();
+        }
+        event_closure.forget();
+
+        let events = Rc::clone(&self.events);
+        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::PointerEvent| {
+            events.borrow_mut().push(PlatformEvent::PointerLeave(event.pointer_id()));
+        });
+        for event_type in ["pointerleave", "pointercancel"] {
+            self.canvas.add_event_listener_with_callback(event_type, event_closure.as_ref().unchecked_ref()).unwrap();
+        }
+        event_closure.forget();
+
+        // middle clicking would start the browser's autoscroll instead of panning
+        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
+            if event.button() == 1 {
+                event.prevent_default();
+            }
+        });
+        self.canvas.add_event_listener_with_callback("mousedown", event_closure.as_ref().unchecked_ref()).unwrap();
+        event_closure.forget();
+
+        let events = Rc::clone(&self.events);
+        let window = Rc::clone(&self.window);
+        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::WheelEvent| {
+            // so the page doesn't scroll or zoom instead
+            event.prevent_default();
+            let pixels_per_unit = match event.delta_mode() {
+                web_sys::WheelEvent::DOM_DELTA_LINE => 16.0,
+                web_sys::WheelEvent::DOM_DELTA_PAGE => window_size(&window).1 as f64,
+                _ => 1.0,
+            };
+            events.borrow_mut().push(PlatformEvent::Wheel((event.delta_y() * pixels_per_unit) as f32));
+        });
+        self.canvas.add_event_listener_with_callback("wheel", event_closure.as_ref().unchecked_ref()).unwrap();
+        event_closure.forget();
+
+        let events = Rc::clone(&self.events);
+        let event_closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
+            // typing into the ui isn't a shortcut, and neither is pressing a focused button
+            let tag_name = event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()).map(|element| element.tag_name());
+            let for_ui = match tag_name.as_deref() {
+                Some("INPUT") | Some("TEXTAREA") | Some("SELECT") => true,
+                Some("BUTTON") => event.key() == " " || event.key() == "Enter",
+                _ => false,
+            };
+            // ctrl r and the like are the browser's
+            let modified = event.ctrl_key() || event.meta_key() || event.alt_key();
+            if (for_ui || modified) && event.type_() == "keydown" {
+                return;
+            }
+            let key = key_name(&event.key());
+            events.borrow_mut().push(if event.type_() == "keydown" { PlatformEvent::KeyDown(key) } else { PlatformEvent::KeyUp(key) });
+        });
+        for event_type in ["keydown", "keyup"] {
+            self.window.add_event_listener_with_callback(event_type, event_closure.as_ref().unchecked_ref()).unwrap();
+        }
+        event_closure.forget();
+
+        let events = Rc::clone(&self.events);
+        let event_closure = Closure::<dyn FnMut()>::new(move || {
+            events.borrow_mut().push(PlatformEvent::Blur);
+        });
+        self.window.add_event_listener_with_callback("blur", event_closure.as_ref().unchecked_ref()).unwrap();
+        event_closure.forget();
+
+        let animation_loop_closure = Rc::new(RefCell::new(None::<Closure<dyn FnMut(_)>>));
+        let animation_loop_closure_outer = animation_loop_closure.clone();
+
+        let window = Rc::clone(&self.window);
+        *animation_loop_closure_outer.borrow_mut() = Some(Closure::<dyn FnMut(_)>::new(move |now: f64| {
+            frame(now * 0.001);
+
+            window.request_animation_frame(animation_loop_closure.borrow().as_ref().unwrap().as_ref().unchecked_ref()).expect("request_animation_frame failed");
+        }));
+        self.window.request_animation_frame(animation_loop_closure_outer.borrow().as_ref().unwrap().as_ref().unchecked_ref()).expect("request_animation_frame failed");
+    }
+
+    fn surface_size(&self) -> (i32, i32) {
+        return window_size(&self.window);
+    }
+
+    fn poll_events(&mut self) -> Vec<PlatformEvent> {
+        return self.events.borrow_mut().drain(..).collect();
+    }
+
+    fn gl(&self) -> Option<&WebGl2RenderingContext> { Some(&self.gl) }
+    fn canvas(&self) -> Option<&HtmlCanvasElement> { Some(&self.canvas) }
+    fn document(&self) -> Option<&Document> { Some(&self.document) }
+    fn body(&self) -> Option<&HtmlElement> { Some(&self.body) }
+}
+
+fn window_size(window: &Window) -> (i32, i32) {
+    let width = window.inner_width().unwrap().as_f64().unwrap() as i32;
+    let height = window.inner_height().unwrap().as_f64().unwrap() as i32;
+    return (width, height);
+}
+
+fn pointer_from_event(event: &web_sys::PointerEvent) -> Pointer {
+    let kind = match event.pointer_type().as_str() {
+        "pen" => PointerKind::Pen,
+        "touch" => PointerKind::Touch,
+        _ => PointerKind::Mouse,
+    };
+    // devices that can't tell how hard they're pressed report 0.5 while down, and a mouse is always all the way
+    let pressure = if kind == PointerKind::Mouse || event.pressure() <= 0.0 { 1.0 } else { event.pressure() };
+    return Pointer {
+        id: event.pointer_id(),
+        kind,
+        position: (event.offset_x(), event.offset_y()),
+        buttons: event.buttons(),
+        pressure,
+        tilt: (event.tilt_x() as f32, event.tilt_y() as f32),
+        primary: event.is_primary(),
+    };
+}
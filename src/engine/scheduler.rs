@@ -0,0 +1,602 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use glam::Mat4;
+use crate::Component;
+use crate::engine::app::App;
+use crate::engine::component_holder::ComponentHolder;
+use crate::engine::game_object::{GameObject, GameObjectId};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    FirstUpdate,
+    Update,
+    PreRender,
+    Render,
+    LateUpdate,
+}
+
+impl Phase {
+    // in the order they run every frame
+    pub fn all() -> [Phase; 5] {
+        return [Phase::FirstUpdate, Phase::Update, Phase::PreRender, Phase::Render, Phase::LateUpdate];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Phase::FirstUpdate => "first_update",
+            Phase::Update => "update",
+            Phase::PreRender => "pre_render",
+            Phase::Render => "render",
+            Phase::LateUpdate => "late_update",
+        };
+    }
+}
+
+// a change to the scene asked for while it's running, made at the next flush
+enum Command {
+    AddGameObject(GameObject),
+    DestroyGameObject(GameObjectId),
+}
+
+/* a game object, and what the scheduler keeps about it so it can be searched while the game object is borrowed */
+struct SceneEntry {
+    id: GameObjectId,
+    name: Rc<RefCell<String>>,
+    tag: Rc<RefCell<String>>,
+    components: Rc<RefCell<Vec<ComponentHolder>>>,
+    game_object: Rc<RefCell<GameObject>>,
+}
+
+/*
+    Runs the game objects through the phases of a frame. Before every phase it copies the list of game objects and
+    their components and lets go of it, so nothing the scheduler owns is borrowed while a component runs. Adding and
+    destroying game objects goes through a command buffer that is flushed before every phase and at the end of the
+    frame, along with components that were added, removed, enabled or disabled. Something added in the middle of a
+    frame gets on_enable at the next flush, then on_first_update just before the first phase it takes part in
+ */
+pub struct Scheduler {
+    scene: RefCell<Vec<SceneEntry>>,
+    commands: RefCell<Vec<Command>>,
+    /* the view and projection of every draw asked for during render */
+    draws: RefCell<Vec<(Mat4, Mat4)>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        return Self {
+            scene: RefCell::new(Vec::new()),
+            commands: RefCell::new(Vec::new()),
+            draws: RefCell::new(Vec::new()),
+        };
+    }
+}
+
+impl Scheduler {
+    pub fn add_game_object(&self, game_object: GameObject) -> GameObjectId {
+        let id = game_object.id();
+        self.commands.borrow_mut().push(Command::AddGameObject(game_object));
+        return id;
+    }
+
+    pub fn destroy_game_object(&self, id: GameObjectId) {
+        self.commands.borrow_mut().push(Command::DestroyGameObject(id));
+    }
+
+    pub fn draw(&self, view: Mat4, projection: Mat4) {
+        self.draws.borrow_mut().push((view, projection));
+    }
+
+    pub fn game_objects(&self) -> Vec<Rc<RefCell<GameObject>>> {
+        return self.scene.borrow().iter().map(|entry| Rc::clone(&entry.game_object)).collect();
+    }
+
+    pub fn get_game_object(&self, id: GameObjectId) -> Option<Rc<RefCell<GameObject>>> {
+        return self.scene.borrow().iter().find(|entry| entry.id == id).map(|entry| Rc::clone(&entry.game_object));
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<GameObjectId> {
+        return self.scene.borrow().iter().find(|entry| *entry.name.borrow() == name).map(|entry| entry.id);
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Vec<GameObjectId> {
+        return self.scene.borrow().iter().filter(|entry| *entry.tag.borrow() == tag).map(|entry| entry.id).collect();
+    }
+
+    pub fn get_component<T: 'static>(&self, id: GameObjectId) -> Option<Weak<RefCell<T>>> {
+        return self.scene.borrow().iter()
+            .find(|entry| entry.id == id)
+            .and_then(|entry| find_component::<T>(&entry.components.borrow()));
+    }
+
+    pub fn find_component_in_scene<T: 'static>(&self) -> Option<Weak<RefCell<T>>> {
+        return self.scene.borrow().iter().find_map(|entry| find_component::<T>(&entry.components.borrow()));
+    }
+
+    pub fn run_frame(&self, app: &App) {
+        for phase in Phase::all() {
+            self.flush(app);
+            self.run_phase(phase, app);
+            if phase == Phase::Render {
+                self.run_draws(app);
+            }
+        }
+        self.flush(app);
+    }
+
+    // makes the changes asked for since the last flush. whatever they ask for in turn is made as well
+    fn flush(&self, app: &App) {
+        loop {
+            let commands: Vec<Command> = self.commands.borrow_mut().drain(..).collect();
+            for command in commands {
+                match command {
+                    Command::AddGameObject(game_object) => {
+                        self.scene.borrow_mut().push(SceneEntry {
+                            id: game_object.id(),
+                            name: game_object.name_rc(),
+                            tag: game_object.tag_rc(),
+                            components: game_object.components(),
+                            game_object: Rc::new(RefCell::new(game_object)),
+                        });
+                    }
+                    Command::DestroyGameObject(id) => {
+                        let index = self.scene.borrow().iter().position(|entry| entry.id == id);
+                        // already destroyed
+                        let entry = match index {
+                            Some(i) => self.scene.borrow_mut().remove(i),
+                            None => continue,
+                        };
+                        let mut game_object = entry.game_object.borrow_mut();
+                        game_object.remove_all_components();
+                        game_object.update_lifecycle(app);
+                    }
+                }
+            }
+
+            for game_object in self.game_objects() {
+                game_object.borrow_mut().update_lifecycle(app);
+            }
+
+            if self.commands.borrow().is_empty() {
+                break;
+            }
+        }
+    }
+
+    fn run_phase(&self, phase: Phase, app: &App) {
+        for game_object in self.game_objects() {
+            let components = game_object.borrow().components().borrow().clone();
+            for component in components {
+                // checked again for every component, an earlier one may have disabled or removed it
+                if !game_object.borrow().enabled() || !component.active() {
+                    continue;
+                }
+                let mut game_object = game_object.borrow_mut();
+                let mut component_mut = component.component().borrow_mut();
+                if !component.had_first_update() {
+                    component.set_had_first_update();
+                    component_mut.on_first_update(&mut game_object, app);
+                }
+                run_component_phase(&mut *component_mut, phase, &mut game_object, app);
+            }
+        }
+    }
+
+    // every active component, once for every draw asked for
+    fn run_draws(&self, app: &App) {
+        let draws: Vec<(Mat4, Mat4)> = self.draws.borrow_mut().drain(..).collect();
+        for (view, projection) in draws {
+            for game_object in self.game_objects() {
+                let components = game_object.borrow().components().borrow().clone();
+                for component in components {
+                    if !game_object.borrow().enabled() || !component.active() {
+                        continue;
+                    }
+                    component.component().borrow_mut().draw(&mut game_object.borrow_mut(), app, &view, &projection);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        return Scheduler::new();
+    }
+}
+
+fn run_component_phase(component: &mut dyn Component, phase: Phase, game_object: &mut GameObject, app: &App) {
+    match phase {
+        // already done by run_phase
+        Phase::FirstUpdate => {}
+        Phase::Update => component.on_update(game_object, app),
+        Phase::PreRender => component.on_pre_render(game_object, app),
+        Phase::Render => component.on_render(game_object, app),
+        Phase::LateUpdate => component.on_late_update(game_object, app),
+    }
+}
+
+fn find_component<T: 'static>(components: &[ComponentHolder]) -> Option<Weak<RefCell<T>>> {
+    return components.iter()
+        .filter(|component| !component.removed())
+        .find_map(|component| component.downcast::<T>());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use glam::Mat4;
+    use crate::Component;
+    use crate::engine::app::App;
+    use crate::engine::game_object::GameObject;
+    use crate::engine::platform::mock::{MockFrame, MockPlatform, MockPlatformHandle};
+    use crate::engine::scheduler::Phase;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    // writes "<name> <callback>" to the log for every callback it gets
+    struct Recorder {
+        name: &'static str,
+        log: Log,
+    }
+
+    impl Recorder {
+        fn new(name: &'static str, log: &Log) -> Self {
+            return Self { name, log: Rc::clone(log) };
+        }
+
+        fn record(&self, callback: &str) {
+            self.log.borrow_mut().push(format!("{} {}", self.name, callback));
+        }
+    }
+
+    impl Component for Recorder {
+        fn on_enable(&mut self, _: &mut GameObject, _: &App) { self.record("enable"); }
+        fn on_first_update(&mut self, _: &mut GameObject, _: &App) { self.record("first_update"); }
+        fn on_update(&mut self, _: &mut GameObject, _: &App) { self.record("update"); }
+        fn on_pre_render(&mut self, _: &mut GameObject, _: &App) { self.record("pre_render"); }
+        fn on_render(&mut self, _: &mut GameObject, _: &App) { self.record("render"); }
+        fn on_late_update(&mut self, _: &mut GameObject, _: &App) { self.record("late_update"); }
+        fn on_disable(&mut self, _: &mut GameObject, _: &App) { self.record("disable"); }
+        fn on_destroy(&mut self, _: &mut GameObject, _: &App) { self.record("destroy"); }
+        fn draw(&mut self, _: &mut GameObject, _: &App, _: &Mat4, _: &Mat4) { self.record("draw"); }
+    }
+
+    type Action = Box<dyn FnOnce(&mut GameObject, &App)>;
+
+    // runs the action once, the first time it gets to the phase
+    struct Hook {
+        phase: Phase,
+        action: Option<Action>,
+    }
+
+    impl Hook {
+        fn new(phase: Phase, action: impl FnOnce(&mut GameObject, &App) + 'static) -> Self {
+            return Self { phase, action: Some(Box::new(action)) };
+        }
+
+        fn run(&mut self, phase: Phase, game_object: &mut GameObject, app: &App) {
+            if phase == self.phase {
+                if let Some(action) = self.action.take() {
+                    action(game_object, app);
+                }
+            }
+        }
+    }
+
+    impl Component for Hook {
+        fn on_first_update(&mut self, game_object: &mut GameObject, app: &App) { self.run(Phase::FirstUpdate, game_object, app); }
+        fn on_update(&mut self, game_object: &mut GameObject, app: &App) { self.run(Phase::Update, game_object, app); }
+        fn on_pre_render(&mut self, game_object: &mut GameObject, app: &App) { self.run(Phase::PreRender, game_object, app); }
+        fn on_render(&mut self, game_object: &mut GameObject, app: &App) { self.run(Phase::Render, game_object, app); }
+        fn on_late_update(&mut self, game_object: &mut GameObject, app: &App) { self.run(Phase::LateUpdate, game_object, app); }
+    }
+
+    fn new_app() -> (Rc<RefCell<App>>, MockPlatformHandle) {
+        let (platform, handle) = MockPlatform::new((100, 100));
+        return (App::new(Box::new(platform)), handle);
+    }
+
+    fn new_log() -> Log {
+        return Rc::new(RefCell::new(Vec::new()));
+    }
+
+    fn taken(log: &Log) -> Vec<String> {
+        return log.borrow_mut().drain(..).collect();
+    }
+
+    #[test]
+    fn phases_run_in_order() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.add_component(Recorder::new("a", &log), &app);
+            app.add_game_object(game_object);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), ["a enable", "a first_update", "a update", "a pre_render", "a render", "a late_update"]);
+
+        handle.run_frame(MockFrame::new(0.016));
+        assert_eq!(taken(&log), ["a update", "a pre_render", "a render", "a late_update"]);
+    }
+
+    #[test]
+    fn game_objects_run_in_the_order_they_were_added() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            for name in ["a", "b"] {
+                let mut game_object = GameObject::new();
+                game_object.add_component(Recorder::new(name, &log), &app);
+                app.add_game_object(game_object);
+            }
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), [
+            "a enable", "b enable",
+            "a first_update", "b first_update",
+            "a update", "b update",
+            "a pre_render", "b pre_render",
+            "a render", "b render",
+            "a late_update", "b late_update",
+        ]);
+    }
+
+    #[test]
+    fn game_object_added_mid_frame_joins_the_next_phase() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            let added_log = Rc::clone(&log);
+            game_object.add_component(Hook::new(Phase::Update, move |_, app| {
+                let mut added = GameObject::new();
+                added.add_component(Recorder::new("b", &added_log), app);
+                app.add_game_object(added);
+            }), &app);
+            app.add_game_object(game_object);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), ["b enable", "b first_update", "b pre_render", "b render", "b late_update"]);
+    }
+
+    #[test]
+    fn component_added_to_its_own_game_object_mid_frame() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.add_component(Recorder::new("a", &log), &app);
+            let added_log = Rc::clone(&log);
+            game_object.add_component(Hook::new(Phase::Update, move |game_object, app| {
+                game_object.add_component(Recorder::new("b", &added_log), app);
+            }), &app);
+            app.add_game_object(game_object);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), [
+            "a enable", "a first_update", "a update",
+            "b enable",
+            "a pre_render", "b first_update", "b pre_render",
+            "a render", "b render",
+            "a late_update", "b late_update",
+        ]);
+    }
+
+    #[test]
+    fn component_removed_mid_frame_stops_straight_away() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.add_component(Hook::new(Phase::Update, |game_object, _| {
+                assert!(game_object.remove_component::<Recorder>());
+            }), &app);
+            game_object.add_component(Recorder::new("a", &log), &app);
+            app.add_game_object(game_object);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), ["a enable", "a first_update", "a disable", "a destroy"]);
+
+        handle.run_frame(MockFrame::new(0.016));
+        assert!(taken(&log).is_empty());
+    }
+
+    #[test]
+    fn component_disabled_and_enabled_mid_frame() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.add_component(Recorder::new("a", &log), &app);
+            game_object.add_component(Hook::new(Phase::Update, |game_object, _| {
+                game_object.set_component_enabled::<Recorder>(false);
+            }), &app);
+            app.add_game_object(game_object);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), ["a enable", "a first_update", "a update", "a disable"]);
+
+        let game_object = app.borrow().game_objects()[0].clone();
+        game_object.borrow_mut().set_component_enabled::<Recorder>(true);
+        handle.run_frame(MockFrame::new(0.016));
+        assert_eq!(taken(&log), ["a enable", "a update", "a pre_render", "a render", "a late_update"]);
+    }
+
+    #[test]
+    fn game_object_destroyed_mid_frame() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut destroyed = GameObject::new();
+            destroyed.add_component(Recorder::new("b", &log), &app);
+            let id = destroyed.id();
+
+            let mut game_object = GameObject::new();
+            game_object.add_component(Hook::new(Phase::Update, move |_, app| {
+                app.destroy_game_object(id);
+                // destroying twice does nothing
+                app.destroy_game_object(id);
+            }), &app);
+            app.add_game_object(game_object);
+            app.add_game_object(destroyed);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), ["b enable", "b first_update", "b update", "b disable", "b destroy"]);
+        assert_eq!(app.borrow().game_objects().len(), 1);
+    }
+
+    #[test]
+    fn game_object_destroyed_before_it_joins() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.add_component(Recorder::new("a", &log), &app);
+            let id = app.add_game_object(game_object);
+            app.destroy_game_object(id);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), ["a destroy"]);
+        assert!(app.borrow().game_objects().is_empty());
+    }
+
+    #[test]
+    fn destroyed_game_object_is_found_until_the_commands_run() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            let mut destroyed = GameObject::new();
+            destroyed.set_name("destroyed");
+            destroyed.set_tag("enemy");
+            destroyed.add_component(Recorder::new("b", &log), &app);
+            let id = destroyed.id();
+
+            let mut game_object = GameObject::new();
+            let seen_log = Rc::clone(&log);
+            game_object.add_component(Hook::new(Phase::LateUpdate, move |_, app| {
+                app.destroy_game_object(id);
+                // still in the scene for the rest of the frame
+                assert_eq!(app.find_by_name("destroyed"), Some(id));
+                assert_eq!(app.find_by_tag("enemy"), [id]);
+                assert!(app.get_game_object(id).is_some());
+                assert!(app.get_component::<Recorder>(id).is_some());
+                seen_log.borrow_mut().push(String::from("destroyed"));
+            }), &app);
+            app.add_game_object(game_object);
+            app.add_game_object(destroyed);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert_eq!(taken(&log), [
+            "b enable", "b first_update", "b update", "b pre_render", "b render",
+            "destroyed", "b late_update", "b disable", "b destroy",
+        ]);
+
+        let app = app.borrow();
+        assert_eq!(app.find_by_name("destroyed"), None);
+        assert!(app.find_by_tag("enemy").is_empty());
+        assert_eq!(app.game_objects().len(), 1);
+    }
+
+    #[test]
+    fn find_by_name_and_tag() {
+        let (app, handle) = new_app();
+        let ids = {
+            let app = app.borrow();
+            [("camera", "main"), ("first", "enemy"), ("second", "enemy"), ("first", "")].map(|(name, tag)| {
+                let mut game_object = GameObject::new();
+                game_object.set_name(name);
+                game_object.set_tag(tag);
+                return app.add_game_object(game_object);
+            })
+        };
+
+        // not found until they join the scene
+        assert_eq!(app.borrow().find_by_name("camera"), None);
+        handle.run_frame(MockFrame::new(0.0));
+
+        let app = app.borrow();
+        assert_eq!(app.find_by_name("camera"), Some(ids[0]));
+        // the first one added wins
+        assert_eq!(app.find_by_name("first"), Some(ids[1]));
+        assert_eq!(app.find_by_name("missing"), None);
+        assert_eq!(app.find_by_tag("enemy"), [ids[1], ids[2]]);
+        assert!(app.find_by_tag("missing").is_empty());
+
+        // renaming is seen straight away
+        app.get_game_object(ids[0]).unwrap().borrow_mut().set_name("renamed");
+        assert_eq!(app.find_by_name("camera"), None);
+        assert_eq!(app.find_by_name("renamed"), Some(ids[0]));
+    }
+
+    #[test]
+    fn draws_come_after_render_and_include_the_drawing_game_object() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        {
+            let app = app.borrow();
+            for name in ["a", "b"] {
+                let mut game_object = GameObject::new();
+                game_object.add_component(Recorder::new(name, &log), &app);
+                if name == "a" {
+                    game_object.add_component(Hook::new(Phase::Render, |_, app| {
+                        app.draw_scene(Mat4::IDENTITY, Mat4::IDENTITY);
+                    }), &app);
+                }
+                app.add_game_object(game_object);
+            }
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        let log = taken(&log);
+        let render_and_draw: Vec<&str> = log.iter().map(|entry| entry.as_str())
+            .filter(|entry| entry.ends_with("render") || entry.ends_with("draw"))
+            .collect();
+        assert_eq!(render_and_draw, ["a pre_render", "b pre_render", "a render", "b render", "a draw", "b draw"]);
+    }
+
+    #[test]
+    fn components_can_be_found_while_their_game_object_is_updating() {
+        let (app, handle) = new_app();
+        let log = new_log();
+        let found = Rc::new(RefCell::new(false));
+        {
+            let app = app.borrow();
+            let mut game_object = GameObject::new();
+            game_object.set_name("a");
+            game_object.set_tag("recorders");
+            game_object.add_component(Recorder::new("a", &log), &app);
+            let found = Rc::clone(&found);
+            game_object.add_component(Hook::new(Phase::Update, move |game_object, app| {
+                let id = game_object.id();
+                assert_eq!(app.find_by_name("a"), Some(id));
+                assert_eq!(app.find_by_tag("recorders"), [id]);
+                assert!(app.get_component::<Recorder>(id).is_some());
+                *found.borrow_mut() = app.find_component_in_scene::<Recorder>().is_some();
+            }), &app);
+            app.add_game_object(game_object);
+        }
+
+        handle.run_frame(MockFrame::new(0.0));
+        assert!(*found.borrow());
+    }
+}
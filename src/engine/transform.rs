@@ -0,0 +1,257 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use glam::{Mat4, Quat, Vec3};
+use crate::{Component, GameObject};
+use crate::engine::app::App;
+
+/*
+    Where a game object is, relative to its parent's transform or the world if it has none. World space is in screen
+    pixels with y down, the same as Camera's. The world matrix is worked out when it's asked for and kept until this
+    transform or one of its parents changes
+ */
+pub struct Transform {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    parent: Option<Weak<RefCell<Transform>>>,
+    children: Vec<Weak<RefCell<Transform>>>,
+    /* None when it has to be worked out again */
+    world_matrix: RefCell<Option<Mat4>>,
+}
+
+impl Transform {
+    pub fn new() -> Self {
+        return Self {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            parent: None,
+            children: Vec::new(),
+            world_matrix: RefCell::new(None),
+        };
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        let mut transform = Transform::new();
+        transform.translation = translation;
+        return transform;
+    }
+}
+
+impl Transform {
+    pub fn translation(&self) -> Vec3 { self.translation }
+    pub fn set_translation(&mut self, translation: Vec3) {
+        self.translation = translation;
+        self.mark_changed();
+    }
+
+    pub fn rotation(&self) -> Quat { self.rotation }
+    pub fn set_rotation(&mut self, rotation: Quat) {
+        self.rotation = rotation;
+        self.mark_changed();
+    }
+
+    pub fn scale(&self) -> Vec3 { self.scale }
+    pub fn set_scale(&mut self, scale: Vec3) {
+        self.scale = scale;
+        self.mark_changed();
+    }
+
+    pub fn local_matrix(&self) -> Mat4 {
+        return Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
+    }
+
+    // local space to world space, through every parent
+    pub fn world_matrix(&self) -> Mat4 {
+        if let Some(world_matrix) = *self.world_matrix.borrow() {
+            return world_matrix;
+        }
+        let world_matrix = match self.parent() {
+            Some(parent) => parent.borrow().world_matrix() * self.local_matrix(),
+            None => self.local_matrix(),
+        };
+        *self.world_matrix.borrow_mut() = Some(world_matrix);
+        return world_matrix;
+    }
+
+    // of the game object's Transform, or the identity if it doesn't have one
+    pub fn world_matrix_of(game_object: &GameObject) -> Mat4 {
+        return match game_object.get_component::<Transform>().and_then(|transform| transform.upgrade()) {
+            Some(transform) => transform.borrow().world_matrix(),
+            None => Mat4::IDENTITY,
+        };
+    }
+
+    pub fn parent(&self) -> Option<Rc<RefCell<Transform>>> {
+        return self.parent.as_ref().and_then(|parent| parent.upgrade());
+    }
+
+    pub fn children(&self) -> Vec<Rc<RefCell<Transform>>> {
+        return self.children.iter().filter_map(|child| child.upgrade()).collect();
+    }
+
+    // the child keeps its local translation, rotation and scale, so it moves to wherever they put it under the new
+    // parent. fails if the parent is the child or one of its children
+    pub fn set_parent(child: &Rc<RefCell<Transform>>, parent: Option<&Rc<RefCell<Transform>>>) -> Result<(), String> {
+        if let Some(parent) = parent {
+            let mut ancestor = Some(Rc::clone(parent));
+            while let Some(transform) = ancestor {
+                if Rc::ptr_eq(&transform, child) {
+                    return Err(String::from("a transform can't be parented to itself or one of its children"));
+                }
+                ancestor = transform.borrow().parent();
+            }
+        }
+
+        if let Some(old_parent) = child.borrow().parent() {
+            old_parent.borrow_mut().children.retain(|other| !Weak::ptr_eq(other, &Rc::downgrade(child)));
+        }
+        if let Some(parent) = parent {
+            parent.borrow_mut().children.push(Rc::downgrade(child));
+        }
+
+        let mut child = child.borrow_mut();
+        child.parent = parent.map(Rc::downgrade);
+        child.mark_changed();
+        return Ok(());
+    }
+
+    // the cached world matrix of this and everything under it is out of date
+    fn mark_changed(&self) {
+        *self.world_matrix.borrow_mut() = None;
+        for child in self.children() {
+            child.borrow().mark_changed();
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        return Transform::new();
+    }
+}
+
+impl Component for Transform {
+    // the children are left without a parent, so their local translation, rotation and scale are in world space
+    fn on_destroy(&mut self, _game_object: &mut GameObject, _app: &App) {
+        let this = self as *const Transform;
+        if let Some(parent) = self.parent() {
+            parent.borrow_mut().children.retain(|other| other.upgrade().is_some_and(|other| !std::ptr::eq(other.as_ptr(), this)));
+        }
+        for child in self.children() {
+            let mut child = child.borrow_mut();
+            child.parent = None;
+            child.mark_changed();
+        }
+        self.children.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use glam::{Mat4, Vec3};
+    use crate::engine::app::App;
+    use crate::engine::game_object::GameObject;
+    use crate::engine::platform::mock::{MockFrame, MockPlatform};
+    use crate::engine::transform::Transform;
+
+    fn at(x: f32, y: f32) -> Rc<RefCell<Transform>> {
+        return Rc::new(RefCell::new(Transform::from_translation(Vec3::new(x, y, 0.0))));
+    }
+
+    fn world_position(transform: &Rc<RefCell<Transform>>) -> Vec3 {
+        return transform.borrow().world_matrix().transform_point3(Vec3::ZERO);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let root = at(0.0, 0.0);
+        let child = at(1.0, 0.0);
+        let grandchild = at(2.0, 0.0);
+        Transform::set_parent(&child, Some(&root)).unwrap();
+        Transform::set_parent(&grandchild, Some(&child)).unwrap();
+
+        assert!(Transform::set_parent(&root, Some(&root)).is_err());
+        assert!(Transform::set_parent(&root, Some(&child)).is_err());
+        assert!(Transform::set_parent(&root, Some(&grandchild)).is_err());
+        // nothing changed
+        assert!(root.borrow().parent().is_none());
+        assert_eq!(root.borrow().children().len(), 1);
+        assert_eq!(world_position(&grandchild), Vec3::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn reparenting_moves_the_child_between_parents() {
+        let first = at(10.0, 0.0);
+        let second = at(0.0, 20.0);
+        let child = at(1.0, 1.0);
+
+        Transform::set_parent(&child, Some(&first)).unwrap();
+        assert_eq!(world_position(&child), Vec3::new(11.0, 1.0, 0.0));
+
+        Transform::set_parent(&child, Some(&second)).unwrap();
+        assert!(first.borrow().children().is_empty());
+        assert!(Rc::ptr_eq(&second.borrow().children()[0], &child));
+        assert!(Rc::ptr_eq(&child.borrow().parent().unwrap(), &second));
+        assert_eq!(world_position(&child), Vec3::new(1.0, 21.0, 0.0));
+
+        Transform::set_parent(&child, None).unwrap();
+        assert!(second.borrow().children().is_empty());
+        assert_eq!(world_position(&child), Vec3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn moving_a_parent_updates_the_cached_world_matrix_of_its_children() {
+        let root = at(0.0, 0.0);
+        let child = at(1.0, 0.0);
+        let grandchild = at(0.0, 1.0);
+        Transform::set_parent(&child, Some(&root)).unwrap();
+        Transform::set_parent(&grandchild, Some(&child)).unwrap();
+        // caches every matrix on the way down
+        assert_eq!(world_position(&grandchild), Vec3::new(1.0, 1.0, 0.0));
+
+        root.borrow_mut().set_translation(Vec3::new(5.0, 5.0, 0.0));
+        assert_eq!(world_position(&grandchild), Vec3::new(6.0, 6.0, 0.0));
+
+        root.borrow_mut().set_scale(Vec3::splat(2.0));
+        assert_eq!(world_position(&child), Vec3::new(7.0, 5.0, 0.0));
+        assert_eq!(world_position(&grandchild), Vec3::new(7.0, 7.0, 0.0));
+
+        child.borrow_mut().set_translation(Vec3::ZERO);
+        assert_eq!(world_position(&grandchild), Vec3::new(5.0, 7.0, 0.0));
+        assert_eq!(world_position(&root), Vec3::new(5.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn destroying_a_parent_unparents_its_children() {
+        let (platform, handle) = MockPlatform::new((100, 100));
+        let app = App::new(Box::new(platform));
+        let (parent, child, grandchild) = {
+            let app = app.borrow();
+            let ids = [(10.0, 0.0), (1.0, 0.0), (0.0, 1.0)].map(|(x, y)| {
+                let mut game_object = GameObject::new();
+                game_object.add_component(Transform::from_translation(Vec3::new(x, y, 0.0)), &app);
+                return app.add_game_object(game_object);
+            });
+            (ids[0], ids[1], ids[2])
+        };
+        handle.run_frame(MockFrame::new(0.0));
+        {
+            let app = app.borrow();
+            app.set_parent(child, Some(parent)).unwrap();
+            app.set_parent(grandchild, Some(child)).unwrap();
+            assert!(app.set_parent(parent, Some(grandchild)).is_err());
+            app.destroy_game_object(child);
+        }
+        handle.run_frame(MockFrame::new(0.016));
+
+        let app = app.borrow();
+        let transform = |id| app.get_component::<Transform>(id).unwrap().upgrade().unwrap();
+        assert!(transform(parent).borrow().children().is_empty());
+        assert!(transform(grandchild).borrow().parent().is_none());
+        assert_eq!(world_position(&transform(grandchild)), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(Transform::world_matrix_of(&app.get_game_object(grandchild).unwrap().borrow()), Mat4::from_translation(Vec3::new(0.0, 1.0, 0.0)));
+    }
+}
@@ -2,16 +2,20 @@ use wasm_bindgen::prelude::*;
 use console_error_panic_hook::hook;
 use crate::engine::component::Component;
 use crate::engine::game_object::GameObject;
+use crate::engine::transform::Transform;
 use crate::utils::create_shader_program;
-use crate::components::reaction_diffusion::ReactionDiffusion;
+use crate::components::reaction_diffusion::{Backend, ReactionDiffusion};
 use crate::components::reaction_diffusion_ui::ReactionDiffusionUI;
 use crate::rendering::camera::Camera;
+use crate::simulation::seed::Seed;
+use crate::engine::platform::web::WebPlatform;
 
 #[macro_use]
 mod utils;
-mod engine;
+pub mod engine;
 mod rendering;
 mod components;
+pub mod simulation;
 
 #[wasm_bindgen(start)]
 pub fn start() -> Result<(), JsValue> {
@@ -19,18 +23,49 @@ pub fn start() -> Result<(), JsValue> {
 
     console_log!("starting app");
 
-    let app_ref = crate::engine::app::App::new();
-    app_ref.borrow_mut().init_gl();
+    let app_ref = crate::engine::app::App::new(Box::new(WebPlatform::new()));
     let app = app_ref.borrow();
 
     let mut camera = GameObject::new();
+    camera.set_name("camera");
     camera.add_component(Camera::new(), &app);
     app.add_game_object(camera);
 
-    let mut game_manager = GameObject::new();
-    game_manager.add_component(ReactionDiffusionUI::new(), &app);
-    game_manager.add_component(ReactionDiffusion::new(&app), &app);
-    app.add_game_object(game_manager);
+    let mut ui = GameObject::new();
+    ui.set_name("ui");
+    ui.add_component(ReactionDiffusionUI::new(), &app);
+    app.add_game_object(ui);
+
+    let mut simulation = GameObject::new();
+    simulation.set_name("simulation");
+    simulation.add_component(Transform::new(), &app);
+    let mut reaction_diffusion = ReactionDiffusion::new(&app, backend_from_url());
+    reaction_diffusion.set_seed(seed_from_url());
+    simulation.add_component(reaction_diffusion, &app);
+    app.add_game_object(simulation);
 
     Ok(())
 }
+
+// ?backend=cpu runs the simulation on the cpu instead of with shaders
+fn backend_from_url() -> Backend {
+    let search = web_sys::window().unwrap().location().search().unwrap_or_default();
+    let params = web_sys::UrlSearchParams::new_with_str(&search).unwrap();
+    return match params.get("backend").as_deref() {
+        Some("cpu") => Backend::Cpu,
+        _ => Backend::Gpu,
+    };
+}
+
+// ?seed=circles:20 or ?seed=text:hello starts from something other than the square in the middle, see Seed::parse
+fn seed_from_url() -> Seed {
+    let search = web_sys::window().unwrap().location().search().unwrap_or_default();
+    let params = web_sys::UrlSearchParams::new_with_str(&search).unwrap();
+    if let Some(text) = params.get("seed") {
+        match Seed::parse(&text) {
+            Ok(seed) => return seed,
+            Err(message) => console_log!("{}", message),
+        }
+    }
+    return Seed::default();
+}
@@ -1,42 +1,128 @@
+use glam::{Mat4, Vec3};
 use web_sys::WebGl2RenderingContext;
 use crate::{Component, GameObject};
 use crate::engine::app::App;
+use crate::engine::app::input::Button::Middle;
 
-pub struct Camera {}
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 64.0;
+// a wheel notch is about 100 pixels, so that zooms by about 20%
+const WHEEL_PIXELS_PER_DOUBLING: f32 = 400.0;
+
+/*
+    Where the camera looks, as a scale and offset of the screen. World positions are the screen pixels (y down) the
+    camera would put them at without any pan or zoom
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CameraView {
+    // the world position in the middle of the screen, relative to the middle of the screen
+    pub position: (f32, f32),
+    pub zoom: f32,
+}
+
+impl CameraView {
+    pub fn new() -> Self {
+        return Self {
+            position: (0.0, 0.0),
+            zoom: 1.0,
+        };
+    }
+
+    pub fn screen_to_world(&self, position: (f32, f32), screen_size: (i32, i32)) -> (f32, f32) {
+        let center = (screen_size.0 as f32 * 0.5, screen_size.1 as f32 * 0.5);
+        return (
+            (position.0 - center.0) / self.zoom + center.0 + self.position.0,
+            (position.1 - center.1) / self.zoom + center.1 + self.position.1,
+        );
+    }
+
+    // world positions to screen pixels, the other way to screen_to_world
+    pub fn view_matrix(&self, screen_size: (i32, i32)) -> Mat4 {
+        let center = Vec3::new(screen_size.0 as f32 * 0.5, screen_size.1 as f32 * 0.5, 0.0);
+        return Mat4::from_translation(center)
+            * Mat4::from_scale(Vec3::new(self.zoom, self.zoom, 1.0))
+            * Mat4::from_translation(-center - Vec3::new(self.position.0, self.position.1, 0.0));
+    }
+
+    // screen pixels, y down, to clip space
+    pub fn projection_matrix(screen_size: (i32, i32)) -> Mat4 {
+        return Mat4::orthographic_rh_gl(0.0, screen_size.0 as f32, screen_size.1 as f32, 0.0, -1.0, 1.0);
+    }
+
+    // zooms by factor keeping whatever is under the screen position where it is
+    pub fn zoom_at(&mut self, position: (f32, f32), factor: f32, screen_size: (i32, i32)) {
+        let world = self.screen_to_world(position, screen_size);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let center = (screen_size.0 as f32 * 0.5, screen_size.1 as f32 * 0.5);
+        self.position = (
+            world.0 - center.0 - (position.0 - center.0) / self.zoom,
+            world.1 - center.1 - (position.1 - center.1) / self.zoom,
+        );
+    }
+
+    // moves what's on screen by delta screen pixels
+    pub fn pan(&mut self, delta: (f32, f32)) {
+        self.position.0 -= delta.0 / self.zoom;
+        self.position.1 -= delta.1 / self.zoom;
+    }
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        return CameraView::new();
+    }
+}
+
+// pans with the middle mouse button or two fingers, zooms with the wheel or by pinching
+pub struct Camera {
+    view: CameraView,
+}
 
 impl Camera {
     pub fn new() -> Self {
-        return Self {};
+        return Self {
+            view: CameraView::new(),
+        };
+    }
+
+    pub fn view(&self) -> CameraView { self.view }
+
+    pub fn reset(&mut self) {
+        self.view = CameraView::new();
     }
 }
 
 impl Component for Camera {
+    fn on_update(&mut self, game_object: &mut GameObject, app: &App) {
+        let input = app.input();
+        let screen_size = app.screen().size();
+
+        let wheel_delta = input.wheel_delta();
+        if wheel_delta != 0.0 {
+            let mouse_position = input.mouse_position();
+            let factor = 2.0f32.powf(-wheel_delta / WHEEL_PIXELS_PER_DOUBLING);
+            self.view.zoom_at((mouse_position.0 as f32, mouse_position.1 as f32), factor, screen_size);
+        }
+
+        if input.get_button(Middle) {
+            // the mouse delta is from where it is now to where it was
+            let delta = input.mouse_delta_position();
+            self.view.pan((-delta.0 as f32, -delta.1 as f32));
+        }
+
+        if let Some(pinch) = input.pinch() {
+            self.view.pan(pinch.delta);
+            self.view.zoom_at(pinch.center, pinch.scale, screen_size);
+        }
+    }
+
     fn on_render(&mut self, game_object: &mut GameObject, app: &App) {
         let gl = app.gl();
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
         gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
 
-        // draw other game objects components
-        let game_objects = app.game_objects();
-        let game_objects_len = game_objects.len();
-        for i in 0..game_objects_len {
-            let game_object = game_objects[i].try_borrow();
-            if game_object.is_ok() {
-                let components = game_object.unwrap().components();
-                for component in components.borrow_mut().iter_mut() {
-                    let game_object = &game_objects[i];
-                    component.component().borrow_mut().draw(&mut game_object.borrow_mut(), app);
-                }
-            }
-        }
-
-        // draw this game object components excluding the camera component which is already borrowed
-        let components = game_object.components();
-        for component in components.borrow().iter() {
-            let component = component.component().try_borrow_mut();
-            if component.is_ok() {
-                component.unwrap().draw(game_object, app);
-            }
-        }
+        // everything is drawn once every component has had on_render, including what's on this game object
+        let screen_size = app.screen().size();
+        app.draw_scene(self.view.view_matrix(screen_size), CameraView::projection_matrix(screen_size));
     }
 }
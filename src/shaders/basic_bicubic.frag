@@ -2,6 +2,8 @@
 precision highp float;
 
 uniform sampler2D u_texture;
+// nearest cell instead of bicubic
+uniform bool u_pixelated;
 
 in vec2 v_uv;
 
@@ -53,5 +55,10 @@ vec4 textureBicubic(sampler2D u_sampler, vec2 texCoords) {
 }
 
 void main() {
-    outColor = textureBicubic(u_texture, v_uv);
+    if(u_pixelated) {
+        ivec2 size = textureSize(u_texture, 0);
+        outColor = texelFetch(u_texture, clamp(ivec2(v_uv * vec2(size)), ivec2(0), size - 1), 0);
+    } else {
+        outColor = textureBicubic(u_texture, v_uv);
+    }
 }
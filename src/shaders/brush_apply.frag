@@ -0,0 +1,54 @@
+#version 300 es
+precision highp float;
+precision highp int;
+
+// same order as BrushChemical::id
+const int CHEMICAL_A = 0;
+const int CHEMICAL_B = 1;
+const int CHEMICAL_ERASE = 2;
+// same order as BlendMode::id
+const int BLEND_MIX = 0;
+const int BLEND_ADD = 1;
+const int BLEND_MAX = 2;
+
+uniform highp usampler2D u_texture;
+// what brush_stamp.frag gathered this frame
+uniform sampler2D u_coverage_texture;
+uniform int u_chemical;
+uniform int u_blend_mode;
+uniform float u_strength;
+
+in vec2 v_uv;
+
+out uvec2 outColor;
+
+// the same as BlendMode::blend
+float blend(float value, float target, float amount) {
+    if(u_blend_mode == BLEND_MIX) {
+        return value + (target - value) * amount;
+    } else if(u_blend_mode == BLEND_ADD) {
+        return target > value ? min(value + amount, target) : max(value - amount, target);
+    }
+    return target > 0.5 ? max(value, amount) : min(value, 1.0 - amount);
+}
+
+void main() {
+    uvec2 cell = texture(u_texture, v_uv).rg;
+    float coverage = texture(u_coverage_texture, v_uv).r;
+    if(coverage == 0.0) {
+        outColor = cell;
+        return;
+    }
+
+    vec2 value = vec2(cell) / 65535.0;
+    float amount = clamp(coverage * u_strength, 0.0, 1.0);
+    if(u_chemical == CHEMICAL_A || u_chemical == CHEMICAL_ERASE) {
+        value.x = blend(value.x, 1.0, amount);
+    }
+    if(u_chemical == CHEMICAL_B) {
+        value.y = blend(value.y, 1.0, amount);
+    } else if(u_chemical == CHEMICAL_ERASE) {
+        value.y = blend(value.y, 0.0, amount);
+    }
+    outColor = uvec2(round(clamp(value, 0.0, 1.0) * 65535.0));
+}
@@ -0,0 +1,32 @@
+#version 300 es
+precision highp float;
+precision highp int;
+
+// same order as BrushShape::id
+const int SHAPE_CIRCLE = 0;
+const int SHAPE_SQUARE = 1;
+const int SHAPE_GAUSSIAN = 2;
+// same as GAUSSIAN_FALLOFF in simulation/brush.rs
+const float GAUSSIAN_FALLOFF = 4.0;
+
+uniform int u_shape;
+
+in vec2 v_uv;
+in float v_pressure;
+
+// drawn into an R8 texture with MAX blending so overlapping stamps keep the most either covers
+out vec4 outColor;
+
+void main() {
+    vec2 offset = (v_uv - vec2(0.5, 0.5)) * 2.0;
+    float distance_squared = dot(offset, offset);
+    float coverage = 0.0;
+    if(u_shape == SHAPE_CIRCLE) {
+        coverage = distance_squared < 1.0 ? 1.0 : 0.0;
+    } else if(u_shape == SHAPE_SQUARE) {
+        coverage = abs(offset.x) < 1.0 && abs(offset.y) < 1.0 ? 1.0 : 0.0;
+    } else if(u_shape == SHAPE_GAUSSIAN) {
+        coverage = distance_squared < 1.0 ? exp(-GAUSSIAN_FALLOFF * distance_squared) : 0.0;
+    }
+    outColor = vec4(coverage * clamp(v_pressure, 0.0, 1.0), 0.0, 0.0, 1.0);
+}
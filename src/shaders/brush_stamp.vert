@@ -0,0 +1,21 @@
+#version 300 es
+
+uniform mat4 u_view;
+uniform mat4 u_projection;
+// in cells
+uniform float u_radius;
+
+in vec4 a_position;
+in vec2 a_uv;
+// x, y in cells and pressure, one per instance
+in vec3 a_stamp;
+
+out vec2 v_uv;
+out float v_pressure;
+
+void main() {
+    v_uv = a_uv;
+    v_pressure = a_stamp.z;
+    vec4 position = vec4(a_position.xy * u_radius * 2.0 + a_stamp.xy, a_position.zw);
+    gl_Position = u_projection * u_view * position;
+}
@@ -2,90 +2,137 @@
 precision highp float;
 precision highp int;
 
-const float D_A = 1.0;
-const float D_B = 0.5;
-const float DELTA_T = 1.0;
-
 uniform float F; // 0.055
 uniform float K; // 0.062
+uniform float u_diffusion_a; // 1.0
+uniform float u_diffusion_b; // 0.5
+uniform float u_delta_t; // 1.0
+
+// 0 uses F and K everywhere, 1 is a gradient across the ranges, 2 reads the ranges from u_parameter_texture
+uniform int u_parameter_mode;
+uniform vec2 u_feed_range;
+uniform vec2 u_kill_range;
+uniform sampler2D u_parameter_texture;
 
+// one stage of an integrator, see simulation/integrator.rs. the rate of change is worked out at u_texture
 uniform highp usampler2D u_texture;
-uniform float u_kernel[9];
+uniform highp usampler2D u_start_texture;
+uniform highp usampler2D u_accumulator_texture;
+uniform float u_next_stage_offset;
+uniform float u_accumulator_weight;
+
+// 5x5 with smaller kernels in the middle, top row first
+uniform float u_kernel[25];
+uniform int u_kernel_radius;
+
+// x and y edges, see simulation/boundary.rs. 0 periodic, 1 neumann, 2 dirichlet, 3 absorbing
+uniform ivec2 u_boundary;
+uniform vec2 u_edge_value;
+
+// 1 in walls, see simulation/mask.rs. walls don't react and nothing diffuses into or out of them
+uniform sampler2D u_mask_texture;
 
 in vec2 v_uv;
 
-out uvec2 outColor;
+layout(location = 0) out uvec2 outColor;
+layout(location = 1) out uvec2 outStage;
 
-void main() {
-    vec2 onePixel = vec2(1) / vec2(textureSize(u_texture, 0));
-
-    uvec2 utexel = texture(u_texture, v_uv).rg;
-    float a = float(utexel.r) / 65535.0;
-    float b = float(utexel.g) / 65535.0;
-
-    float nabla_squared_a = 0.0;
-    float nabla_squared_b = 0.0;
-
-    uvec2 utexel0 = texture(u_texture, v_uv + onePixel * vec2(-1, 1)).rg;
-    float a0 = float(utexel0.r) / 65535.0;
-    float b0 = float(utexel0.g) / 65535.0;
-    nabla_squared_a += a0 * u_kernel[0];
-    nabla_squared_b += b0 * u_kernel[0];
-
-    uvec2 utexel1 = texture(u_texture, v_uv + onePixel * vec2(0, 1)).rg;
-    float a1 = float(utexel1.r) / 65535.0;
-    float b1 = float(utexel1.g) / 65535.0;
-    nabla_squared_a += a1 * u_kernel[1];
-    nabla_squared_b += b1 * u_kernel[1];
-
-    uvec2 utexel2 = texture(u_texture, v_uv + onePixel * vec2(1, 1)).rg;
-    float a2 = float(utexel2.r) / 65535.0;
-    float b2 = float(utexel2.g) / 65535.0;
-    nabla_squared_a += a2 * u_kernel[2];
-    nabla_squared_b += b2 * u_kernel[2];
-
-    uvec2 utexel3 = texture(u_texture, v_uv + onePixel * vec2(-1, 0)).rg;
-    float a3 = float(utexel3.r) / 65535.0;
-    float b3 = float(utexel3.g) / 65535.0;
-    nabla_squared_a += a3 * u_kernel[3];
-    nabla_squared_b += b3 * u_kernel[3];
-
-    uvec2 utexel4 = texture(u_texture, v_uv + onePixel * vec2(0, 0)).rg;
-    float a4 = float(utexel4.r) / 65535.0;
-    float b4 = float(utexel4.g) / 65535.0;
-    nabla_squared_a += a4 * u_kernel[4];
-    nabla_squared_b += b4 * u_kernel[4];
-
-    uvec2 utexel5 = texture(u_texture, v_uv + onePixel * vec2(1, 0)).rg;
-    float a5 = float(utexel5.r) / 65535.0;
-    float b5 = float(utexel5.g) / 65535.0;
-    nabla_squared_a += a5 * u_kernel[5];
-    nabla_squared_b += b5 * u_kernel[5];
-
-    uvec2 utexel6 = texture(u_texture, v_uv + onePixel * vec2(-1, -1)).rg;
-    float a6 = float(utexel6.r) / 65535.0;
-    float b6 = float(utexel6.g) / 65535.0;
-    nabla_squared_a += a6 * u_kernel[6];
-    nabla_squared_b += b6 * u_kernel[6];
-
-    uvec2 utexel7 = texture(u_texture, v_uv + onePixel * vec2(0, -1)).rg;
-    float a7 = float(utexel7.r) / 65535.0;
-    float b7 = float(utexel7.g) / 65535.0;
-    nabla_squared_a += a7 * u_kernel[7];
-    nabla_squared_b += b7 * u_kernel[7];
-
-    uvec2 utexel8 = texture(u_texture, v_uv + onePixel * vec2(1, -1)).rg;
-    float a8 = float(utexel8.r) / 65535.0;
-    float b8 = float(utexel8.g) / 65535.0;
-    nabla_squared_a += a8 * u_kernel[8];
-    nabla_squared_b += b8 * u_kernel[8];
+vec2 read_cell(highp usampler2D tex, vec2 uv) {
+    uvec2 utexel = texture(tex, uv).rg;
+    return vec2(float(utexel.r) / 65535.0, float(utexel.g) / 65535.0);
+}
 
-    // math from https://karlsims.com/rd.html
-    float a_prime = a + (D_A * nabla_squared_a - a * b * b + F * (1.0 - a)) * DELTA_T;
-    float b_prime = b + (D_B * nabla_squared_b + a * b * b - (K + F) * b) * DELTA_T;
+// same as Boundary::resolve, -1 when i is past an edge that holds a fixed value
+int resolve(int i, int size, int boundary) {
+    if (i >= 0 && i < size) {
+        return i;
+    }
+    if (boundary == 0) {
+        return i - size * int(floor(float(i) / float(size)));
+    }
+    if (boundary == 1) {
+        int period = size * 2;
+        int m = i - period * int(floor(float(i) / float(period)));
+        return m < size ? m : period - 1 - m;
+    }
+    return -1;
+}
 
-    int a_prime_int = int(round(clamp(a_prime, 0.0, 1.0) * 65535.0));
-    int b_prime_int = int(round(clamp(b_prime, 0.0, 1.0) * 65535.0));
+vec2 fixed_value(int boundary) {
+    return boundary == 3 ? vec2(0.0) : u_edge_value;
+}
 
-    outColor = uvec2(a_prime_int, b_prime_int);
+bool is_wall(ivec2 cell) {
+    return texelFetch(u_mask_texture, cell, 0).r > 0.5;
+}
+
+// same as Boundaries::neighbor. nothing flows in from a wall, so it's seen as a copy of the center cell
+vec2 read_neighbor(ivec2 cell, vec2 center_value) {
+    ivec2 size = textureSize(u_texture, 0);
+    int x = resolve(cell.x, size.x, u_boundary.x);
+    int y = resolve(cell.y, size.y, u_boundary.y);
+    if (x < 0) {
+        return fixed_value(u_boundary.x);
+    }
+    if (y < 0) {
+        return fixed_value(u_boundary.y);
+    }
+    if (is_wall(ivec2(x, y))) {
+        return center_value;
+    }
+    uvec2 utexel = texelFetch(u_texture, ivec2(x, y), 0).rg;
+    return vec2(float(utexel.r) / 65535.0, float(utexel.g) / 65535.0);
+}
+
+uvec2 to_cell(vec2 value) {
+    int a_int = int(round(clamp(value.x, 0.0, 1.0) * 65535.0));
+    int b_int = int(round(clamp(value.y, 0.0, 1.0) * 65535.0));
+    return uvec2(a_int, b_int);
+}
+
+void main() {
+    ivec2 center = ivec2(gl_FragCoord.xy);
+
+    vec2 cell = read_cell(u_texture, v_uv);
+    float a = cell.x;
+    float b = cell.y;
+
+    // same order as Kernel::taps
+    vec2 nabla_squared = vec2(0.0);
+    for (int y = 2; y >= -2; y--) {
+        for (int x = -2; x <= 2; x++) {
+            float weight = u_kernel[(2 - y) * 5 + x + 2];
+            if (abs(x) > u_kernel_radius || abs(y) > u_kernel_radius || weight == 0.0) {
+                continue;
+            }
+            nabla_squared += read_neighbor(center + ivec2(x, y), cell) * weight;
+        }
+    }
+
+    float f = F;
+    float k = K;
+    if (u_parameter_mode == 1) {
+        f = mix(u_feed_range.x, u_feed_range.y, v_uv.x);
+        k = mix(u_kill_range.x, u_kill_range.y, v_uv.y);
+    } else if (u_parameter_mode == 2) {
+        // the image is uploaded top row first
+        vec2 parameters = texture(u_parameter_texture, vec2(v_uv.x, 1.0 - v_uv.y)).rg;
+        f = mix(u_feed_range.x, u_feed_range.y, parameters.r);
+        k = mix(u_kill_range.x, u_kill_range.y, parameters.g);
+    }
+
+    // math from https://karlsims.com/rd.html
+    vec2 rate = vec2(
+        u_diffusion_a * nabla_squared.x - a * b * b + f * (1.0 - a),
+        u_diffusion_b * nabla_squared.y + a * b * b - (k + f) * b
+    );
+    if (is_wall(center)) {
+        rate = vec2(0.0);
+    }
+
+    vec2 accumulator = read_cell(u_accumulator_texture, v_uv);
+    outColor = to_cell(accumulator + u_accumulator_weight * u_delta_t * rate);
+
+    vec2 start = read_cell(u_start_texture, v_uv);
+    outStage = to_cell(start + u_next_stage_offset * u_delta_t * rate);
 }
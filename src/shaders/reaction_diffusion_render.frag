@@ -1,51 +1,54 @@
 #version 300 es
 precision highp float;
 
+// must match MAX_COLOR_STOPS in simulation/color_map.rs
+const int MAX_COLOR_STOPS = 8;
+
 uniform highp usampler2D u_texture;
 
+// rgb and the value where the color is reached, sorted by value
+uniform vec4 u_color_stops[MAX_COLOR_STOPS];
+uniform int u_color_stop_count;
+
+// walls from simulation/mask.rs are drawn flat over the field
+uniform sampler2D u_mask_texture;
+const vec3 WALL_COLOR = vec3(0.35);
+
 in vec2 v_uv;
 
 out vec4 outColor;
 
-// from https://github.com/pmneila/jsexp/blob/master/grayscott/index.html
 void main() {
     highp uvec2 utexel = texture(u_texture, v_uv).rg;
     float value = float(utexel.g) / 65535.0;
 
-    vec4 color1 = vec4(0.1, 0.1, 0.1, 0);
-    vec4 color2 = vec4(0.5, 0.5, 0.5, 0.2);
-    vec4 color3 = vec4(0.5, 0.5, 0.75, 0.21);
-    vec4 color4 = vec4(0, 0, 1, 0.4);
-    vec4 color5 = vec4(1, 1, 1, 0.6);
+    vec4 first = u_color_stops[0];
+    vec4 last = u_color_stops[u_color_stop_count - 1];
 
     float a;
     vec3 col;
 
-    if (value <= color1.a) {
-        col = color1.rgb;
+    if (value <= first.a) {
+        col = first.rgb;
     }
-    if (value > color1.a && value <= color2.a)
-    {
-        a = (value - color1.a) / (color2.a - color1.a);
-        col = mix(color1.rgb, color2.rgb, a);
+    for (int i = 1; i < MAX_COLOR_STOPS; i++) {
+        if (i >= u_color_stop_count) {
+            break;
+        }
+        vec4 from = u_color_stops[i - 1];
+        vec4 to = u_color_stops[i];
+        if (value > from.a && value <= to.a)
+        {
+            a = (value - from.a) / (to.a - from.a);
+            col = mix(from.rgb, to.rgb, a);
+        }
     }
-    if (value > color2.a && value <= color3.a)
-    {
-        a = (value - color2.a) / (color3.a - color2.a);
-        col = mix(color2.rgb, color3.rgb, a);
+    if (value > last.a) {
+        col = last.rgb;
     }
-    if (value > color3.a && value <= color4.a)
-    {
-        a = (value - color3.a) / (color4.a - color3.a);
-        col = mix(color3.rgb, color4.rgb, a);
-    }
-    if (value > color4.a && value <= color5.a)
-    {
-        a = (value - color4.a) / (color5.a - color4.a);
-        col = mix(color4.rgb, color5.rgb, a);
-    }
-    if (value > color5.a) {
-        col = color5.rgb;
+
+    if (texture(u_mask_texture, v_uv).r > 0.5) {
+        col = WALL_COLOR;
     }
 
     outColor = vec4(col, 1.0);
@@ -0,0 +1,230 @@
+use crate::engine::app::time::FrameTimes;
+
+pub const DEFAULT_TARGET_FPS: f32 = 60.0;
+
+// how long to measure after a change before deciding on the next one
+const SETTLE_TIME: f32 = 0.5;
+// below target * LOWER_THRESHOLD the budget goes down, above target * RAISE_THRESHOLD it goes up.
+// the gap between them keeps it from flipping back and forth every measurement
+const LOWER_THRESHOLD: f32 = 0.9;
+const RAISE_THRESHOLD: f32 = 0.97;
+const SCALE_STEP: f32 = 0.25;
+// how much faster than the speed slider it can run when there's time to spare
+pub const MAX_SPEED: f32 = 4.0;
+
+// holds a target frame rate by changing a speed multiplier on the iterations per second, the most iterations a frame
+// can run, and optionally the simulation scale.
+// when it's too slow the extra speed goes first, then the iterations, and the scale only gets coarser once iterations
+// are at the minimum. when there's time to spare it goes back the other way: the scale back to min_scale, the
+// iterations up while they're what's holding the simulation back, then the speed up to MAX_SPEED.
+// the iterations never go far past what was actually run, so there's no slack to use up before lowering them helps
+pub struct AdaptiveBudget {
+    frames: FrameTimes,
+    target_fps: f32,
+    iterations: u32,
+    min_iterations: u32,
+    max_iterations: u32,
+    /* the most iterations run in a frame since the last change */
+    most_run: u32,
+    speed: f32,
+    adapt_scale: bool,
+    scale: f32,
+    min_scale: f32,
+    max_scale: f32,
+}
+
+impl AdaptiveBudget {
+    pub fn new(iterations: u32, scale: f32) -> Self {
+        return Self {
+            frames: FrameTimes::new(SETTLE_TIME * 2.0),
+            target_fps: DEFAULT_TARGET_FPS,
+            iterations,
+            min_iterations: 1,
+            max_iterations: 250,
+            most_run: 0,
+            speed: 1.0,
+            adapt_scale: false,
+            scale,
+            min_scale: scale,
+            max_scale: 4.0,
+        };
+    }
+}
+
+impl AdaptiveBudget {
+    pub fn target_fps(&self) -> f32 { self.target_fps }
+    pub fn set_target_fps(&mut self, target_fps: f32) {
+        self.target_fps = target_fps;
+        self.frames.clear();
+        self.most_run = 0;
+    }
+
+    // the most iterations a frame can run
+    pub fn iterations(&self) -> u32 { self.iterations }
+
+    // multiplies the iterations per second, at least 1
+    pub fn speed(&self) -> f32 { self.speed }
+
+    pub fn adapt_scale(&self) -> bool { self.adapt_scale }
+    pub fn set_adapt_scale(&mut self, adapt_scale: bool) { self.adapt_scale = adapt_scale; }
+
+    pub fn scale(&self) -> f32 { self.scale }
+
+    // the finest scale it goes back up to, which is where it starts again from
+    pub fn set_min_scale(&mut self, min_scale: f32) {
+        self.min_scale = min_scale;
+        self.max_scale = self.max_scale.max(min_scale);
+        self.scale = min_scale;
+        self.frames.clear();
+        self.most_run = 0;
+    }
+
+    // the average frame rate over the last measurement
+    pub fn fps(&self) -> f32 { self.frames.fps() }
+
+    // call once a frame while the simulation is running, with how many iterations the frame ran
+    pub fn update(&mut self, unscaled_time: f32, delta_time: f32, iterations_run: u32) {
+        self.frames.push(unscaled_time, delta_time);
+        self.most_run = self.most_run.max(iterations_run);
+        if self.frames.duration() < SETTLE_TIME {
+            return;
+        }
+
+        let fps = self.frames.fps();
+        // anything above what was run didn't cost anything, so lowering starts from there
+        let iterations = self.iterations.min(self.most_run).max(self.min_iterations);
+        if fps < self.target_fps * LOWER_THRESHOLD {
+            if self.speed > 1.0 {
+                self.speed = (self.speed / 1.25).max(1.0);
+            } else if iterations > self.min_iterations {
+                self.iterations = ((iterations as f32 * 0.8) as u32).max(self.min_iterations);
+            } else if self.adapt_scale && self.scale < self.max_scale {
+                self.scale = (self.scale + SCALE_STEP).min(self.max_scale);
+            } else {
+                return;
+            }
+        } else if fps > self.target_fps * RAISE_THRESHOLD {
+            if self.adapt_scale && self.scale > self.min_scale {
+                self.scale = (self.scale - SCALE_STEP).max(self.min_scale);
+            } else if self.most_run >= self.iterations && self.iterations < self.max_iterations {
+                // the limit is what's holding the simulation back
+                self.iterations = ((self.iterations as f32 * 1.1).ceil() as u32).min(self.max_iterations);
+            } else if self.speed < MAX_SPEED {
+                self.speed = (self.speed * 1.1).min(MAX_SPEED);
+                self.iterations = iterations;
+            } else {
+                return;
+            }
+        } else {
+            return;
+        }
+
+        // measure again from scratch with the new settings
+        self.frames.clear();
+        self.most_run = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::adaptive_budget::*;
+
+    // frames at fps that each ran iterations_run, or as many as the limit let them, until the budget changes
+    // something or a measurement's worth of frames went by without a change
+    fn measure(budget: &mut AdaptiveBudget, time: &mut f32, fps: f32, iterations_run: u32) {
+        let settings = |budget: &AdaptiveBudget| (budget.iterations(), budget.speed(), budget.scale());
+        let before = settings(budget);
+        for _ in 0..((SETTLE_TIME * fps) as u32 + 2) {
+            *time += 1.0 / fps;
+            budget.update(*time, 1.0 / fps, iterations_run.min(budget.iterations()));
+            if settings(budget) != before {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn holds_still_near_the_target() {
+        let mut budget = AdaptiveBudget::new(15, 1.5);
+        let mut time = 0.0;
+        for _ in 0..10 {
+            measure(&mut budget, &mut time, 57.0, 15);
+        }
+        assert_eq!((budget.iterations(), budget.speed(), budget.scale()), (15, 1.0, 1.5));
+    }
+
+    #[test]
+    fn only_raises_iterations_while_they_hold_the_simulation_back() {
+        let mut budget = AdaptiveBudget::new(15, 1.5);
+        let mut time = 0.0;
+        // 15 is all the fixed timestep asks for, so past that only the speed goes up
+        for _ in 0..40 {
+            measure(&mut budget, &mut time, 120.0, 15);
+            assert!(budget.iterations() <= 17, "{}", budget.iterations());
+        }
+        assert_eq!(budget.speed(), MAX_SPEED);
+
+        // the fixed timestep asks for more than the limit, so the limit goes up
+        for _ in 0..10 {
+            measure(&mut budget, &mut time, 120.0, 1000);
+        }
+        assert!(budget.iterations() > 30, "{}", budget.iterations());
+        assert_eq!(budget.scale(), 1.5);
+    }
+
+    #[test]
+    fn lowers_the_speed_then_the_iterations_then_the_scale() {
+        let mut budget = AdaptiveBudget::new(15, 1.5);
+        budget.set_adapt_scale(true);
+        let mut time = 0.0;
+        for _ in 0..10 {
+            measure(&mut budget, &mut time, 120.0, 15);
+        }
+        assert!(budget.speed() > 1.0);
+
+        let mut last_speed = budget.speed();
+        while budget.speed() > 1.0 {
+            measure(&mut budget, &mut time, 30.0, 15);
+            assert!(budget.speed() < last_speed);
+            assert_eq!((budget.iterations(), budget.scale()), (15, 1.5));
+            last_speed = budget.speed();
+        }
+
+        while budget.iterations() > 1 {
+            measure(&mut budget, &mut time, 30.0, 15);
+            assert_eq!(budget.scale(), 1.5);
+        }
+        measure(&mut budget, &mut time, 30.0, 15);
+        assert_eq!((budget.iterations(), budget.scale()), (1, 1.75));
+        for _ in 0..20 {
+            measure(&mut budget, &mut time, 30.0, 15);
+        }
+        assert_eq!(budget.scale(), 4.0);
+    }
+
+    #[test]
+    fn brings_the_scale_back_before_raising_iterations() {
+        let mut budget = AdaptiveBudget::new(1, 1.5);
+        budget.set_adapt_scale(true);
+        let mut time = 0.0;
+        for _ in 0..4 {
+            measure(&mut budget, &mut time, 30.0, 15);
+        }
+        assert_eq!((budget.iterations(), budget.scale()), (1, 2.5));
+
+        for scale in [2.25, 2.0, 1.75, 1.5] {
+            measure(&mut budget, &mut time, 120.0, 15);
+            assert_eq!((budget.iterations(), budget.scale()), (1, scale));
+        }
+        measure(&mut budget, &mut time, 120.0, 15);
+        assert_eq!(budget.iterations(), 2);
+    }
+
+    #[test]
+    fn lowering_starts_from_what_was_run() {
+        let mut budget = AdaptiveBudget::new(100, 1.5);
+        let mut time = 0.0;
+        measure(&mut budget, &mut time, 30.0, 10);
+        assert_eq!(budget.iterations(), 8);
+    }
+}
@@ -0,0 +1,207 @@
+// what the laplacian sees past the edge of the grid, picked separately for each axis
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Boundary {
+    // wraps around to the other side. both axes periodic is a torus, what the simulation has always been
+    Periodic,
+    // zero flux. the edge mirrors the cells next to it so nothing flows through
+    Neumann,
+    // cells past the edge hold Boundaries::edge_value
+    Dirichlet,
+    // cells past the edge are empty so both chemicals drain out through it
+    Absorbing,
+}
+
+impl Boundary {
+    pub fn all() -> [Boundary; 4] {
+        return [Boundary::Periodic, Boundary::Neumann, Boundary::Dirichlet, Boundary::Absorbing];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Boundary::Periodic => "periodic",
+            Boundary::Neumann => "neumann",
+            Boundary::Dirichlet => "dirichlet",
+            Boundary::Absorbing => "absorbing",
+        };
+    }
+
+    pub fn by_name(name: &str) -> Option<Boundary> {
+        return Boundary::all().into_iter().find(|boundary| boundary.name().eq_ignore_ascii_case(name));
+    }
+
+    // the number stored in state files and given to u_boundary in shaders/reaction_diffusion.frag
+    pub fn id(&self) -> u8 {
+        return match self {
+            Boundary::Periodic => 0,
+            Boundary::Neumann => 1,
+            Boundary::Dirichlet => 2,
+            Boundary::Absorbing => 3,
+        };
+    }
+
+    pub fn from_id(id: u8) -> Option<Boundary> {
+        return Boundary::all().into_iter().find(|boundary| boundary.id() == id);
+    }
+
+    // the cell along this axis that i reads from, or None when it's past an edge that holds a fixed value
+    fn resolve(&self, i: i32, size: i32) -> Option<i32> {
+        if i >= 0 && i < size {
+            return Some(i);
+        }
+        return match self {
+            Boundary::Periodic => Some(i.rem_euclid(size)),
+            Boundary::Neumann => {
+                // mirrored about the edge between cells, so -1 reads 0 and -2 reads 1
+                let period = size * 2;
+                let i = i.rem_euclid(period);
+                Some(if i < size { i } else { period - 1 - i })
+            }
+            Boundary::Dirichlet | Boundary::Absorbing => None,
+        };
+    }
+}
+
+pub enum Neighbor {
+    Cell(i32, i32),
+    Fixed(f32, f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Boundaries {
+    pub x: Boundary,
+    pub y: Boundary,
+    // a, b past a dirichlet edge
+    pub edge_value: (f32, f32),
+}
+
+impl Boundaries {
+    pub fn new(x: Boundary, y: Boundary) -> Self {
+        return Self {
+            x,
+            y,
+            edge_value: (1.0, 0.0),
+        };
+    }
+
+    pub fn periodic() -> Self {
+        return Boundaries::new(Boundary::Periodic, Boundary::Periodic);
+    }
+
+    // where the kernel reads cell x, y from. the x axis wins in the corners when both axes hold fixed values
+    pub fn neighbor(&self, x: i32, y: i32, width: i32, height: i32) -> Neighbor {
+        let resolved_x = self.x.resolve(x, width);
+        let resolved_y = self.y.resolve(y, height);
+        return match (resolved_x, resolved_y) {
+            (Some(x), Some(y)) => Neighbor::Cell(x, y),
+            (None, _) => self.fixed_value(self.x),
+            (_, None) => self.fixed_value(self.y),
+        };
+    }
+
+    fn fixed_value(&self, boundary: Boundary) -> Neighbor {
+        return match boundary {
+            Boundary::Absorbing => Neighbor::Fixed(0.0, 0.0),
+            _ => Neighbor::Fixed(self.edge_value.0, self.edge_value.1),
+        };
+    }
+}
+
+impl Default for Boundaries {
+    fn default() -> Self {
+        return Boundaries::periodic();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::boundary::*;
+
+    fn cell(neighbor: Neighbor) -> (i32, i32) {
+        return match neighbor {
+            Neighbor::Cell(x, y) => (x, y),
+            Neighbor::Fixed(a, b) => panic!("expected a cell, got the fixed value {}, {}", a, b),
+        };
+    }
+
+    fn fixed(neighbor: Neighbor) -> (f32, f32) {
+        return match neighbor {
+            Neighbor::Cell(x, y) => panic!("expected a fixed value, got the cell {}, {}", x, y),
+            Neighbor::Fixed(a, b) => (a, b),
+        };
+    }
+
+    #[test]
+    fn ids_and_names_round_trip() {
+        for boundary in Boundary::all() {
+            assert_eq!(Boundary::from_id(boundary.id()), Some(boundary));
+            assert_eq!(Boundary::by_name(&boundary.name().to_uppercase()), Some(boundary));
+        }
+        assert_eq!(Boundary::from_id(4), None);
+        assert_eq!(Boundary::by_name("reflective"), None);
+    }
+
+    #[test]
+    fn inside_the_grid_is_always_the_cell() {
+        for boundary in Boundary::all() {
+            for i in 0..4 {
+                assert_eq!(boundary.resolve(i, 4), Some(i), "{}", boundary.name());
+            }
+        }
+    }
+
+    #[test]
+    fn periodic_wraps_at_both_edges() {
+        assert_eq!(Boundary::Periodic.resolve(-1, 4), Some(3));
+        assert_eq!(Boundary::Periodic.resolve(-2, 4), Some(2));
+        assert_eq!(Boundary::Periodic.resolve(4, 4), Some(0));
+        assert_eq!(Boundary::Periodic.resolve(5, 4), Some(1));
+    }
+
+    #[test]
+    fn neumann_mirrors_at_both_edges() {
+        assert_eq!(Boundary::Neumann.resolve(-1, 4), Some(0));
+        assert_eq!(Boundary::Neumann.resolve(-2, 4), Some(1));
+        assert_eq!(Boundary::Neumann.resolve(4, 4), Some(3));
+        assert_eq!(Boundary::Neumann.resolve(5, 4), Some(2));
+        // a 5x5 kernel on a grid a cell wide still lands on it
+        assert_eq!(Boundary::Neumann.resolve(-2, 1), Some(0));
+        assert_eq!(Boundary::Neumann.resolve(2, 1), Some(0));
+    }
+
+    #[test]
+    fn dirichlet_and_absorbing_are_fixed_at_both_edges() {
+        let mut boundaries = Boundaries::new(Boundary::Dirichlet, Boundary::Absorbing);
+        boundaries.edge_value = (0.25, 0.75);
+        for x in [-2, -1, 4, 5] {
+            assert_eq!(fixed(boundaries.neighbor(x, 1, 4, 4)), (0.25, 0.75), "x {}", x);
+        }
+        for y in [-2, -1, 4, 5] {
+            assert_eq!(fixed(boundaries.neighbor(1, y, 4, 4)), (0.0, 0.0), "y {}", y);
+        }
+    }
+
+    #[test]
+    fn each_axis_uses_its_own_boundary() {
+        let boundaries = Boundaries::new(Boundary::Periodic, Boundary::Neumann);
+        assert_eq!(cell(boundaries.neighbor(-1, -1, 4, 4)), (3, 0));
+        assert_eq!(cell(boundaries.neighbor(4, 4, 4, 4)), (0, 3));
+
+        let boundaries = Boundaries::new(Boundary::Neumann, Boundary::Periodic);
+        assert_eq!(cell(boundaries.neighbor(-1, -1, 4, 4)), (0, 3));
+    }
+
+    #[test]
+    fn the_x_axis_wins_in_the_corners() {
+        let mut boundaries = Boundaries::new(Boundary::Absorbing, Boundary::Dirichlet);
+        boundaries.edge_value = (0.25, 0.75);
+        assert_eq!(fixed(boundaries.neighbor(-1, -1, 4, 4)), (0.0, 0.0));
+        assert_eq!(fixed(boundaries.neighbor(4, 4, 4, 4)), (0.0, 0.0));
+
+        let boundaries = Boundaries::new(Boundary::Dirichlet, Boundary::Absorbing);
+        assert_eq!(fixed(boundaries.neighbor(4, -1, 4, 4)), (1.0, 0.0));
+
+        // a fixed y edge still applies past a corner where x wraps or mirrors
+        let boundaries = Boundaries::new(Boundary::Neumann, Boundary::Absorbing);
+        assert_eq!(fixed(boundaries.neighbor(-1, 4, 4, 4)), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::simulation::gray_scott::{float_to_u16float, u16float_to_float};
+
+// how far past the center the gaussian brush has faded, exp(-4) at the radius
+const GAUSSIAN_FALLOFF: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrushShape {
+    Circle,
+    Square,
+    // soft edges that fade out towards the radius
+    Gaussian,
+}
+
+impl BrushShape {
+    pub fn all() -> [BrushShape; 3] {
+        return [BrushShape::Circle, BrushShape::Square, BrushShape::Gaussian];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            BrushShape::Circle => "circle",
+            BrushShape::Square => "square",
+            BrushShape::Gaussian => "soft",
+        };
+    }
+
+    // the number given to u_shape in shaders/brush_stamp.frag
+    pub fn id(&self) -> i32 {
+        return match self {
+            BrushShape::Circle => 0,
+            BrushShape::Square => 1,
+            BrushShape::Gaussian => 2,
+        };
+    }
+
+    // how much of the brush covers a point, with the offset from the center in radii
+    pub fn coverage(&self, offset: (f32, f32)) -> f32 {
+        let distance_squared = offset.0 * offset.0 + offset.1 * offset.1;
+        return match self {
+            BrushShape::Circle => if distance_squared < 1.0 { 1.0 } else { 0.0 },
+            BrushShape::Square => if offset.0.abs() < 1.0 && offset.1.abs() < 1.0 { 1.0 } else { 0.0 },
+            BrushShape::Gaussian => if distance_squared < 1.0 { (-GAUSSIAN_FALLOFF * distance_squared).exp() } else { 0.0 },
+        };
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrushChemical {
+    A,
+    B,
+    // back towards the rest state, a = 1 and b = 0
+    Erase,
+}
+
+impl BrushChemical {
+    pub fn all() -> [BrushChemical; 3] {
+        return [BrushChemical::A, BrushChemical::B, BrushChemical::Erase];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            BrushChemical::A => "a",
+            BrushChemical::B => "b",
+            BrushChemical::Erase => "erase",
+        };
+    }
+
+    // the number given to u_chemical in shaders/brush_apply.frag
+    pub fn id(&self) -> i32 {
+        return match self {
+            BrushChemical::A => 0,
+            BrushChemical::B => 1,
+            BrushChemical::Erase => 2,
+        };
+    }
+
+    // what a and b are pulled towards, None for a chemical the brush leaves alone
+    fn targets(&self) -> (Option<f32>, Option<f32>) {
+        return match self {
+            BrushChemical::A => (Some(1.0), None),
+            BrushChemical::B => (None, Some(1.0)),
+            BrushChemical::Erase => (Some(1.0), Some(0.0)),
+        };
+    }
+}
+
+// how the brush amount, its coverage times strength, mixes with what is already there
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlendMode {
+    // moves the amount of the way to the target, so a full strength brush sets it
+    Mix,
+    // adds the amount towards the target every frame the brush is held
+    Add,
+    // only ever brings it up to the amount, so painting over a cell again does nothing
+    Max,
+}
+
+impl BlendMode {
+    pub fn all() -> [BlendMode; 3] {
+        return [BlendMode::Mix, BlendMode::Add, BlendMode::Max];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            BlendMode::Mix => "mix",
+            BlendMode::Add => "add",
+            BlendMode::Max => "max",
+        };
+    }
+
+    // the number given to u_blend_mode in shaders/brush_apply.frag
+    pub fn id(&self) -> i32 {
+        return match self {
+            BlendMode::Mix => 0,
+            BlendMode::Add => 1,
+            BlendMode::Max => 2,
+        };
+    }
+
+    fn blend(&self, value: f32, target: f32, amount: f32) -> f32 {
+        return match self {
+            BlendMode::Mix => value + (target - value) * amount,
+            BlendMode::Add => if target > value { (value + amount).min(target) } else { (value - amount).max(target) },
+            BlendMode::Max => if target > 0.5 { value.max(amount) } else { value.min(1.0 - amount) },
+        };
+    }
+}
+
+// one place the brush touched this frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stamp {
+    // in cells
+    pub center: (f32, f32),
+    // 0 to 1, 1 for devices that don't report it
+    pub pressure: f32,
+}
+
+/*
+    What dragging over the field paints. A frame's stamps are gathered into one coverage value per cell first,
+    the most any stamp covers it, and that is blended in once. Overlapping stamps don't build up, so a stroke
+    looks the same however fast it's drawn. The gpu does the same thing with shaders/brush_stamp.frag into an R8
+    texture with MAX blending and shaders/brush_apply.frag
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Brush {
+    pub shape: BrushShape,
+    // in cells
+    pub radius: f32,
+    pub chemical: BrushChemical,
+    pub strength: f32,
+    pub blend_mode: BlendMode,
+}
+
+impl Brush {
+    // the coverage of every cell for these stamps, rounded to bytes like the R8 texture the gpu gathers them in
+    pub fn coverage(&self, stamps: &[Stamp], width: i32, height: i32) -> Vec<u8> {
+        let mut coverage = vec![0u8; (width * height) as usize];
+        for stamp in stamps {
+            let min_x = ((stamp.center.0 - self.radius).floor() as i32).max(0);
+            let max_x = ((stamp.center.0 + self.radius).ceil() as i32).min(width - 1);
+            let min_y = ((stamp.center.1 - self.radius).floor() as i32).max(0);
+            let max_y = ((stamp.center.1 + self.radius).ceil() as i32).min(height - 1);
+
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let offset = ((x as f32 + 0.5 - stamp.center.0) / self.radius, (y as f32 + 0.5 - stamp.center.1) / self.radius);
+                    let value = (self.shape.coverage(offset) * stamp.pressure.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    let i = (x + y * width) as usize;
+                    coverage[i] = coverage[i].max(value);
+                }
+            }
+        }
+        return coverage;
+    }
+
+    // blends the brush into one cell's a, b
+    pub fn apply(&self, a: f32, b: f32, coverage: f32) -> (f32, f32) {
+        let amount = (coverage * self.strength).clamp(0.0, 1.0);
+        let (target_a, target_b) = self.chemical.targets();
+        let a = target_a.map_or(a, |target| self.blend_mode.blend(a, target, amount));
+        let b = target_b.map_or(b, |target| self.blend_mode.blend(b, target, amount));
+        return (a.clamp(0.0, 1.0), b.clamp(0.0, 1.0));
+    }
+
+    // paints the stamps into interleaved a, b cells. stamps are cut off at the edges, the same as on the gpu
+    pub fn paint(&self, cells: &mut [u16], width: i32, height: i32, stamps: &[Stamp]) {
+        let coverage = self.coverage(stamps, width, height);
+        for (i, coverage) in coverage.iter().enumerate() {
+            if *coverage == 0 {
+                continue;
+            }
+            let (a, b) = self.apply(u16float_to_float(cells[i * 2]), u16float_to_float(cells[i * 2 + 1]), *coverage as f32 / 255.0);
+            cells[i * 2] = float_to_u16float(a);
+            cells[i * 2 + 1] = float_to_u16float(b);
+        }
+    }
+}
+
+impl Default for Brush {
+    // what the mouse has always painted, solid b in a small circle
+    fn default() -> Self {
+        return Self {
+            shape: BrushShape::Circle,
+            radius: 5.0,
+            chemical: BrushChemical::B,
+            strength: 1.0,
+            blend_mode: BlendMode::Max,
+        };
+    }
+}
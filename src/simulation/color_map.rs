@@ -0,0 +1,144 @@
+// gradients for coloring the b channel. the render shader gets the stops as uniforms and colors the same way
+// color() does, so images saved on the cpu have the same colors as what is on screen
+
+// must match MAX_COLOR_STOPS in shaders/reaction_diffusion_render.frag
+pub const MAX_COLOR_STOPS: usize = 8;
+
+#[derive(Clone)]
+pub struct ColorStop {
+    // the b value where color is reached
+    pub position: f32,
+    pub color: [f32; 3],
+}
+
+#[derive(Clone)]
+pub struct ColorMap {
+    name: String,
+    stops: Vec<ColorStop>,
+}
+
+impl ColorMap {
+    // stops must be sorted by position
+    pub fn new(name: &str, stops: Vec<ColorStop>) -> Result<Self, String> {
+        if stops.is_empty() || stops.len() > MAX_COLOR_STOPS {
+            return Err(format!("a color map needs between 1 and {} stops", MAX_COLOR_STOPS));
+        }
+        if stops.windows(2).any(|pair| pair[0].position >= pair[1].position) {
+            return Err(String::from("color stops must be sorted by position"));
+        }
+
+        return Ok(Self {
+            name: String::from(name),
+            stops,
+        });
+    }
+
+    // from https://github.com/pmneila/jsexp/blob/master/grayscott/index.html
+    pub fn pmneila() -> Self {
+        return Self::from_table("pmneila", &[
+            [0.1, 0.1, 0.1, 0.0],
+            [0.5, 0.5, 0.5, 0.2],
+            [0.5, 0.5, 0.75, 0.21],
+            [0.0, 0.0, 1.0, 0.4],
+            [1.0, 1.0, 1.0, 0.6],
+        ]);
+    }
+
+    // matplotlib's viridis sampled at 8 points, spread over the range b usually stays in
+    pub fn viridis() -> Self {
+        return Self::from_table("viridis", &[
+            [0.267, 0.005, 0.329, 0.0],
+            [0.275, 0.196, 0.494, 0.0714],
+            [0.212, 0.361, 0.553, 0.1429],
+            [0.153, 0.498, 0.557, 0.2143],
+            [0.122, 0.631, 0.529, 0.2857],
+            [0.290, 0.757, 0.427, 0.3571],
+            [0.627, 0.855, 0.224, 0.4286],
+            [0.992, 0.906, 0.145, 0.5],
+        ]);
+    }
+
+    pub fn magma() -> Self {
+        return Self::from_table("magma", &[
+            [0.0, 0.0, 0.016, 0.0],
+            [0.110, 0.063, 0.267, 0.0714],
+            [0.310, 0.071, 0.482, 0.1429],
+            [0.506, 0.145, 0.506, 0.2143],
+            [0.710, 0.212, 0.478, 0.2857],
+            [0.898, 0.349, 0.392, 0.3571],
+            [0.984, 0.529, 0.380, 0.4286],
+            [0.988, 0.992, 0.749, 0.5],
+        ]);
+    }
+
+    pub fn grayscale() -> Self {
+        return Self::from_table("grayscale", &[
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0, 0.5],
+        ]);
+    }
+
+    // in the order they are shown in the ui
+    pub fn named() -> Vec<Self> {
+        return vec![Self::pmneila(), Self::viridis(), Self::magma(), Self::grayscale()];
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        return Self::named().into_iter().find(|color_map| color_map.name == name);
+    }
+
+    // rows of r, g, b, position
+    fn from_table(name: &str, table: &[[f32; 4]]) -> Self {
+        let stops = table.iter().map(|row| ColorStop { position: row[3], color: [row[0], row[1], row[2]] }).collect();
+        return Self::new(name, stops).unwrap();
+    }
+}
+
+impl ColorMap {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn stops(&self) -> &[ColorStop] { &self.stops }
+
+    pub fn color(&self, value: f32) -> [f32; 3] {
+        let first = &self.stops[0];
+        let last = &self.stops[self.stops.len() - 1];
+
+        if value <= first.position {
+            return first.color;
+        }
+        for i in 1..self.stops.len() {
+            let from = &self.stops[i - 1];
+            let to = &self.stops[i];
+            if value > from.position && value <= to.position {
+                let a = (value - from.position) / (to.position - from.position);
+                return [mix(from.color[0], to.color[0], a), mix(from.color[1], to.color[1], a), mix(from.color[2], to.color[2], a)];
+            }
+        }
+        return last.color;
+    }
+
+    // the b channel of a RG16UI texel to an RGBA8 pixel, like rendering into render_texture does
+    pub fn color_u16(&self, b: u16) -> [u8; 4] {
+        let color = self.color(b as f32 / 65535.0);
+        return [unorm_to_u8(color[0]), unorm_to_u8(color[1]), unorm_to_u8(color[2]), 255];
+    }
+
+    // vec4s of r, g, b, position for u_color_stops, padded to MAX_COLOR_STOPS
+    pub fn uniform_data(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(MAX_COLOR_STOPS * 4);
+        for stop in &self.stops {
+            data.extend_from_slice(&[stop.color[0], stop.color[1], stop.color[2], stop.position]);
+        }
+        data.resize(MAX_COLOR_STOPS * 4, 0.0);
+        return data;
+    }
+}
+
+// glsl mix
+fn mix(x: f32, y: f32, a: f32) -> f32 {
+    return x * (1.0 - a) + y * a;
+}
+
+// how gl converts a float written to a normalized 8 bit color attachment
+fn unorm_to_u8(value: f32) -> u8 {
+    return (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+}
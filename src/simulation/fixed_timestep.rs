@@ -0,0 +1,100 @@
+// 15 iterations a frame at 60 fps, which is how fast the simulation always used to run
+pub const DEFAULT_ITERATIONS_PER_SECOND: f32 = 900.0;
+
+// turns frame times into a whole number of iterations so the simulation runs at the same speed at any frame rate.
+// time that doesn't add up to a whole iteration is carried over to the next frame
+pub struct FixedTimestep {
+    iterations_per_second: f32,
+    // after a long frame (like the tab being in the background) the rest is dropped instead of trying to catch up
+    max_iterations_per_frame: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(iterations_per_second: f32, max_iterations_per_frame: u32) -> Self {
+        return Self {
+            iterations_per_second,
+            max_iterations_per_frame,
+            accumulator: 0.0,
+        };
+    }
+}
+
+impl FixedTimestep {
+    pub fn iterations_per_second(&self) -> f32 { self.iterations_per_second }
+    pub fn set_iterations_per_second(&mut self, iterations_per_second: f32) { self.iterations_per_second = iterations_per_second.max(0.0); }
+
+    pub fn max_iterations_per_frame(&self) -> u32 { self.max_iterations_per_frame }
+    pub fn set_max_iterations_per_frame(&mut self, max_iterations_per_frame: u32) { self.max_iterations_per_frame = max_iterations_per_frame; }
+
+    // how many iterations to run for a frame that took delta_time seconds
+    pub fn iterations(&mut self, delta_time: f32) -> u32 {
+        self.accumulator += delta_time.max(0.0) * self.iterations_per_second;
+        let iterations = self.accumulator.floor();
+        self.accumulator -= iterations;
+
+        if iterations > self.max_iterations_per_frame as f32 {
+            return self.max_iterations_per_frame;
+        }
+        return iterations as u32;
+    }
+
+    // forget time that hasn't been turned into iterations yet, for when the simulation is paused
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::fixed_timestep::*;
+
+    // total iterations over seconds of frames at frame_rate
+    fn run(fixed_timestep: &mut FixedTimestep, frame_rate: f32, seconds: f32) -> u32 {
+        let frames = (frame_rate * seconds).round() as u32;
+        return (0..frames).map(|_| fixed_timestep.iterations(1.0 / frame_rate)).sum();
+    }
+
+    #[test]
+    fn same_speed_at_any_frame_rate() {
+        for frame_rate in [30.0, 60.0, 75.0, 144.0, 240.0] {
+            let mut fixed_timestep = FixedTimestep::new(DEFAULT_ITERATIONS_PER_SECOND, 1000);
+            let iterations = run(&mut fixed_timestep, frame_rate, 10.0);
+            // the last fraction of an iteration can still be waiting in the accumulator after rounding
+            assert!((iterations as i32 - 9000).abs() <= 1, "{} iterations at {} fps", iterations, frame_rate);
+        }
+    }
+
+    #[test]
+    fn carries_fractions_over_to_the_next_frame() {
+        let mut fixed_timestep = FixedTimestep::new(4.0, 100);
+        // a quarter of an iteration a frame
+        let iterations: Vec<u32> = (0..8).map(|_| fixed_timestep.iterations(1.0 / 16.0)).collect();
+        assert_eq!(iterations, [0, 0, 0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn long_frames_are_capped_and_the_rest_is_dropped() {
+        let mut fixed_timestep = FixedTimestep::new(900.0, 30);
+        assert_eq!(fixed_timestep.iterations(1.0), 30);
+        // nothing left over to catch up on
+        assert_eq!(fixed_timestep.iterations(1.0 / 60.0), 15);
+
+        fixed_timestep.set_max_iterations_per_frame(10);
+        assert_eq!(fixed_timestep.iterations(1.0 / 60.0), 10);
+    }
+
+    #[test]
+    fn reset_and_negative_time() {
+        let mut fixed_timestep = FixedTimestep::new(10.0, 100);
+        assert_eq!(fixed_timestep.iterations(0.09), 0);
+        fixed_timestep.reset();
+        assert_eq!(fixed_timestep.iterations(0.09), 0);
+        assert_eq!(fixed_timestep.iterations(-1.0), 0);
+        assert_eq!(fixed_timestep.iterations(0.02), 1);
+
+        fixed_timestep.set_iterations_per_second(-5.0);
+        assert_eq!(fixed_timestep.iterations_per_second(), 0.0);
+        assert_eq!(fixed_timestep.iterations(10.0), 0);
+    }
+}
@@ -0,0 +1,453 @@
+use crate::simulation::boundary::{Boundaries, Neighbor};
+use crate::simulation::brush::{Brush, Stamp};
+use crate::simulation::integrator::{Integrator, Stage};
+use crate::simulation::mask::Mask;
+use crate::simulation::parameter_map::ParameterMap;
+use crate::simulation::state_file::SimulationState;
+use crate::simulation::stencil::Kernel;
+
+// pure rust version of shaders/reaction_diffusion.frag so the simulation can be stepped without a gpu.
+// everything here is done the same way as the shader (f32 math, same sample order, same quantization)
+// so that one call to step() gives the same cells as one step of reaction_diffusion passes
+
+pub const DEFAULT_DIFFUSION_A: f32 = 1.0;
+pub const DEFAULT_DIFFUSION_B: f32 = 0.5;
+pub const DEFAULT_DELTA_T: f32 = 1.0;
+
+pub struct GrayScottGrid {
+    width: i32,
+    height: i32,
+    cells: Vec<u16>,
+    next_cells: Vec<u16>,
+    /* only used by integrators with more than one stage */
+    accumulator_cells: Vec<u16>,
+    stage_cells: Vec<u16>,
+    next_stage_cells: Vec<u16>,
+    feed: f32,
+    kill: f32,
+    kernel: Kernel,
+    integrator: Integrator,
+    diffusion_a: f32,
+    diffusion_b: f32,
+    delta_t: f32,
+    boundaries: Boundaries,
+    mask: Mask,
+    parameter_map: ParameterMap,
+}
+
+impl GrayScottGrid {
+    pub fn new(width: i32, height: i32, feed: f32, kill: f32) -> Self {
+        let mut cells = vec![0; (width * height * 2) as usize];
+        init_cells(&mut cells, width, height);
+
+        return Self {
+            width,
+            height,
+            next_cells: vec![0; cells.len()],
+            accumulator_cells: Vec::new(),
+            stage_cells: Vec::new(),
+            next_stage_cells: Vec::new(),
+            cells,
+            feed,
+            kill,
+            kernel: Kernel::default(),
+            integrator: Integrator::Euler,
+            diffusion_a: DEFAULT_DIFFUSION_A,
+            diffusion_b: DEFAULT_DIFFUSION_B,
+            delta_t: DEFAULT_DELTA_T,
+            boundaries: Boundaries::periodic(),
+            mask: Mask::new(width, height),
+            parameter_map: ParameterMap::uniform(),
+        };
+    }
+}
+
+impl GrayScottGrid {
+    pub fn width(&self) -> i32 { self.width }
+    pub fn height(&self) -> i32 { self.height }
+
+    // interleaved a, b pairs laid out the same way as the RG16UI texture
+    pub fn cells(&self) -> &[u16] { &self.cells }
+    pub fn cells_mut(&mut self) -> &mut [u16] { &mut self.cells }
+
+    pub fn feed(&self) -> f32 { self.feed }
+    pub fn set_feed(&mut self, feed: f32) { self.feed = feed; }
+
+    pub fn kill(&self) -> f32 { self.kill }
+    pub fn set_kill(&mut self, kill: f32) { self.kill = kill; }
+
+    pub fn kernel(&self) -> &Kernel { &self.kernel }
+    pub fn set_kernel(&mut self, kernel: Kernel) { self.kernel = kernel; }
+
+    pub fn integrator(&self) -> Integrator { self.integrator }
+    pub fn set_integrator(&mut self, integrator: Integrator) { self.integrator = integrator; }
+
+    pub fn diffusion_a(&self) -> f32 { self.diffusion_a }
+    pub fn set_diffusion_a(&mut self, diffusion_a: f32) { self.diffusion_a = diffusion_a; }
+
+    pub fn diffusion_b(&self) -> f32 { self.diffusion_b }
+    pub fn set_diffusion_b(&mut self, diffusion_b: f32) { self.diffusion_b = diffusion_b; }
+
+    pub fn delta_t(&self) -> f32 { self.delta_t }
+    pub fn set_delta_t(&mut self, delta_t: f32) { self.delta_t = delta_t; }
+
+    pub fn boundaries(&self) -> Boundaries { self.boundaries }
+    pub fn set_boundaries(&mut self, boundaries: Boundaries) { self.boundaries = boundaries; }
+
+    pub fn mask(&self) -> &Mask { &self.mask }
+    pub fn mask_mut(&mut self) -> &mut Mask { &mut self.mask }
+    // resized to the grid if it's a different size
+    pub fn set_mask(&mut self, mask: Mask) {
+        self.mask = if mask.width() == self.width && mask.height() == self.height { mask } else { mask.resized(self.width, self.height) };
+    }
+
+    pub fn parameter_map(&self) -> &ParameterMap { &self.parameter_map }
+    pub fn set_parameter_map(&mut self, parameter_map: ParameterMap) { self.parameter_map = parameter_map; }
+
+    pub fn get(&self, x: i32, y: i32) -> (f32, f32) {
+        return sample(&self.cells, x, y, self.width, self.height);
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, a: f32, b: f32) {
+        let i = cell_xy_to_index(x, y, self.width, self.height);
+        self.cells[i] = float_to_u16float(a.clamp(0.0, 1.0));
+        self.cells[i + 1] = float_to_u16float(b.clamp(0.0, 1.0));
+    }
+
+    pub fn clear(&mut self) {
+        for i in (0..self.cells.len()).step_by(2) {
+            self.cells[i] = u16::MAX;
+            self.cells[i + 1] = 0;
+        }
+    }
+
+    pub fn step(&mut self) {
+        let stages = self.integrator.stages();
+        let len = self.cells.len();
+        let mut accumulator = std::mem::take(&mut self.accumulator_cells);
+        let mut next_accumulator = std::mem::take(&mut self.next_cells);
+        let mut stage_cells = std::mem::take(&mut self.stage_cells);
+        let mut next_stage_cells = std::mem::take(&mut self.next_stage_cells);
+        next_accumulator.resize(len, 0);
+        if stages.len() > 1 {
+            accumulator.resize(len, 0);
+            stage_cells.resize(len, 0);
+            next_stage_cells.resize(len, 0);
+        }
+
+        for (i, stage) in stages.iter().enumerate() {
+            let out_stage = if i == stages.len() - 1 { None } else { Some(next_stage_cells.as_mut_slice()) };
+            if i == 0 {
+                self.step_stage(&self.cells, &self.cells, stage, &mut next_accumulator, out_stage);
+            } else {
+                self.step_stage(&stage_cells, &accumulator, stage, &mut next_accumulator, out_stage);
+            }
+            std::mem::swap(&mut accumulator, &mut next_accumulator);
+            std::mem::swap(&mut stage_cells, &mut next_stage_cells);
+        }
+
+        // accumulator is the new state after the last stage
+        std::mem::swap(&mut self.cells, &mut accumulator);
+        self.next_cells = accumulator;
+        self.accumulator_cells = next_accumulator;
+        self.stage_cells = stage_cells;
+        self.next_stage_cells = next_stage_cells;
+    }
+
+    // one pass of the reaction_diffusion shader. the rate of change is worked out at current, then dt times it scaled by
+    // the stage's accumulator_weight is added to accumulator, and if out_stage is given it gets the start of the step
+    // plus dt times it scaled by next_stage_offset, which is where the next stage works out its rate of change
+    fn step_stage(&self, current: &[u16], accumulator: &[u16], stage: &Stage, out_accumulator: &mut [u16], mut out_stage: Option<&mut [u16]>) {
+        let taps = self.kernel.taps();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (a, b) = sample(current, x, y, self.width, self.height);
+                let (feed, kill) = self.parameter_map.feed_kill(x, y, self.width, self.height, self.feed, self.kill);
+                let wall = self.mask.is_wall(x, y);
+
+                let mut nabla_squared_a = 0.0;
+                let mut nabla_squared_b = 0.0;
+                for (offset_x, offset_y, weight) in &taps {
+                    let (a_n, b_n) = match self.boundaries.neighbor(x + offset_x, y + offset_y, self.width, self.height) {
+                        // nothing flows in from a wall, so it's seen as a copy of this cell
+                        Neighbor::Cell(x, y) if self.mask.is_wall(x, y) => (a, b),
+                        Neighbor::Cell(x, y) => sample(current, x, y, self.width, self.height),
+                        Neighbor::Fixed(a, b) => (a, b),
+                    };
+                    nabla_squared_a += a_n * weight;
+                    nabla_squared_b += b_n * weight;
+                }
+
+                // math from https://karlsims.com/rd.html
+                let mut rate_a = self.diffusion_a * nabla_squared_a - a * b * b + feed * (1.0 - a);
+                let mut rate_b = self.diffusion_b * nabla_squared_b + a * b * b - (kill + feed) * b;
+                if wall {
+                    rate_a = 0.0;
+                    rate_b = 0.0;
+                }
+
+                let i = cell_xy_to_index(x, y, self.width, self.height);
+                let (accumulator_a, accumulator_b) = sample(accumulator, x, y, self.width, self.height);
+                let scale = stage.accumulator_weight * self.delta_t;
+                out_accumulator[i] = float_to_u16float((accumulator_a + scale * rate_a).clamp(0.0, 1.0));
+                out_accumulator[i + 1] = float_to_u16float((accumulator_b + scale * rate_b).clamp(0.0, 1.0));
+
+                if let Some(out_stage) = out_stage.as_mut() {
+                    let (start_a, start_b) = self.get(x, y);
+                    let scale = stage.next_stage_offset * self.delta_t;
+                    out_stage[i] = float_to_u16float((start_a + scale * rate_a).clamp(0.0, 1.0));
+                    out_stage[i + 1] = float_to_u16float((start_b + scale * rate_b).clamp(0.0, 1.0));
+                }
+            }
+        }
+    }
+
+    // nearest neighbour resample, same as drawing the old texture over the new one with basic_RG16UI
+    pub fn resize(&mut self, width: i32, height: i32) {
+        let mut cells = vec![0; (width * height * 2) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let old_x = ((x as f32 + 0.5) / width as f32 * self.width as f32) as i32;
+                let old_y = ((y as f32 + 0.5) / height as f32 * self.height as f32) as i32;
+                let old_i = cell_xy_to_index(old_x, old_y, self.width, self.height);
+                let i = cell_xy_to_index(x, y, width, height);
+                cells[i] = self.cells[old_i];
+                cells[i + 1] = self.cells[old_i + 1];
+            }
+        }
+
+        self.mask = self.mask.resized(width, height);
+        self.width = width;
+        self.height = height;
+        self.next_cells = vec![0; cells.len()];
+        self.cells = cells;
+    }
+
+    pub fn to_state(&self, iterations: u64) -> SimulationState {
+        return SimulationState {
+            width: self.width,
+            height: self.height,
+            feed: self.feed,
+            kill: self.kill,
+            kernel: self.kernel.weights().to_vec(),
+            integrator: self.integrator,
+            diffusion_a: self.diffusion_a,
+            diffusion_b: self.diffusion_b,
+            delta_t: self.delta_t,
+            boundaries: self.boundaries,
+            mask: if self.mask.has_walls() { Some(self.mask.cells().to_vec()) } else { None },
+            iterations,
+            cells: self.cells.clone(),
+        };
+    }
+
+    // takes on the size of the state. resample the state first to keep the current size
+    pub fn load_state(&mut self, state: &SimulationState) -> Result<(), String> {
+        let expected = state.width.max(0) as usize * state.height.max(0) as usize * 2;
+        if state.cells.len() != expected {
+            return Err(format!("a {}x{} state needs {} cell values, got {}", state.width, state.height, expected, state.cells.len()));
+        }
+        self.kernel = Kernel::new(state.kernel.clone())?;
+        self.integrator = state.integrator;
+        self.width = state.width;
+        self.height = state.height;
+        self.feed = state.feed;
+        self.kill = state.kill;
+        self.diffusion_a = state.diffusion_a;
+        self.diffusion_b = state.diffusion_b;
+        self.delta_t = state.delta_t;
+        self.boundaries = state.boundaries;
+        self.mask = match state.mask.as_ref() {
+            Some(cells) => Mask::from_cells(state.width, state.height, cells.clone())?,
+            None => Mask::new(state.width, state.height),
+        };
+        self.cells = state.cells.clone();
+        self.next_cells = vec![0; self.cells.len()];
+        return Ok(());
+    }
+
+    pub fn paint(&mut self, brush: &Brush, stamps: &[Stamp]) {
+        brush.paint(&mut self.cells, self.width, self.height, stamps);
+    }
+}
+
+fn sample(cells: &[u16], x: i32, y: i32, width: i32, height: i32) -> (f32, f32) {
+    let i = cell_xy_to_index(x, y, width, height);
+    return (u16float_to_float(cells[i]), u16float_to_float(cells[i + 1]));
+}
+
+pub fn u16float_to_float(value: u16) -> f32 {
+    return value as f32 / u16::MAX as f32;
+}
+
+pub fn float_to_u16float(value: f32) -> u16 {
+    return (value * u16::MAX as f32).round() as u16;
+}
+
+pub fn cell_xy_to_index(x: i32, y: i32, width: i32, height: i32) -> usize {
+    return (x.rem_euclid(width) + y.rem_euclid(height) * width) as usize * 2;
+}
+
+pub fn init_cells(cells: &mut [u16], width: i32, height: i32) {
+    for i in (0..cells.len()).step_by(2) {
+        cells[i] = float_to_u16float(1.0);
+        cells[i + 1] = float_to_u16float(0.0);
+    }
+
+    for x in (width / 2 - 10)..(width / 2 + 10) {
+        for y in (height / 2 - 10)..(height / 2 + 10) {
+            let i = cell_xy_to_index(x, y, width, height);
+            cells[i + 1] = float_to_u16float(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::gray_scott::*;
+    use crate::simulation::integrator::{Integrator, Stage};
+    use crate::simulation::stencil::Stencil;
+
+    // one step of quantization either way
+    const EPSILON: f32 = 2.0 / u16::MAX as f32;
+
+    fn assert_cell(grid: &GrayScottGrid, x: i32, y: i32, expected_a: f32, expected_b: f32) {
+        let (a, b) = grid.get(x, y);
+        assert!((a - expected_a).abs() <= EPSILON, "a at {}, {} is {}, expected {}", x, y, a, expected_a);
+        assert!((b - expected_b).abs() <= EPSILON, "b at {}, {} is {}, expected {}", x, y, b, expected_b);
+    }
+
+    // every cell set to a, b
+    fn uniform_grid(width: i32, height: i32, a: f32, b: f32, feed: f32, kill: f32) -> GrayScottGrid {
+        let mut grid = GrayScottGrid::new(width, height, feed, kill);
+        for y in 0..height {
+            for x in 0..width {
+                grid.set(x, y, a, b);
+            }
+        }
+        return grid;
+    }
+
+    #[test]
+    fn constants_match_the_shader() {
+        assert_eq!(DEFAULT_DIFFUSION_A, 1.0);
+        assert_eq!(DEFAULT_DIFFUSION_B, 0.5);
+        assert_eq!(DEFAULT_DELTA_T, 1.0);
+
+        let grid = GrayScottGrid::new(4, 4, 0.055, 0.062);
+        assert_eq!(grid.diffusion_a(), DEFAULT_DIFFUSION_A);
+        assert_eq!(grid.diffusion_b(), DEFAULT_DIFFUSION_B);
+        assert_eq!(grid.delta_t(), DEFAULT_DELTA_T);
+    }
+
+    #[test]
+    fn quantization_round_trips() {
+        for value in 0..=u16::MAX {
+            assert_eq!(float_to_u16float(u16float_to_float(value)), value);
+        }
+        assert_eq!(float_to_u16float(0.0), 0);
+        assert_eq!(float_to_u16float(1.0), u16::MAX);
+        // 0.5 * 65535 = 32767.5, rounded up
+        assert_eq!(float_to_u16float(0.5), 32768);
+    }
+
+    #[test]
+    fn set_clamps_to_0_1() {
+        let mut grid = GrayScottGrid::new(4, 4, 0.0, 0.0);
+        grid.set(1, 1, 2.0, -1.0);
+        assert_eq!(grid.get(1, 1), (1.0, 0.0));
+    }
+
+    #[test]
+    fn laplacian_wraps_around_the_edges() {
+        // only b at 0, 0, no reaction, so b only diffuses: rate_b = 0.5 * laplacian
+        let mut grid = uniform_grid(4, 4, 0.0, 0.0, 0.0, 0.0);
+        grid.set_kernel(Stencil::FivePoint.kernel());
+        grid.set(0, 0, 0.0, 1.0);
+        grid.step();
+
+        assert_cell(&grid, 0, 0, 0.0, 1.0 - 0.5 * 0.8);
+        // the neighbours on the other side of the grid
+        assert_cell(&grid, 3, 0, 0.0, 0.5 * 0.2);
+        assert_cell(&grid, 0, 3, 0.0, 0.5 * 0.2);
+        assert_cell(&grid, 1, 0, 0.0, 0.5 * 0.2);
+        assert_cell(&grid, 0, 1, 0.0, 0.5 * 0.2);
+        // the five point kernel doesn't reach the corners
+        assert_cell(&grid, 3, 3, 0.0, 0.0);
+        assert_cell(&grid, 2, 2, 0.0, 0.0);
+    }
+
+    #[test]
+    fn nine_point_laplacian_wraps_diagonally() {
+        let mut grid = uniform_grid(4, 4, 0.0, 0.0, 0.0, 0.0);
+        grid.set(0, 0, 0.0, 1.0);
+        grid.step();
+
+        assert_cell(&grid, 0, 0, 0.0, 1.0 - 0.5 * 1.0);
+        assert_cell(&grid, 3, 0, 0.0, 0.5 * 0.2);
+        assert_cell(&grid, 3, 3, 0.0, 0.5 * 0.05);
+        assert_cell(&grid, 1, 3, 0.0, 0.5 * 0.05);
+        assert_cell(&grid, 2, 2, 0.0, 0.0);
+    }
+
+    #[test]
+    fn reaction_on_a_uniform_grid() {
+        // nothing to diffuse, so only the reaction terms are left
+        let mut grid = uniform_grid(4, 4, 0.5, 0.25, 0.05, 0.06);
+        grid.step();
+
+        // -a * b * b + feed * (1 - a) = -0.03125 + 0.025
+        // a * b * b - (kill + feed) * b = 0.03125 - 0.0275
+        assert_cell(&grid, 2, 1, 0.5 - 0.00625, 0.25 + 0.00375);
+    }
+
+    #[test]
+    fn rk2_uses_the_rate_at_the_midpoint() {
+        // b decays at -kill * b
+        let mut euler = uniform_grid(4, 4, 0.0, 0.5, 0.0, 0.1);
+        euler.step();
+        assert_cell(&euler, 0, 0, 0.0, 0.45);
+
+        let mut rk2 = uniform_grid(4, 4, 0.0, 0.5, 0.0, 0.1);
+        rk2.set_integrator(Integrator::Rk2);
+        rk2.step();
+        // midpoint 0.5 - 0.5 * 0.05 = 0.475, then 0.5 - 0.1 * 0.475
+        assert_cell(&rk2, 0, 0, 0.0, 0.4525);
+    }
+
+    #[test]
+    fn step_stage_writes_the_accumulator_and_the_next_stage() {
+        let grid = uniform_grid(2, 2, 0.0, 0.5, 0.0, 0.1);
+        let cells = grid.cells().to_vec();
+        let mut accumulator = vec![0; cells.len()];
+        for i in (0..accumulator.len()).step_by(2) {
+            accumulator[i + 1] = float_to_u16float(0.25);
+        }
+        let mut out_accumulator = vec![0; cells.len()];
+        let mut out_stage = vec![0; cells.len()];
+        let stage = Stage { next_stage_offset: 0.5, accumulator_weight: 0.5 };
+        grid.step_stage(&cells, &accumulator, &stage, &mut out_accumulator, Some(&mut out_stage));
+
+        // rate_b = -0.1 * 0.5
+        let b = u16float_to_float(out_accumulator[1]);
+        assert!((b - (0.25 - 0.5 * 0.05)).abs() <= EPSILON);
+        let b = u16float_to_float(out_stage[1]);
+        assert!((b - (0.5 - 0.5 * 0.05)).abs() <= EPSILON);
+    }
+
+    #[test]
+    fn load_state_rejects_cells_that_dont_match_the_size() {
+        let mut grid = uniform_grid(2, 2, 1.0, 0.0, 0.055, 0.062);
+        let mut state = uniform_grid(3, 3, 0.5, 0.5, 0.03, 0.06).to_state(0);
+        let last = state.cells.pop().unwrap();
+        assert!(grid.load_state(&state).is_err());
+        // left as it was
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.feed(), 0.055);
+
+        state.cells.push(last);
+        grid.load_state(&state).unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_cell(&grid, 2, 2, 0.5, 0.5);
+    }
+}
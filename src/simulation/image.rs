@@ -0,0 +1,191 @@
+use crate::simulation::color_map::ColorMap;
+use crate::simulation::gray_scott::cell_xy_to_index;
+use crate::simulation::mask::{Mask, WALL_COLOR};
+
+// colors the b channel of interleaved a, b cells, with walls drawn over them. rows are flipped because row 0 of
+// the cells is the bottom of the texture but the top of an image
+pub fn cells_to_rgba(cells: &[u16], width: i32, height: i32, color_map: &ColorMap, mask: Option<&Mask>) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            if mask.map_or(false, |mask| mask.is_wall(x, y)) {
+                rgba.extend_from_slice(&WALL_COLOR);
+                continue;
+            }
+            let i = cell_xy_to_index(x, y, width, height);
+            rgba.extend_from_slice(&color_map.color_u16(cells[i + 1]));
+        }
+    }
+    return rgba;
+}
+
+// for pixels read back from gl, which start with the bottom row
+pub fn flip_rows(rgba: &[u8], width: i32, height: i32) -> Vec<u8> {
+    let row_len = (width * 4) as usize;
+    let mut flipped = Vec::with_capacity(rgba.len());
+    for row in rgba.chunks(row_len).rev().take(height as usize) {
+        flipped.extend_from_slice(row);
+    }
+    return flipped;
+}
+
+// bilinear like the LINEAR filtering render_texture is drawn with. wraps around the edges like the simulation
+pub fn upscale_rgba(rgba: &[u8], width: i32, height: i32, scale: u32) -> Vec<u8> {
+    if scale <= 1 {
+        return rgba.to_vec();
+    }
+
+    let new_width = width * scale as i32;
+    let new_height = height * scale as i32;
+    let pixel = |x: i32, y: i32| ((x.rem_euclid(width) + y.rem_euclid(height) * width) * 4) as usize;
+
+    let mut upscaled = Vec::with_capacity((new_width * new_height * 4) as usize);
+    for y in 0..new_height {
+        for x in 0..new_width {
+            let source_x = (x as f32 + 0.5) / scale as f32 - 0.5;
+            let source_y = (y as f32 + 0.5) / scale as f32 - 0.5;
+            let x0 = source_x.floor() as i32;
+            let y0 = source_y.floor() as i32;
+            let tx = source_x - x0 as f32;
+            let ty = source_y - y0 as f32;
+
+            for channel in 0..4 {
+                let sample = |x: i32, y: i32| rgba[pixel(x, y) + channel] as f32;
+                let top = sample(x0, y0) * (1.0 - tx) + sample(x0 + 1, y0) * tx;
+                let bottom = sample(x0, y0 + 1) * (1.0 - tx) + sample(x0 + 1, y0 + 1) * tx;
+                upscaled.push((top * (1.0 - ty) + bottom * ty).round() as u8);
+            }
+        }
+    }
+    return upscaled;
+}
+
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(rgba).map_err(|e| e.to_string())?;
+    }
+    return Ok(bytes);
+}
+
+// any png as 8 bit rgba, top row first
+pub fn decode_png(bytes: &[u8]) -> Result<(i32, i32, Vec<u8>), String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let pixels = &buffer[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+        png::ColorType::Indexed => return Err(String::from("indexed png was not expanded")),
+    };
+    return Ok((info.width as i32, info.height as i32, rgba));
+}
+
+// colors cells and encodes them as a png, scale times larger than the grid
+pub fn cells_to_png(cells: &[u16], width: i32, height: i32, color_map: &ColorMap, mask: Option<&Mask>, scale: u32) -> Result<Vec<u8>, String> {
+    let scale = scale.max(1);
+    let rgba = upscale_rgba(&cells_to_rgba(cells, width, height, color_map, mask), width, height, scale);
+    return encode_png(width as u32 * scale, height as u32 * scale, &rgba);
+}
+
+// little endian u16 a, b pairs, bottom row first. the same layout as the RG16UI texture
+pub fn cells_to_raw(cells: &[u16]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(cells.len() * 2);
+    for value in cells {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    return bytes;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::color_map::{ColorMap, MAX_COLOR_STOPS};
+    use crate::simulation::gray_scott::float_to_u16float;
+    use crate::simulation::image::*;
+    use crate::simulation::mask::{Mask, WALL_COLOR};
+
+    const RENDER_SHADER: &str = include_str!("../shaders/reaction_diffusion_render.frag");
+
+    // a 1x1 grid colored with pmneila's stops
+    fn pmneila_pixel(a: f32, b: f32) -> Vec<u8> {
+        let cells = [float_to_u16float(a), float_to_u16float(b)];
+        return cells_to_rgba(&cells, 1, 1, &ColorMap::pmneila(), None);
+    }
+
+    #[test]
+    fn shader_constants_match() {
+        assert!(RENDER_SHADER.contains(&format!("const int MAX_COLOR_STOPS = {};", MAX_COLOR_STOPS)));
+        assert!(RENDER_SHADER.contains("const vec3 WALL_COLOR = vec3(0.35);"));
+        // 0.35 * 255 = 89.25
+        assert_eq!(WALL_COLOR, [89, 89, 89, 255]);
+    }
+
+    #[test]
+    fn colors_b_with_the_shader_stops() {
+        // below the first stop
+        assert_eq!(pmneila_pixel(1.0, 0.0), [26, 26, 26, 255]);
+        // 0.300007 is 0.4737 of the way from (0.5, 0.5, 0.75) at 0.21 to (0, 0, 1) at 0.4
+        assert_eq!(pmneila_pixel(0.5, 0.3), [67, 67, 221, 255]);
+        // 0.500008 is halfway from (0, 0, 1) at 0.4 to (1, 1, 1) at 0.6
+        assert_eq!(pmneila_pixel(0.0, 0.5), [128, 128, 255, 255]);
+        // past the last stop
+        assert_eq!(pmneila_pixel(0.0, 1.0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn a_does_not_change_the_color() {
+        assert_eq!(pmneila_pixel(0.0, 0.3), pmneila_pixel(1.0, 0.3));
+    }
+
+    #[test]
+    fn walls_are_drawn_over_the_field() {
+        let cells = [0, u16::MAX, 0, u16::MAX];
+        let mut mask = Mask::new(2, 1);
+        mask.set(1, 0, true);
+        let rgba = cells_to_rgba(&cells, 2, 1, &ColorMap::pmneila(), Some(&mask));
+        assert_eq!(rgba, [255, 255, 255, 255, 89, 89, 89, 255]);
+    }
+
+    #[test]
+    fn top_row_comes_first() {
+        // row 0 is the bottom of the texture
+        let cells = [u16::MAX, 0, 0, u16::MAX];
+        let rgba = cells_to_rgba(&cells, 1, 2, &ColorMap::grayscale(), None);
+        assert_eq!(rgba, [255, 255, 255, 255, 0, 0, 0, 255]);
+        assert_eq!(flip_rows(&rgba, 1, 2), [0, 0, 0, 255, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn png_round_trips() {
+        let rgba: Vec<u8> = (0..(3 * 2 * 4)).map(|i| (i * 10) as u8).collect();
+        let bytes = encode_png(3, 2, &rgba).unwrap();
+        assert_eq!(decode_png(&bytes).unwrap(), (3, 2, rgba));
+    }
+
+    #[test]
+    fn scaled_png_is_larger() {
+        let cells = [0, float_to_u16float(0.5), 0, float_to_u16float(0.5)];
+        let bytes = cells_to_png(&cells, 2, 1, &ColorMap::pmneila(), None, 2).unwrap();
+        let (width, height, rgba) = decode_png(&bytes).unwrap();
+        assert_eq!((width, height), (4, 2));
+        // both cells are the same color so blending between them doesn't change it
+        for pixel in rgba.chunks(4) {
+            assert_eq!(pixel, [128, 128, 255, 255]);
+        }
+    }
+
+    #[test]
+    fn raw_is_little_endian() {
+        assert_eq!(cells_to_raw(&[0x0102, 0xa0b0]), [0x02, 0x01, 0xb0, 0xa0]);
+    }
+}
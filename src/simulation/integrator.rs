@@ -0,0 +1,94 @@
+// explicit runge-kutta methods, done as one pass per stage so the gpu only ever needs RG16UI textures.
+// every stage works out the rate of change at the current stage state and writes two things:
+//     accumulator + accumulator_weight * dt * rate, which ends up as the next state after the last stage
+//     start + next_stage_offset * dt * rate, the state the next stage works out its rate at
+// the first stage starts with the accumulator and the stage state both equal to the state at the start of the step
+
+#[derive(Clone, Copy)]
+pub struct Stage {
+    pub next_stage_offset: f32,
+    pub accumulator_weight: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Integrator {
+    Euler,
+    // midpoint method
+    Rk2,
+    // classic fourth order runge-kutta
+    Rk4,
+}
+
+const EULER_STAGES: [Stage; 1] = [
+    Stage { next_stage_offset: 0.0, accumulator_weight: 1.0 },
+];
+
+const RK2_STAGES: [Stage; 2] = [
+    Stage { next_stage_offset: 0.5, accumulator_weight: 0.0 },
+    Stage { next_stage_offset: 0.0, accumulator_weight: 1.0 },
+];
+
+const RK4_STAGES: [Stage; 4] = [
+    Stage { next_stage_offset: 0.5, accumulator_weight: 1.0 / 6.0 },
+    Stage { next_stage_offset: 0.5, accumulator_weight: 1.0 / 3.0 },
+    Stage { next_stage_offset: 1.0, accumulator_weight: 1.0 / 3.0 },
+    Stage { next_stage_offset: 0.0, accumulator_weight: 1.0 / 6.0 },
+];
+
+impl Integrator {
+    pub fn all() -> [Integrator; 3] {
+        return [Integrator::Euler, Integrator::Rk2, Integrator::Rk4];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Integrator::Euler => "euler",
+            Integrator::Rk2 => "rk2",
+            Integrator::Rk4 => "rk4",
+        };
+    }
+
+    pub fn by_name(name: &str) -> Option<Integrator> {
+        return Integrator::all().into_iter().find(|integrator| integrator.name().eq_ignore_ascii_case(name));
+    }
+
+    // the number stored in state files
+    pub fn id(&self) -> u8 {
+        return match self {
+            Integrator::Euler => 0,
+            Integrator::Rk2 => 1,
+            Integrator::Rk4 => 2,
+        };
+    }
+
+    pub fn from_id(id: u8) -> Option<Integrator> {
+        return Integrator::all().into_iter().find(|integrator| integrator.id() == id);
+    }
+
+    pub fn stages(&self) -> &'static [Stage] {
+        return match self {
+            Integrator::Euler => &EULER_STAGES,
+            Integrator::Rk2 => &RK2_STAGES,
+            Integrator::Rk4 => &RK4_STAGES,
+        };
+    }
+
+    // how much a mode that changes by z per unit of time gets multiplied by each step, as a polynomial in
+    // z = dt * λ. these are the taylor series of e^z up to the order of the method
+    pub fn amplification(&self, z: (f32, f32)) -> f32 {
+        let order = match self {
+            Integrator::Euler => 1,
+            Integrator::Rk2 => 2,
+            Integrator::Rk4 => 4,
+        };
+
+        let mut sum = (1.0, 0.0);
+        let mut term = (1.0, 0.0);
+        for n in 1..=order {
+            // term *= z / n
+            term = ((term.0 * z.0 - term.1 * z.1) / n as f32, (term.0 * z.1 + term.1 * z.0) / n as f32);
+            sum = (sum.0 + term.0, sum.1 + term.1);
+        }
+        return (sum.0 * sum.0 + sum.1 * sum.1).sqrt();
+    }
+}
@@ -0,0 +1,109 @@
+use crate::simulation::image::decode_png;
+
+pub const WALL: u8 = 255;
+pub const OPEN: u8 = 0;
+// same as WALL_COLOR in shaders/reaction_diffusion_render.frag
+pub const WALL_COLOR: [u8; 4] = [89, 89, 89, 255];
+
+// walls the reaction can't happen in. nothing diffuses into or out of them, the cells next to a wall see it as
+// a zero flux edge. one byte per cell laid out like the grid, bottom row first, so it uploads straight to an R8 texture
+#[derive(Clone)]
+pub struct Mask {
+    width: i32,
+    height: i32,
+    cells: Vec<u8>,
+}
+
+impl Mask {
+    pub fn new(width: i32, height: i32) -> Self {
+        return Self {
+            width,
+            height,
+            cells: vec![OPEN; (width * height) as usize],
+        };
+    }
+
+    pub fn from_cells(width: i32, height: i32, cells: Vec<u8>) -> Result<Self, String> {
+        if cells.len() != (width * height) as usize {
+            return Err(format!("expected {} mask cells for a {}x{} grid, got {}", width * height, width, height, cells.len()));
+        }
+        return Ok(Self { width, height, cells });
+    }
+
+    // dark, opaque pixels are walls. the image is stretched over the grid
+    pub fn from_png(bytes: &[u8], width: i32, height: i32) -> Result<Self, String> {
+        let (image_width, image_height, rgba) = decode_png(bytes)?;
+        let mut mask = Mask::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let image_x = ((x as f32 + 0.5) / width as f32 * image_width as f32) as i32;
+                // the image is top row first
+                let image_y = image_height - 1 - ((y as f32 + 0.5) / height as f32 * image_height as f32) as i32;
+                let i = ((image_x + image_y * image_width) * 4) as usize;
+                let luminance = 0.299 * rgba[i] as f32 + 0.587 * rgba[i + 1] as f32 + 0.114 * rgba[i + 2] as f32;
+                if luminance < 128.0 && rgba[i + 3] >= 128 {
+                    mask.set(x, y, true);
+                }
+            }
+        }
+        return Ok(mask);
+    }
+
+    pub fn width(&self) -> i32 { self.width }
+    pub fn height(&self) -> i32 { self.height }
+    pub fn cells(&self) -> &[u8] { &self.cells }
+
+    // cells outside the grid are never walls, the boundaries decide what happens there
+    pub fn is_wall(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return false;
+        }
+        return self.cells[(x + y * self.width) as usize] == WALL;
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, wall: bool) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+        self.cells[(x + y * self.width) as usize] = if wall { WALL } else { OPEN };
+    }
+
+    pub fn has_walls(&self) -> bool {
+        return self.cells.iter().any(|cell| *cell == WALL);
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(OPEN);
+    }
+
+    // the same cells as a circle Brush covers
+    pub fn paint_circle(&mut self, center: (f32, f32), radius: f32, wall: bool) {
+        let min_x = (center.0 - radius).floor() as i32;
+        let max_x = (center.0 + radius).ceil() as i32;
+        let min_y = (center.1 - radius).floor() as i32;
+        let max_y = (center.1 + radius).ceil() as i32;
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let x_diff = x as f32 + 0.5 - center.0;
+                let y_diff = y as f32 + 0.5 - center.1;
+                if x_diff * x_diff + y_diff * y_diff < radius * radius {
+                    self.set(x, y, wall);
+                }
+            }
+        }
+    }
+
+    // nearest neighbour, the same as GrayScottGrid::resize
+    pub fn resized(&self, width: i32, height: i32) -> Self {
+        let mut mask = Mask::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let old_x = ((x as f32 + 0.5) / width as f32 * self.width as f32) as i32;
+                let old_y = ((y as f32 + 0.5) / height as f32 * self.height as f32) as i32;
+                mask.cells[(x + y * width) as usize] = self.cells[(old_x + old_y * self.width) as usize];
+            }
+        }
+        return mask;
+    }
+}
@@ -0,0 +1,18 @@
+pub mod gray_scott;
+pub mod image;
+pub mod color_map;
+pub mod state_file;
+pub mod preset;
+pub mod parameter_map;
+pub mod stability;
+pub mod stencil;
+pub mod integrator;
+pub mod fixed_timestep;
+pub mod adaptive_budget;
+pub mod resolution;
+pub mod boundary;
+pub mod mask;
+pub mod seed;
+pub mod brush;
+pub mod stroke;
+pub mod shortcuts;
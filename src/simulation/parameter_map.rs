@@ -0,0 +1,109 @@
+use crate::simulation::image::decode_png;
+
+// roughly the part of the Pearson classification where anything interesting happens
+pub const PEARSON_FEED_RANGE: (f32, f32) = (0.01, 0.1);
+pub const PEARSON_KILL_RANGE: (f32, f32) = (0.045, 0.07);
+
+#[derive(Clone)]
+pub struct ParameterImage {
+    pub width: i32,
+    pub height: i32,
+    // 8 bit rgba, top row first like a png
+    pub rgba: Vec<u8>,
+}
+
+impl ParameterImage {
+    pub fn from_png(bytes: &[u8]) -> Result<Self, String> {
+        let (width, height, rgba) = decode_png(bytes)?;
+        return Ok(Self { width, height, rgba });
+    }
+
+    // nearest pixel to uv, the same as sampling a NEAREST, CLAMP_TO_EDGE texture at (u, 1 - v)
+    fn sample(&self, u: f32, v: f32) -> (f32, f32) {
+        let x = ((u * self.width as f32) as i32).clamp(0, self.width - 1);
+        let y = (((1.0 - v) * self.height as f32) as i32).clamp(0, self.height - 1);
+        let i = ((x + y * self.width) * 4) as usize;
+        return (self.rgba[i] as f32 / 255.0, self.rgba[i + 1] as f32 / 255.0);
+    }
+}
+
+#[derive(Clone)]
+pub enum ParameterSource {
+    // the single feed and kill rate everywhere
+    Uniform,
+    // feed goes across feed_range from left to right and kill across kill_range from bottom to top
+    Gradient,
+    // red picks feed inside feed_range and green picks kill inside kill_range
+    Image(ParameterImage),
+}
+
+impl ParameterSource {
+    // by index, in the order the ui lists them
+    pub fn names() -> [&'static str; 3] {
+        return ["uniform", "feed/kill map", "image"];
+    }
+
+    pub fn index(&self) -> usize {
+        return match self {
+            ParameterSource::Uniform => 0,
+            ParameterSource::Gradient => 1,
+            ParameterSource::Image(_) => 2,
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct ParameterMap {
+    pub source: ParameterSource,
+    pub feed_range: (f32, f32),
+    pub kill_range: (f32, f32),
+}
+
+impl ParameterMap {
+    pub fn uniform() -> Self {
+        return Self {
+            source: ParameterSource::Uniform,
+            feed_range: PEARSON_FEED_RANGE,
+            kill_range: PEARSON_KILL_RANGE,
+        };
+    }
+
+    pub fn gradient(feed_range: (f32, f32), kill_range: (f32, f32)) -> Self {
+        return Self {
+            source: ParameterSource::Gradient,
+            feed_range,
+            kill_range,
+        };
+    }
+
+    pub fn image(image: ParameterImage, feed_range: (f32, f32), kill_range: (f32, f32)) -> Self {
+        return Self {
+            source: ParameterSource::Image(image),
+            feed_range,
+            kill_range,
+        };
+    }
+
+    // value of u_parameter_mode in shaders/reaction_diffusion.frag
+    pub fn shader_mode(&self) -> i32 {
+        return self.source.index() as i32;
+    }
+
+    // feed and kill for cell x, y. feed and kill are the rates used when the map is uniform.
+    // uv is the center of the cell like v_uv in the shader
+    pub fn feed_kill(&self, x: i32, y: i32, width: i32, height: i32, feed: f32, kill: f32) -> (f32, f32) {
+        let u = (x as f32 + 0.5) / width as f32;
+        let v = (y as f32 + 0.5) / height as f32;
+        let (feed_t, kill_t) = match &self.source {
+            ParameterSource::Uniform => return (feed, kill),
+            ParameterSource::Gradient => (u, v),
+            ParameterSource::Image(image) => image.sample(u, v),
+        };
+        return (mix(self.feed_range, feed_t), mix(self.kill_range, kill_t));
+    }
+}
+
+// same as glsl mix
+fn mix(range: (f32, f32), t: f32) -> f32 {
+    return range.0 * (1.0 - t) + range.1 * t;
+}
@@ -0,0 +1,112 @@
+use serde::Deserialize;
+use crate::simulation::color_map::ColorMap;
+use crate::simulation::seed::Seed;
+use crate::simulation::stencil::Kernel;
+
+// feed and kill values from: https://pmneila.github.io/jsexp/grayscott/
+const BUILTIN_PRESETS: &str = include_str!("presets.json");
+
+#[derive(Clone, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub feed: f32,
+    pub kill: f32,
+    #[serde(default)]
+    pub diffusion_a: Option<f32>,
+    #[serde(default)]
+    pub diffusion_b: Option<f32>,
+    #[serde(default)]
+    pub delta_t: Option<f32>,
+    // 3x3 or 5x5, top row first
+    #[serde(default)]
+    pub kernel: Option<Vec<f32>>,
+    // how the field should be started for this preset, see Seed::parse
+    #[serde(default)]
+    pub seed: Option<String>,
+    // name of a ColorMap
+    #[serde(default)]
+    pub palette: Option<String>,
+}
+
+impl Preset {
+    pub fn kernel(&self) -> Option<Kernel> {
+        return self.kernel.as_ref().and_then(|kernel| Kernel::new(kernel.clone()).ok());
+    }
+
+    pub fn seed(&self) -> Option<Seed> {
+        return self.seed.as_ref().and_then(|seed| Seed::parse(seed).ok());
+    }
+
+    pub fn color_map(&self) -> Option<ColorMap> {
+        return self.palette.as_ref().and_then(|palette| ColorMap::by_name(palette));
+    }
+}
+
+// a json array of presets
+pub fn load_presets(json: &str) -> Result<Vec<Preset>, String> {
+    let presets: Vec<Preset> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    for preset in &presets {
+        if preset.kernel.is_some() && preset.kernel().is_none() {
+            return Err(format!("preset {} has a kernel that isn't 3x3 or 5x5", preset.name));
+        }
+        if let Some(seed) = preset.seed.as_ref() {
+            Seed::parse(seed).map_err(|e| format!("preset {}: {}", preset.name, e))?;
+        }
+        if let Some(palette) = preset.palette.as_ref() {
+            if ColorMap::by_name(palette).is_none() {
+                return Err(format!("preset {} has an unknown palette {}", preset.name, palette));
+            }
+        }
+    }
+    return Ok(presets);
+}
+
+pub fn builtin_presets() -> Vec<Preset> {
+    return load_presets(BUILTIN_PRESETS).expect("presets.json is invalid");
+}
+
+pub fn find_preset<'a>(presets: &'a [Preset], name: &str) -> Option<&'a Preset> {
+    return presets.iter().find(|preset| preset.name.eq_ignore_ascii_case(name));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::preset::*;
+
+    #[test]
+    fn builtin_presets_load() {
+        let presets = load_presets(BUILTIN_PRESETS).unwrap();
+        assert!(!presets.is_empty());
+        for preset in &presets {
+            assert_eq!(preset.seed.is_some(), preset.seed().is_some(), "{}", preset.name);
+            assert_eq!(preset.palette.is_some(), preset.color_map().is_some(), "{}", preset.name);
+            assert_eq!(preset.kernel.is_some(), preset.kernel().is_some(), "{}", preset.name);
+        }
+        assert!(find_preset(&presets, presets[0].name.to_uppercase().as_str()).is_some());
+    }
+
+    #[test]
+    fn unknown_palette_fails() {
+        let json = r#"[{ "name": "test", "feed": 0.05, "kill": 0.06, "palette": "rainbow" }]"#;
+        assert!(load_presets(json).is_err());
+    }
+
+    #[test]
+    fn unknown_seed_fails() {
+        let json = r#"[{ "name": "test", "feed": 0.05, "kill": 0.06, "seed": "spiral" }]"#;
+        assert!(load_presets(json).is_err());
+    }
+
+    #[test]
+    fn bad_kernel_fails() {
+        let json = r#"[{ "name": "test", "feed": 0.05, "kill": 0.06, "kernel": [0.0, 1.0, 0.0, 1.0] }]"#;
+        assert!(load_presets(json).is_err());
+    }
+
+    #[test]
+    fn optional_fields_can_be_left_out() {
+        let presets = load_presets(r#"[{ "name": "test", "feed": 0.05, "kill": 0.06, "seed": "squares:4" }]"#).unwrap();
+        assert!(presets[0].diffusion_a.is_none() && presets[0].kernel().is_none() && presets[0].color_map().is_none());
+        assert!(matches!(presets[0].seed(), Some(Seed::Squares { count: 4 })));
+    }
+}
@@ -0,0 +1,164 @@
+// how big the grid is compared to the canvas
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Resolution {
+    // screen pixels per cell, so the grid follows the window size
+    Scale(f32),
+    // exactly this many cells no matter how big the window is
+    Fixed(i32, i32),
+}
+
+impl Resolution {
+    pub fn grid_size(&self, screen_size: (i32, i32)) -> (i32, i32) {
+        return match *self {
+            Resolution::Scale(scale) => (
+                ((screen_size.0 as f32 / scale).round() as i32).max(1),
+                ((screen_size.1 as f32 / scale).round() as i32).max(1),
+            ),
+            Resolution::Fixed(width, height) => (width.max(1), height.max(1)),
+        };
+    }
+
+    // "1.5x" for a scale or "512x256" for a fixed size
+    pub fn parse(text: &str) -> Result<Resolution, String> {
+        let text = text.trim();
+        if let Some(scale) = text.strip_suffix('x') {
+            let scale = scale.parse::<f32>().map_err(|_| format!("invalid scale \"{}\"", text))?;
+            if scale <= 0.0 {
+                return Err(format!("scale has to be above 0, got {}", scale));
+            }
+            return Ok(Resolution::Scale(scale));
+        }
+        let (width, height) = text.split_once('x').ok_or(format!("invalid resolution \"{}\", expected like 1.5x or 512x512", text))?;
+        let width = width.parse::<i32>().map_err(|_| format!("invalid width \"{}\"", width))?;
+        let height = height.parse::<i32>().map_err(|_| format!("invalid height \"{}\"", height))?;
+        if width <= 0 || height <= 0 {
+            return Err(format!("resolution has to be at least 1x1, got {}x{}", width, height));
+        }
+        return Ok(Resolution::Fixed(width, height));
+    }
+
+    pub fn name(&self) -> String {
+        return match self {
+            Resolution::Scale(scale) => format!("{}x", scale),
+            Resolution::Fixed(width, height) => format!("{}x{}", width, height),
+        };
+    }
+}
+
+// how the grid is drawn when it isn't the same shape as the canvas
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FitMode {
+    // fills the whole canvas, cells stop being square
+    Stretch,
+    // as big as fits with square cells, black bars on the sides that are left over
+    Letterbox,
+}
+
+impl FitMode {
+    pub fn all() -> [FitMode; 2] {
+        return [FitMode::Stretch, FitMode::Letterbox];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            FitMode::Stretch => "stretch",
+            FitMode::Letterbox => "letterbox",
+        };
+    }
+
+    // (x, y, width, height) in screen pixels of where a grid_size grid goes, with y up like gl.viewport
+    pub fn display_rect(&self, grid_size: (i32, i32), screen_size: (i32, i32)) -> (i32, i32, i32, i32) {
+        return match self {
+            FitMode::Stretch => (0, 0, screen_size.0, screen_size.1),
+            FitMode::Letterbox => {
+                let scale = (screen_size.0 as f32 / grid_size.0 as f32).min(screen_size.1 as f32 / grid_size.1 as f32);
+                let width = ((grid_size.0 as f32 * scale).round() as i32).max(1);
+                let height = ((grid_size.1 as f32 * scale).round() as i32).max(1);
+                ((screen_size.0 - width) / 2, (screen_size.1 - height) / 2, width, height)
+            }
+        };
+    }
+
+    // a position in screen pixels (y down) to a position in cells (y up). can be outside the grid in the bars
+    pub fn screen_to_grid(&self, position: (f32, f32), grid_size: (i32, i32), screen_size: (i32, i32)) -> (f32, f32) {
+        let (x, y, width, height) = self.display_rect(grid_size, screen_size);
+        let screen_y_up = screen_size.1 as f32 - position.1;
+        return (
+            (position.0 - x as f32) / width as f32 * grid_size.0 as f32,
+            (screen_y_up - y as f32) / height as f32 * grid_size.1 as f32,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::resolution::*;
+
+    #[test]
+    fn stretch_fills_the_screen() {
+        for grid_size in [(100, 50), (50, 100), (3, 7)] {
+            assert_eq!(FitMode::Stretch.display_rect(grid_size, (800, 600)), (0, 0, 800, 600));
+        }
+        // cells are wider than they are tall, 8x12 pixels
+        assert_eq!(FitMode::Stretch.screen_to_grid((400.0, 300.0), (100, 50), (800, 600)), (50.0, 25.0));
+        assert_eq!(FitMode::Stretch.screen_to_grid((200.0, 150.0), (100, 50), (800, 600)), (25.0, 37.5));
+        assert_eq!(FitMode::Stretch.screen_to_grid((0.0, 600.0), (100, 50), (800, 600)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn letterbox_bars_above_and_below_a_wide_grid() {
+        let (grid_size, screen_size) = ((100, 50), (800, 600));
+        assert_eq!(FitMode::Letterbox.display_rect(grid_size, screen_size), (0, 100, 800, 400));
+
+        assert_eq!(FitMode::Letterbox.screen_to_grid((400.0, 300.0), grid_size, screen_size), (50.0, 25.0));
+        // corners of the grid, screen y is down
+        assert_eq!(FitMode::Letterbox.screen_to_grid((0.0, 500.0), grid_size, screen_size), (0.0, 0.0));
+        assert_eq!(FitMode::Letterbox.screen_to_grid((800.0, 100.0), grid_size, screen_size), (100.0, 50.0));
+        // in the bars
+        assert_eq!(FitMode::Letterbox.screen_to_grid((400.0, 50.0), grid_size, screen_size), (50.0, 56.25));
+        assert_eq!(FitMode::Letterbox.screen_to_grid((400.0, 590.0), grid_size, screen_size), (50.0, -11.25));
+    }
+
+    #[test]
+    fn letterbox_bars_on_the_sides_of_a_tall_grid() {
+        let (grid_size, screen_size) = ((50, 100), (800, 600));
+        assert_eq!(FitMode::Letterbox.display_rect(grid_size, screen_size), (250, 0, 300, 600));
+
+        assert_eq!(FitMode::Letterbox.screen_to_grid((400.0, 300.0), grid_size, screen_size), (25.0, 50.0));
+        assert_eq!(FitMode::Letterbox.screen_to_grid((250.0, 600.0), grid_size, screen_size), (0.0, 0.0));
+        assert_eq!(FitMode::Letterbox.screen_to_grid((550.0, 0.0), grid_size, screen_size), (50.0, 100.0));
+        assert_eq!(FitMode::Letterbox.screen_to_grid((100.0, 300.0), grid_size, screen_size), (-25.0, 50.0));
+    }
+
+    #[test]
+    fn letterbox_keeps_cells_square() {
+        for (grid_size, screen_size) in [((100, 50), (800, 600)), ((50, 100), (800, 600)), ((640, 480), (1920, 1080)), ((3, 1), (10, 10))] {
+            let (x, y, width, height) = FitMode::Letterbox.display_rect(grid_size, screen_size);
+            let cell_width = width as f32 / grid_size.0 as f32;
+            let cell_height = height as f32 / grid_size.1 as f32;
+            assert!((cell_width - cell_height).abs() <= 0.5, "{:?} on {:?}", grid_size, screen_size);
+            // centered and inside the screen, touching it on at least one axis
+            assert!(x >= 0 && y >= 0 && x + width <= screen_size.0 && y + height <= screen_size.1);
+            assert!(width == screen_size.0 || height == screen_size.1);
+            assert!((screen_size.0 - width - 2 * x).abs() <= 1 && (screen_size.1 - height - 2 * y).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn grid_size_and_parse() {
+        assert_eq!(Resolution::Scale(2.0).grid_size((801, 600)), (401, 300));
+        assert_eq!(Resolution::Scale(1000.0).grid_size((800, 600)), (1, 1));
+        assert_eq!(Resolution::Fixed(512, 256).grid_size((800, 600)), (512, 256));
+
+        assert_eq!(Resolution::parse(" 1.5x "), Ok(Resolution::Scale(1.5)));
+        assert_eq!(Resolution::parse("512x256"), Ok(Resolution::Fixed(512, 256)));
+        // a trailing x always means a scale
+        assert_eq!(Resolution::parse("512x"), Ok(Resolution::Scale(512.0)));
+        for text in ["", "0x", "-1x", "x", "512", "0x10", "ax2"] {
+            assert!(Resolution::parse(text).is_err(), "{}", text);
+        }
+        for resolution in [Resolution::Scale(0.5), Resolution::Fixed(300, 200)] {
+            assert_eq!(Resolution::parse(&resolution.name()), Ok(resolution));
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use rand::Rng;
+use crate::simulation::gray_scott::{cell_xy_to_index, float_to_u16float};
+use crate::simulation::image::decode_png;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SeedShape {
+    Square,
+    Circle,
+}
+
+// how the field starts out before any painting. everything starts as a = 1, b = 0 and the seed adds b
+#[derive(Clone, PartialEq, Debug)]
+pub enum Seed {
+    // one shape of b in the middle, radius cells from the center to the edge
+    Center { shape: SeedShape, radius: i32 },
+    // every cell has a density chance of being b
+    Noise { density: f32 },
+    // count circles of b at random positions
+    Circles { count: i32, radius: i32 },
+    // count 20x20 squares of b at random positions, what rd-headless --seed starts from
+    Squares { count: i32 },
+    // b is the luminance of the image, stretched over the grid. rgba is top row first like a png
+    Image { width: i32, height: i32, rgba: Vec<u8> },
+    // the text in a blocky 5x7 font, as large as fits in the middle. lines are split on '\n'
+    Text(String),
+}
+
+impl Default for Seed {
+    // the same 20x20 square as init_cells
+    fn default() -> Self {
+        return Seed::Center { shape: SeedShape::Square, radius: 10 };
+    }
+}
+
+impl Seed {
+    pub fn image_from_png(bytes: &[u8]) -> Result<Seed, String> {
+        let (width, height, rgba) = decode_png(bytes)?;
+        return Ok(Seed::Image { width, height, rgba });
+    }
+
+    // "center", "circle", "noise:0.05", "circles:12", "squares:8" or "text:hello". images need the file read
+    // first so they go through image_from_png
+    pub fn parse(text: &str) -> Result<Seed, String> {
+        let (name, value) = match text.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (text, None),
+        };
+        let number = |default: f32| -> Result<f32, String> {
+            return match value {
+                Some(value) => value.parse::<f32>().map_err(|_| format!("invalid value for seed {}: {}", name, value)),
+                None => Ok(default),
+            };
+        };
+        return match name {
+            "center" | "square" => Ok(Seed::default()),
+            "circle" => Ok(Seed::Center { shape: SeedShape::Circle, radius: number(10.0)? as i32 }),
+            "noise" => Ok(Seed::Noise { density: number(0.05)? }),
+            "circles" => Ok(Seed::Circles { count: number(12.0)? as i32, radius: 10 }),
+            "squares" => Ok(Seed::Squares { count: number(8.0)? as i32 }),
+            // a literal \n also starts a new line, for command lines and urls
+            "text" => Ok(Seed::Text(value.unwrap_or("").replace("\\n", "\n"))),
+            _ => Err(format!("unknown seed {}", text)),
+        };
+    }
+
+    // fills cells with this seed. the random ones draw from rng so a seeded rng gives the same field every time
+    pub fn apply(&self, cells: &mut [u16], width: i32, height: i32, rng: &mut impl Rng) {
+        for i in (0..cells.len()).step_by(2) {
+            cells[i] = u16::MAX;
+            cells[i + 1] = 0;
+        }
+
+        match self {
+            Seed::Center { shape, radius } => {
+                draw_shape(cells, width, height, *shape, (width / 2, height / 2), *radius);
+            }
+            Seed::Noise { density } => {
+                for i in (0..cells.len()).step_by(2) {
+                    if rng.gen::<f32>() < *density {
+                        cells[i + 1] = u16::MAX;
+                    }
+                }
+            }
+            Seed::Circles { count, radius } => {
+                for _ in 0..*count {
+                    let center = (rng.gen_range(0..width), rng.gen_range(0..height));
+                    draw_shape(cells, width, height, SeedShape::Circle, center, *radius);
+                }
+            }
+            Seed::Squares { count } => {
+                for _ in 0..*count {
+                    let center = (rng.gen_range(0..width), rng.gen_range(0..height));
+                    draw_shape(cells, width, height, SeedShape::Square, center, 10);
+                }
+            }
+            Seed::Image { width: image_width, height: image_height, rgba } => {
+                for y in 0..height {
+                    for x in 0..width {
+                        let image_x = ((x as f32 + 0.5) / width as f32 * *image_width as f32) as i32;
+                        let image_y = image_height - 1 - ((y as f32 + 0.5) / height as f32 * *image_height as f32) as i32;
+                        let pixel = ((image_x + image_y * image_width) * 4) as usize;
+                        let luminance = (0.299 * rgba[pixel] as f32 + 0.587 * rgba[pixel + 1] as f32 + 0.114 * rgba[pixel + 2] as f32) / 255.0;
+                        let i = cell_xy_to_index(x, y, width, height);
+                        cells[i + 1] = float_to_u16float(luminance.clamp(0.0, 1.0));
+                    }
+                }
+            }
+            Seed::Text(text) => draw_text(cells, width, height, text),
+        }
+    }
+}
+
+// wraps around the edges like the simulation
+fn draw_shape(cells: &mut [u16], width: i32, height: i32, shape: SeedShape, center: (i32, i32), radius: i32) {
+    for y in (center.1 - radius)..(center.1 + radius) {
+        for x in (center.0 - radius)..(center.0 + radius) {
+            if let SeedShape::Circle = shape {
+                let x_diff = x as f32 + 0.5 - center.0 as f32;
+                let y_diff = y as f32 + 0.5 - center.1 as f32;
+                if x_diff * x_diff + y_diff * y_diff >= (radius * radius) as f32 {
+                    continue;
+                }
+            }
+            let i = cell_xy_to_index(x, y, width, height);
+            cells[i + 1] = u16::MAX;
+        }
+    }
+}
+
+const GLYPH_WIDTH: i32 = 5;
+const GLYPH_HEIGHT: i32 = 7;
+
+// rows top to bottom, the highest of the 5 bits is the left column. lowercase is drawn as uppercase and
+// anything else missing as '?'
+const GLYPHS: [(char, [u8; 7]); 44] = [
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('\'', [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+];
+
+fn glyph(c: char) -> [u8; 7] {
+    let c = c.to_ascii_uppercase();
+    let found = GLYPHS.iter().find(|(glyph_char, _)| *glyph_char == c).or_else(|| GLYPHS.iter().find(|(glyph_char, _)| *glyph_char == '?'));
+    return found.unwrap().1;
+}
+
+fn draw_text(cells: &mut [u16], width: i32, height: i32, text: &str) {
+    let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+    let longest = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32;
+    if longest == 0 {
+        return;
+    }
+
+    // a column between letters and a row between lines
+    let columns = longest * (GLYPH_WIDTH + 1) - 1;
+    let rows = lines.len() as i32 * (GLYPH_HEIGHT + 1) - 1;
+    let scale = ((width as f32 * 0.8 / columns as f32).min(height as f32 * 0.6 / rows as f32) as i32).max(1);
+
+    // grid y is up, so the first line starts at the top
+    let left = (width - columns * scale) / 2;
+    let top = (height + rows * scale) / 2 - 1;
+    for (line_index, line) in lines.iter().enumerate() {
+        let line_left = left + (columns - (line.len() as i32 * (GLYPH_WIDTH + 1) - 1)) * scale / 2;
+        for (char_index, c) in line.iter().enumerate() {
+            let rows_bits = glyph(*c);
+            for (row, bits) in rows_bits.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                        continue;
+                    }
+                    let x = line_left + (char_index as i32 * (GLYPH_WIDTH + 1) + column) * scale;
+                    let y = top - (line_index as i32 * (GLYPH_HEIGHT + 1) + row as i32) * scale;
+                    for offset_y in 0..scale {
+                        for offset_x in 0..scale {
+                            let i = cell_xy_to_index(x + offset_x, y - offset_y, width, height);
+                            cells[i + 1] = u16::MAX;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::simulation::seed::*;
+
+    // the cells with b, as (x, y) with y up
+    fn b_cells(seed: &Seed, width: i32, height: i32) -> Vec<(i32, i32)> {
+        let mut cells = vec![0; (width * height * 2) as usize];
+        seed.apply(&mut cells, width, height, &mut StdRng::seed_from_u64(1));
+        let mut lit = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let i = cell_xy_to_index(x, y, width, height);
+                assert_eq!(cells[i], u16::MAX);
+                if cells[i + 1] != 0 {
+                    lit.push((x, y));
+                }
+            }
+        }
+        return lit;
+    }
+
+    #[test]
+    fn parses_every_seed() {
+        assert_eq!(Seed::parse("center").unwrap(), Seed::default());
+        assert_eq!(Seed::parse("square").unwrap(), Seed::default());
+        assert_eq!(Seed::parse("circle").unwrap(), Seed::Center { shape: SeedShape::Circle, radius: 10 });
+        assert_eq!(Seed::parse("circle:25").unwrap(), Seed::Center { shape: SeedShape::Circle, radius: 25 });
+        assert_eq!(Seed::parse("noise").unwrap(), Seed::Noise { density: 0.05 });
+        assert_eq!(Seed::parse("noise:0.2").unwrap(), Seed::Noise { density: 0.2 });
+        assert_eq!(Seed::parse("circles").unwrap(), Seed::Circles { count: 12, radius: 10 });
+        assert_eq!(Seed::parse("circles:3").unwrap(), Seed::Circles { count: 3, radius: 10 });
+        assert_eq!(Seed::parse("squares").unwrap(), Seed::Squares { count: 8 });
+        assert_eq!(Seed::parse("squares:2").unwrap(), Seed::Squares { count: 2 });
+        assert_eq!(Seed::parse("text").unwrap(), Seed::Text(String::new()));
+        // only the first colon splits, the rest is text
+        assert_eq!(Seed::parse("text:a:b").unwrap(), Seed::Text(String::from("a:b")));
+        assert_eq!(Seed::parse("text:two\\nlines").unwrap(), Seed::Text(String::from("two\nlines")));
+    }
+
+    #[test]
+    fn bad_seeds_fail() {
+        for text in ["", "image", "Circle", "noise:lots", "circles:", "squares:-", "center square"] {
+            assert!(Seed::parse(text).is_err(), "{}", text);
+        }
+        assert_eq!(Seed::parse("spiral").err().unwrap(), "unknown seed spiral");
+    }
+
+    #[test]
+    fn center_shapes() {
+        let square = b_cells(&Seed::default(), 64, 64);
+        assert_eq!(square.len(), 400);
+        assert!(square.contains(&(22, 22)) && square.contains(&(41, 41)) && !square.contains(&(42, 42)));
+
+        let circle = b_cells(&Seed::Center { shape: SeedShape::Circle, radius: 10 }, 64, 64);
+        assert!(circle.len() > 300 && circle.len() < 320, "{}", circle.len());
+        assert!(circle.contains(&(32, 32)) && !circle.contains(&(22, 22)));
+    }
+
+    #[test]
+    fn random_seeds_repeat_with_the_same_rng() {
+        for seed in [Seed::Noise { density: 0.1 }, Seed::Circles { count: 3, radius: 4 }, Seed::Squares { count: 3 }] {
+            let cells = b_cells(&seed, 64, 64);
+            assert!(!cells.is_empty(), "{:?}", seed);
+            assert_eq!(b_cells(&seed, 64, 64), cells, "{:?}", seed);
+        }
+        let noise = b_cells(&Seed::Noise { density: 0.1 }, 64, 64).len();
+        assert!(noise > 300 && noise < 520, "{}", noise);
+    }
+
+    #[test]
+    fn image_seeds_use_luminance_with_the_top_row_first() {
+        // white on top, black underneath
+        let rgba = [255, 255, 255, 255, 0, 0, 0, 255].to_vec();
+        let seed = Seed::Image { width: 1, height: 2, rgba };
+        assert_eq!(b_cells(&seed, 2, 4), [(0, 2), (1, 2), (0, 3), (1, 3)]);
+    }
+
+    #[test]
+    fn text_is_drawn_from_the_glyphs() {
+        // I is 11 pixels, 3 wide at the top and bottom
+        let i = b_cells(&Seed::Text(String::from("I")), 10, 14);
+        // scaled to fit 80% of the width
+        let scale = (10.0 * 0.8 / 5.0) as usize;
+        assert_eq!(scale, 1);
+        assert_eq!(i.len(), 11);
+        let top = i.iter().map(|(_, y)| *y).max().unwrap();
+        let bottom = i.iter().map(|(_, y)| *y).min().unwrap();
+        assert_eq!(top - bottom, 6);
+        assert_eq!(i.iter().filter(|(_, y)| *y == top).count(), 3);
+        assert_eq!(i.iter().filter(|(_, y)| *y == top - 1).count(), 1);
+
+        // twice as big on a grid twice the size
+        assert_eq!(b_cells(&Seed::Text(String::from("I")), 20, 28).len(), 11 * 4);
+        // lower case is drawn as upper case, anything without a glyph as ?
+        assert_eq!(b_cells(&Seed::Text(String::from("i")), 10, 14), i);
+        assert_eq!(b_cells(&Seed::Text(String::from("~")), 10, 14), b_cells(&Seed::Text(String::from("?")), 10, 14));
+        assert!(b_cells(&Seed::Text(String::new()), 10, 14).is_empty());
+    }
+
+    #[test]
+    fn text_lines_go_down_the_grid() {
+        // L's full row is at the bottom of its glyph, so the second line's full row is lower down
+        let lines = b_cells(&Seed::Text(String::from("I\nL")), 40, 40);
+        // two lines only fit at 1 cell a pixel, I and L are 11 pixels each
+        assert_eq!(lines.len(), 22);
+        let lowest = lines.iter().map(|(_, y)| *y).min().unwrap();
+        let highest = lines.iter().map(|(_, y)| *y).max().unwrap();
+        let bottom_row = lines.iter().filter(|(_, y)| *y == lowest).count();
+        let top_row = lines.iter().filter(|(_, y)| *y == highest).count();
+        // L's bottom is 5 wide, I's top 3 wide
+        assert_eq!(bottom_row * 3, top_row * 5);
+    }
+}
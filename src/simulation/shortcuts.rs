@@ -0,0 +1,186 @@
+// where the bindings are kept between visits
+const STORAGE_KEY: &str = "reaction-diffusion-shortcuts";
+// number keys 1 to 9 pick the first nine presets
+const PRESET_SHORTCUTS: usize = 9;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Pause,
+    Clear,
+    RandomPreset,
+    // by index into the preset list
+    Preset(usize),
+    SmallerBrush,
+    BiggerBrush,
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec![Action::Pause, Action::Clear, Action::RandomPreset];
+        actions.extend((0..PRESET_SHORTCUTS).map(Action::Preset));
+        actions.push(Action::SmallerBrush);
+        actions.push(Action::BiggerBrush);
+        return actions;
+    }
+
+    pub fn name(&self) -> String {
+        return match self {
+            Action::Pause => String::from("pause"),
+            Action::Clear => String::from("clear"),
+            Action::RandomPreset => String::from("random preset"),
+            Action::Preset(i) => format!("preset {}", i + 1),
+            Action::SmallerBrush => String::from("smaller brush"),
+            Action::BiggerBrush => String::from("bigger brush"),
+        };
+    }
+
+    pub fn by_name(name: &str) -> Option<Action> {
+        return Action::all().into_iter().find(|action| action.name() == name);
+    }
+
+    // by key_name in engine::app::input
+    fn default_key(&self) -> String {
+        return match self {
+            Action::Pause => String::from("space"),
+            Action::Clear => String::from("c"),
+            Action::RandomPreset => String::from("r"),
+            Action::Preset(i) => (i + 1).to_string(),
+            Action::SmallerBrush => String::from("["),
+            Action::BiggerBrush => String::from("]"),
+        };
+    }
+}
+
+// which key does what. a key only ever does one thing, an action without a key is bound to ""
+#[derive(Clone, PartialEq, Debug)]
+pub struct Shortcuts {
+    bindings: Vec<(Action, String)>,
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        return Self {
+            bindings: Action::all().into_iter().map(|action| (action, action.default_key())).collect(),
+        };
+    }
+
+    pub fn key(&self, action: Action) -> &str {
+        return self.bindings.iter().find(|(a, _)| *a == action).map_or("", |(_, key)| key.as_str());
+    }
+
+    pub fn action(&self, key: &str) -> Option<Action> {
+        return self.bindings.iter().find(|(_, k)| k == key).map(|(action, _)| *action);
+    }
+
+    // whatever the key did before is left without one
+    pub fn rebind(&mut self, action: Action, key: &str) {
+        for (a, k) in self.bindings.iter_mut() {
+            if *a == action {
+                *k = String::from(key);
+            } else if k == key {
+                k.clear();
+            }
+        }
+    }
+
+    // one "action=key" line each
+    pub fn to_text(&self) -> String {
+        return self.bindings.iter().map(|(action, key)| format!("{}={}\n", action.name(), key)).collect();
+    }
+
+    // lines for actions that don't exist any more are skipped, and missing ones keep their default
+    pub fn from_text(text: &str) -> Self {
+        let mut shortcuts = Shortcuts::new();
+        for line in text.lines() {
+            // names never have an = in them, so "=" itself can still be a key
+            let (name, key) = match line.split_once('=') {
+                Some((name, key)) => (name, key),
+                None => continue,
+            };
+            if let Some(action) = Action::by_name(name) {
+                shortcuts.rebind(action, key);
+            }
+        }
+        return shortcuts;
+    }
+
+    // from local storage, or the defaults if nothing has been saved
+    pub fn load() -> Self {
+        let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+        return match storage.and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten()) {
+            Some(text) => Shortcuts::from_text(&text),
+            None => Shortcuts::new(),
+        };
+    }
+
+    pub fn save(&self) {
+        if let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok().flatten()) {
+            if storage.set_item(STORAGE_KEY, &self.to_text()).is_err() {
+                console_log!("could not save the shortcuts");
+            }
+        }
+    }
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        return Shortcuts::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::shortcuts::*;
+
+    #[test]
+    fn every_action_has_its_own_name_and_key() {
+        let shortcuts = Shortcuts::new();
+        for action in Action::all() {
+            assert_eq!(Action::by_name(&action.name()), Some(action));
+            assert_eq!(shortcuts.action(shortcuts.key(action)), Some(action));
+        }
+        assert_eq!(Action::by_name("preset 10"), None);
+    }
+
+    #[test]
+    fn rebinding_takes_the_key_from_the_action_that_had_it() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.rebind(Action::Pause, "c");
+        assert_eq!(shortcuts.key(Action::Pause), "c");
+        assert_eq!(shortcuts.key(Action::Clear), "");
+        assert_eq!(shortcuts.action("c"), Some(Action::Pause));
+        assert_eq!(shortcuts.action("space"), None);
+
+        // the other actions keep theirs
+        assert_eq!(shortcuts.key(Action::RandomPreset), "r");
+        assert_eq!(shortcuts.key(Action::Preset(0)), "1");
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.rebind(Action::Pause, "p");
+        shortcuts.rebind(Action::Preset(2), "=");
+        shortcuts.rebind(Action::BiggerBrush, "[");
+        shortcuts.rebind(Action::Clear, "");
+
+        let loaded = Shortcuts::from_text(&shortcuts.to_text());
+        assert_eq!(loaded, shortcuts);
+        assert_eq!(loaded.key(Action::Preset(2)), "=");
+        assert_eq!(loaded.key(Action::SmallerBrush), "");
+        assert_eq!(loaded.key(Action::Clear), "");
+    }
+
+    #[test]
+    fn unknown_actions_and_garbage_fall_back_to_the_defaults() {
+        assert_eq!(Shortcuts::from_text(""), Shortcuts::new());
+        assert_eq!(Shortcuts::from_text("not shortcuts at all\n\0\u{fffd}\n==\n"), Shortcuts::new());
+
+        let shortcuts = Shortcuts::from_text("teleport=t\npreset 12=x\nPause=p\nclear=k\n");
+        let mut expected = Shortcuts::new();
+        expected.rebind(Action::Clear, "k");
+        assert_eq!(shortcuts, expected);
+        assert_eq!(shortcuts.action("t"), None);
+        assert_eq!(shortcuts.action("x"), None);
+    }
+}
@@ -0,0 +1,109 @@
+use std::f32::consts::PI;
+use crate::simulation::integrator::Integrator;
+use crate::simulation::stencil::Kernel;
+
+// number of wave numbers checked along each axis
+const SAMPLES: usize = 32;
+
+// the largest time step the integrator can take on the diffusion term before it blows up.
+// each wave number k of the grid gets multiplied by integrator.amplification(dt * diffusion * λ(k)) every step,
+// where λ(k) is the kernel's fourier transform, so it's stable while that stays at or below 1 (von Neumann analysis).
+// the reaction terms are small next to diffusion so they are left out
+pub fn max_stable_delta_t(kernel: &Kernel, integrator: Integrator, diffusion: f32) -> f32 {
+    if diffusion <= 0.0 {
+        return f32::INFINITY;
+    }
+
+    let taps = kernel.taps();
+    let mut limit = f32::INFINITY;
+    for i in 0..SAMPLES {
+        for j in 0..SAMPLES {
+            let kx = i as f32 / SAMPLES as f32 * 2.0 * PI;
+            let ky = j as f32 / SAMPLES as f32 * 2.0 * PI;
+
+            let mut lambda = (0.0, 0.0);
+            for (x, y, weight) in &taps {
+                let phase = kx * *x as f32 + ky * *y as f32;
+                lambda.0 += weight * phase.cos();
+                lambda.1 += weight * phase.sin();
+            }
+
+            if lambda.0 * lambda.0 + lambda.1 * lambda.1 < 1e-10 {
+                // the constant part of the field, which a kernel that sums to 0 leaves alone
+                continue;
+            }
+            limit = limit.min(max_stable_step(integrator, lambda));
+        }
+    }
+    return limit / diffusion;
+}
+
+// the largest h where amplification(h * λ) <= 1, assuming it's stable from 0 up to there
+fn max_stable_step(integrator: Integrator, lambda: (f32, f32)) -> f32 {
+    let stable = |h: f32| integrator.amplification((h * lambda.0, h * lambda.1)) <= 1.0 + 1e-6;
+
+    let mut high = 1.0;
+    while stable(high) {
+        high *= 2.0;
+        if high > 1e6 {
+            return f32::INFINITY;
+        }
+    }
+
+    let mut low = 0.0;
+    for _ in 0..32 {
+        let middle = (low + high) / 2.0;
+        if stable(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    return low;
+}
+
+// a message to show when the settings would make the simulation blow up
+pub fn stability_warning(kernel: &Kernel, integrator: Integrator, diffusion_a: f32, diffusion_b: f32, delta_t: f32) -> Option<String> {
+    let limit = max_stable_delta_t(kernel, integrator, diffusion_a).min(max_stable_delta_t(kernel, integrator, diffusion_b));
+    if limit <= 0.0 {
+        return Some(format!("unstable for any time step with this kernel and {}", integrator.name()));
+    }
+    if delta_t > limit {
+        return Some(format!("unstable: time step {:.3} is above {:.3} for these diffusion rates", delta_t, limit));
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::gray_scott::{DEFAULT_DELTA_T, DEFAULT_DIFFUSION_A, DEFAULT_DIFFUSION_B};
+    use crate::simulation::integrator::Integrator;
+    use crate::simulation::stability::*;
+    use crate::simulation::stencil::{Kernel, Stencil};
+
+    #[test]
+    fn every_stencil_is_stable_at_the_defaults() {
+        for stencil in Stencil::all() {
+            for integrator in Integrator::all() {
+                let warning = stability_warning(&stencil.kernel(), integrator, DEFAULT_DIFFUSION_A, DEFAULT_DIFFUSION_B, DEFAULT_DELTA_T);
+                assert_eq!(warning, None, "{} with {}", stencil.name(), integrator.name());
+            }
+        }
+    }
+
+    #[test]
+    fn euler_limit_for_the_nine_point_stencil() {
+        // the checkerboard is multiplied by 1 - dt * diffusion * 1.6, which has to stay above -1
+        let limit = max_stable_delta_t(&Stencil::NinePoint.kernel(), Integrator::Euler, 1.0);
+        assert!((limit - 1.25).abs() < 1e-3, "{}", limit);
+        assert_eq!(max_stable_delta_t(&Stencil::NinePoint.kernel(), Integrator::Euler, 0.0), f32::INFINITY);
+    }
+
+    #[test]
+    fn warns_past_the_limit() {
+        // 0.3 times the 5-point laplacian, a checkerboard changes by 2.4 a step
+        let kernel = Kernel::new(vec![0.0, 0.3, 0.0, 0.3, -1.2, 0.3, 0.0, 0.3, 0.0]).unwrap();
+        assert!(stability_warning(&kernel, Integrator::Euler, 1.0, 0.5, 1.0).is_some());
+        assert!(stability_warning(&kernel, Integrator::Euler, 1.0, 0.5, 0.8).is_none());
+    }
+}
@@ -0,0 +1,397 @@
+use crate::simulation::boundary::{Boundaries, Boundary};
+use crate::simulation::integrator::Integrator;
+use crate::simulation::mask::Mask;
+use crate::simulation::gray_scott::{cell_xy_to_index, float_to_u16float, u16float_to_float};
+
+/*
+    Simulation state file. Everything is little endian
+
+    offset  size              field
+    0       4                 magic "RDST"
+    4       2                 version (u16), currently 1
+    6       4                 width (u32)
+    10      4                 height (u32)
+    14      4                 feed (f32)
+    18      4                 kill (f32)
+    22      4                 diffusion rate of a (f32)
+    26      4                 diffusion rate of b (f32)
+    30      4                 time step (f32)
+    34      1                 integrator (u8), Integrator::id
+    35      1                 x boundary (u8), Boundary::id
+    36      1                 y boundary (u8), Boundary::id
+    37      4                 a past a dirichlet edge (f32)
+    41      4                 b past a dirichlet edge (f32)
+    45      8                 iteration count (u64)
+    53      1                 kernel size n (u8), the kernel is n x n
+    54      n * n * 4         kernel weights (f32), top row first
+    ...     1                 has mask (u8), 0 or 1
+    ...     width * height * 4  cells as a, b pairs (u16, u16), bottom row first like the RG16UI texture
+    ...     width * height    mask (u8) if there is one, 255 for walls, bottom row first
+ */
+
+const MAGIC: &[u8; 4] = b"RDST";
+const VERSION: u16 = 1;
+
+pub const FILE_EXTENSION: &str = "rdstate";
+// the largest texture webgl2 implementations make, anything bigger can't be simulated so isn't read
+pub const MAX_SIZE: i32 = 16384;
+
+pub struct SimulationState {
+    pub width: i32,
+    pub height: i32,
+    pub feed: f32,
+    pub kill: f32,
+    pub diffusion_a: f32,
+    pub diffusion_b: f32,
+    pub delta_t: f32,
+    pub kernel: Vec<f32>,
+    pub integrator: Integrator,
+    pub boundaries: Boundaries,
+    // Mask cells, None when there are no walls
+    pub mask: Option<Vec<u8>>,
+    pub iterations: u64,
+    pub cells: Vec<u16>,
+}
+
+impl SimulationState {
+    pub fn kernel_size(&self) -> usize {
+        return (self.kernel.len() as f32).sqrt().round() as usize;
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(54 + self.kernel.len() * 4 + self.cells.len() * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.width as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.feed.to_le_bytes());
+        bytes.extend_from_slice(&self.kill.to_le_bytes());
+        bytes.extend_from_slice(&self.diffusion_a.to_le_bytes());
+        bytes.extend_from_slice(&self.diffusion_b.to_le_bytes());
+        bytes.extend_from_slice(&self.delta_t.to_le_bytes());
+        bytes.push(self.integrator.id());
+        bytes.push(self.boundaries.x.id());
+        bytes.push(self.boundaries.y.id());
+        bytes.extend_from_slice(&self.boundaries.edge_value.0.to_le_bytes());
+        bytes.extend_from_slice(&self.boundaries.edge_value.1.to_le_bytes());
+        bytes.extend_from_slice(&self.iterations.to_le_bytes());
+        bytes.push(self.kernel_size() as u8);
+        for weight in &self.kernel {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+        bytes.push(self.mask.is_some() as u8);
+        for value in &self.cells {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        if let Some(mask) = self.mask.as_ref() {
+            bytes.extend_from_slice(mask);
+        }
+        return bytes;
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err(String::from("not a simulation state file"));
+        }
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(format!("unsupported state file version {}", version));
+        }
+
+        let width = reader.u32()? as i32;
+        let height = reader.u32()? as i32;
+        if width <= 0 || height <= 0 || width > MAX_SIZE || height > MAX_SIZE {
+            return Err(format!("invalid size {}x{}", width, height));
+        }
+        let feed = reader.f32()?;
+        let kill = reader.f32()?;
+        let diffusion_a = reader.f32()?;
+        let diffusion_b = reader.f32()?;
+        let delta_t = reader.f32()?;
+        let id = reader.take(1)?[0];
+        let integrator = Integrator::from_id(id).ok_or_else(|| format!("unknown integrator {}", id))?;
+        let mut boundary = || {
+            let id = reader.take(1)?[0];
+            return Boundary::from_id(id).ok_or_else(|| format!("unknown boundary {}", id));
+        };
+        let mut boundaries = Boundaries::new(boundary()?, boundary()?);
+        boundaries.edge_value = (reader.f32()?, reader.f32()?);
+        let iterations = reader.u64()?;
+
+        let kernel_size = reader.take(1)?[0] as usize;
+        let mut kernel = Vec::with_capacity(kernel_size * kernel_size);
+        for _ in 0..(kernel_size * kernel_size) {
+            kernel.push(reader.f32()?);
+        }
+
+        let has_mask = reader.take(1)?[0] != 0;
+
+        let too_big = || format!("a {}x{} grid is too big", width, height);
+        let cell_count = (width as usize).checked_mul(height as usize).ok_or_else(too_big)?;
+        let cells_len = cell_count.checked_mul(2).ok_or_else(too_big)?;
+        let mask_len = if has_mask { cell_count } else { 0 };
+        let expected_len = cells_len.checked_mul(2).and_then(|len| len.checked_add(mask_len)).ok_or_else(too_big)?;
+        if bytes.len() - reader.position != expected_len {
+            return Err(format!("expected {} bytes of cells for a {}x{} grid", expected_len, width, height));
+        }
+        let mut cells = Vec::with_capacity(cells_len);
+        for _ in 0..cells_len {
+            cells.push(reader.u16()?);
+        }
+        let mask = if has_mask { Some(reader.take(mask_len)?.to_vec()) } else { None };
+
+        return Ok(Self {
+            width,
+            height,
+            feed,
+            kill,
+            diffusion_a,
+            diffusion_b,
+            delta_t,
+            kernel,
+            integrator,
+            boundaries,
+            mask,
+            iterations,
+            cells,
+        });
+    }
+
+    // bilinear resample. samples past a periodic edge wrap around like the simulation does, past any other edge they
+    // take the closest cell so nothing from the opposite side bleeds in
+    pub fn resampled(&self, width: i32, height: i32) -> Self {
+        let edge = |i: i32, size: i32, boundary: Boundary| if boundary == Boundary::Periodic { i } else { i.clamp(0, size - 1) };
+        let mut cells = vec![0; width as usize * height as usize * 2];
+        for y in 0..height {
+            for x in 0..width {
+                let source_x = (x as f32 + 0.5) / width as f32 * self.width as f32 - 0.5;
+                let source_y = (y as f32 + 0.5) / height as f32 * self.height as f32 - 0.5;
+                let x0 = source_x.floor() as i32;
+                let y0 = source_y.floor() as i32;
+                let tx = source_x - x0 as f32;
+                let ty = source_y - y0 as f32;
+
+                let i = cell_xy_to_index(x, y, width, height);
+                for channel in 0..2 {
+                    let sample = |x: i32, y: i32| {
+                        let x = edge(x, self.width, self.boundaries.x);
+                        let y = edge(y, self.height, self.boundaries.y);
+                        return u16float_to_float(self.cells[cell_xy_to_index(x, y, self.width, self.height) + channel]);
+                    };
+                    let bottom = sample(x0, y0) * (1.0 - tx) + sample(x0 + 1, y0) * tx;
+                    let top = sample(x0, y0 + 1) * (1.0 - tx) + sample(x0 + 1, y0 + 1) * tx;
+                    cells[i + channel] = float_to_u16float((bottom * (1.0 - ty) + top * ty).clamp(0.0, 1.0));
+                }
+            }
+        }
+
+        return Self {
+            width,
+            height,
+            feed: self.feed,
+            kill: self.kill,
+            diffusion_a: self.diffusion_a,
+            diffusion_b: self.diffusion_b,
+            delta_t: self.delta_t,
+            kernel: self.kernel.clone(),
+            integrator: self.integrator,
+            boundaries: self.boundaries,
+            // nearest neighbour so walls stay walls
+            mask: self.mask.as_ref().map(|mask| Mask::from_cells(self.width, self.height, mask.clone()).unwrap().resized(width, height).cells().to_vec()),
+            iterations: self.iterations,
+            cells,
+        };
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.position + len > self.bytes.len() {
+            return Err(String::from("state file is truncated"));
+        }
+        let slice = &self.bytes[self.position..(self.position + len)];
+        self.position += len;
+        return Ok(slice);
+    }
+
+    fn u16(&mut self) -> Result<u16, String> { Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap())) }
+    fn u32(&mut self) -> Result<u32, String> { Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap())) }
+    fn u64(&mut self) -> Result<u64, String> { Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap())) }
+    fn f32(&mut self) -> Result<f32, String> { Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap())) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::boundary::{Boundaries, Boundary};
+    use crate::simulation::gray_scott::{cell_xy_to_index, float_to_u16float, u16float_to_float};
+    use crate::simulation::integrator::Integrator;
+    use crate::simulation::state_file::*;
+
+    fn test_state() -> SimulationState {
+        let width = 3;
+        let height = 2;
+        let cells = (0..(width * height * 2)).map(|i| (i * 1000) as u16).collect();
+        let mut boundaries = Boundaries::new(Boundary::Dirichlet, Boundary::Neumann);
+        boundaries.edge_value = (0.75, 0.25);
+        return SimulationState {
+            width,
+            height,
+            feed: 0.055,
+            kill: 0.062,
+            diffusion_a: 0.9,
+            diffusion_b: 0.4,
+            delta_t: 0.8,
+            kernel: vec![0.05, 0.2, 0.05, 0.2, -1.0, 0.2, 0.05, 0.2, 0.05],
+            integrator: Integrator::Rk4,
+            boundaries,
+            mask: Some(vec![0, 255, 0, 0, 0, 255]),
+            iterations: 1234,
+            cells,
+        };
+    }
+
+    fn assert_same_grid(loaded: &SimulationState, state: &SimulationState) {
+        assert_eq!((loaded.width, loaded.height), (state.width, state.height));
+        assert_eq!((loaded.feed, loaded.kill), (state.feed, state.kill));
+        assert_eq!(loaded.iterations, state.iterations);
+        assert_eq!(loaded.kernel, state.kernel);
+        assert_eq!(loaded.cells, state.cells);
+    }
+
+    #[test]
+    fn round_trips() {
+        let state = test_state();
+        let loaded = SimulationState::from_bytes(&state.to_bytes()).unwrap();
+
+        assert_same_grid(&loaded, &state);
+        assert_eq!((loaded.diffusion_a, loaded.diffusion_b, loaded.delta_t), (state.diffusion_a, state.diffusion_b, state.delta_t));
+        assert!(loaded.integrator == state.integrator);
+        assert_eq!(loaded.boundaries, state.boundaries);
+        assert_eq!(loaded.mask, state.mask);
+    }
+
+    #[test]
+    fn round_trips_without_a_mask() {
+        let mut state = test_state();
+        state.mask = None;
+        let loaded = SimulationState::from_bytes(&state.to_bytes()).unwrap();
+
+        assert_same_grid(&loaded, &state);
+        assert_eq!(loaded.mask, None);
+    }
+
+    #[test]
+    fn truncated_files_fail() {
+        let bytes = test_state().to_bytes();
+        for len in [0, 3, 5, 20, 53, bytes.len() - 1] {
+            assert!(SimulationState::from_bytes(&bytes[..len]).is_err(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn extra_bytes_fail() {
+        let mut bytes = test_state().to_bytes();
+        bytes.push(0);
+        assert!(SimulationState::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn corrupt_headers_fail() {
+        let state = test_state();
+
+        let mut bytes = state.to_bytes();
+        bytes[0] = b'X';
+        assert_eq!(SimulationState::from_bytes(&bytes).err().unwrap(), "not a simulation state file");
+
+        for version in [0u16, 2] {
+            let mut bytes = state.to_bytes();
+            bytes[4..6].copy_from_slice(&version.to_le_bytes());
+            assert!(SimulationState::from_bytes(&bytes).is_err(), "version {}", version);
+        }
+
+        let mut bytes = state.to_bytes();
+        bytes[6..10].copy_from_slice(&0u32.to_le_bytes());
+        assert!(SimulationState::from_bytes(&bytes).is_err());
+
+        // would wrap around a 32 bit usize if it was multiplied out
+        for size in [65536u32, (MAX_SIZE + 1) as u32, u32::MAX] {
+            let mut bytes = state.to_bytes();
+            bytes[6..10].copy_from_slice(&size.to_le_bytes());
+            bytes[10..14].copy_from_slice(&size.to_le_bytes());
+            assert!(SimulationState::from_bytes(&bytes).is_err(), "size {}", size);
+        }
+
+        // integrator, then the two boundaries
+        for offset in [34, 35, 36] {
+            let mut bytes = state.to_bytes();
+            bytes[offset] = 200;
+            assert!(SimulationState::from_bytes(&bytes).is_err(), "byte {}", offset);
+        }
+    }
+
+    // 4x4 with a on the left half, b on the right half and walls in the last column
+    fn halves_state(boundaries: Boundaries) -> SimulationState {
+        let mut state = test_state();
+        state.width = 4;
+        state.height = 4;
+        state.boundaries = boundaries;
+        state.cells = vec![0; 32];
+        state.mask = Some((0..16).map(|i| if i % 4 == 3 { 255 } else { 0 }).collect());
+        for y in 0..4 {
+            for x in 0..4 {
+                let i = cell_xy_to_index(x, y, 4, 4);
+                state.cells[i] = float_to_u16float(if x < 2 { 1.0 } else { 0.0 });
+                state.cells[i + 1] = float_to_u16float(if x < 2 { 0.0 } else { 1.0 });
+            }
+        }
+        return state;
+    }
+
+    fn first_row_cell(state: &SimulationState, x: i32) -> (f32, f32) {
+        let i = cell_xy_to_index(x, 0, state.width, state.height);
+        return (u16float_to_float(state.cells[i]), u16float_to_float(state.cells[i + 1]));
+    }
+
+    #[test]
+    fn resampled_to_a_different_size() {
+        let resampled = halves_state(Boundaries::periodic()).resampled(8, 2);
+        let state = test_state();
+        assert_eq!((resampled.width, resampled.height), (8, 2));
+        assert_eq!(resampled.cells.len(), 8 * 2 * 2);
+        assert_eq!(resampled.iterations, state.iterations);
+        assert_eq!((resampled.feed, resampled.kill), (state.feed, state.kill));
+        assert_eq!(resampled.boundaries, Boundaries::periodic());
+
+        // x = 3 samples at 1.25, a quarter of the way from column 1 to column 2
+        let cell = |x: i32| first_row_cell(&resampled, x);
+        assert_eq!(cell(1), (1.0, 0.0));
+        let (a, b) = cell(3);
+        assert!((a - 0.75).abs() < 0.001 && (b - 0.25).abs() < 0.001, "{} {}", a, b);
+        assert_eq!(cell(5), (0.0, 1.0));
+        // x = 7 samples at 3.25, between the last column and the first
+        let (a, b) = cell(7);
+        assert!((a - 0.25).abs() < 0.001 && (b - 0.75).abs() < 0.001, "{} {}", a, b);
+
+        // walls in the last column stay walls
+        let mask = resampled.mask.unwrap();
+        assert_eq!(mask.len(), 16);
+        assert_eq!(&mask[0..8], &[0, 0, 0, 0, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn resampled_only_wraps_around_periodic_edges() {
+        for boundary in [Boundary::Neumann, Boundary::Dirichlet, Boundary::Absorbing] {
+            let resampled = halves_state(Boundaries::new(boundary, Boundary::Periodic)).resampled(8, 2);
+            // x = 0 and 7 sample past the left and right edges, which would mix in the other side if they wrapped
+            assert_eq!(first_row_cell(&resampled, 0), (1.0, 0.0), "{}", boundary.name());
+            assert_eq!(first_row_cell(&resampled, 7), (0.0, 1.0), "{}", boundary.name());
+        }
+    }
+}
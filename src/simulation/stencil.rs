@@ -0,0 +1,123 @@
+// the largest kernel the reaction_diffusion shader can take. smaller kernels sit in the middle of it
+pub const MAX_KERNEL_SIZE: usize = 5;
+
+// each of these is a fraction of the laplacian, scaled so a checkerboard (the pattern that changes fastest) changes
+// by at most 1.6 a step like it does with NINE_POINT. that keeps every stencil stable with euler at the default
+// diffusion rates and time step, at the cost of the same diffusion rate spreading a little slower with FIVE_POINT
+// (0.2 times the laplacian) and WIDE (0.15 times) than with NINE_POINT (0.3 times)
+const FIVE_POINT: [f32; 9] = [
+    0.0, 0.2, 0.0,
+    0.2, -0.8, 0.2,
+    0.0, 0.2, 0.0,
+];
+
+// from https://karlsims.com/rd.html
+const NINE_POINT: [f32; 9] = [
+    0.05, 0.2, 0.05,
+    0.2, -1.0, 0.2,
+    0.05, 0.2, 0.05,
+];
+
+// fourth order accurate (-1, 16, -30, 16, -1) / 12 along each axis
+const WIDE: [f32; 25] = [
+    0.0, 0.0, -0.0125, 0.0, 0.0,
+    0.0, 0.0, 0.2, 0.0, 0.0,
+    -0.0125, 0.2, -0.75, 0.2, -0.0125,
+    0.0, 0.0, 0.2, 0.0, 0.0,
+    0.0, 0.0, -0.0125, 0.0, 0.0,
+];
+
+// a square laplacian kernel with an odd size, top row first
+#[derive(Clone, PartialEq)]
+pub struct Kernel {
+    size: usize,
+    weights: Vec<f32>,
+}
+
+impl Kernel {
+    pub fn new(weights: Vec<f32>) -> Result<Self, String> {
+        let size = (weights.len() as f32).sqrt().round() as usize;
+        if size * size != weights.len() || size % 2 == 0 || size > MAX_KERNEL_SIZE {
+            return Err(format!("unsupported kernel with {} weights, expected 3x3 or 5x5", weights.len()));
+        }
+        return Ok(Self { size, weights });
+    }
+
+    pub fn size(&self) -> usize { self.size }
+    pub fn radius(&self) -> i32 { (self.size / 2) as i32 }
+    pub fn weights(&self) -> &[f32] { &self.weights }
+
+    // (x offset, y offset, weight) for every non zero weight, in the same order the shader samples them.
+    // y is up because row 0 of the texture is at v = 0
+    pub fn taps(&self) -> Vec<(i32, i32, f32)> {
+        let radius = self.radius();
+        let mut taps = Vec::with_capacity(self.weights.len());
+        for (i, weight) in self.weights.iter().enumerate() {
+            if *weight != 0.0 {
+                let x = (i % self.size) as i32 - radius;
+                let y = radius - (i / self.size) as i32;
+                taps.push((x, y, *weight));
+            }
+        }
+        return taps;
+    }
+
+    // the weights in the middle of a MAX_KERNEL_SIZE x MAX_KERNEL_SIZE grid of zeros for u_kernel
+    pub fn uniform_data(&self) -> [f32; MAX_KERNEL_SIZE * MAX_KERNEL_SIZE] {
+        let mut data = [0.0; MAX_KERNEL_SIZE * MAX_KERNEL_SIZE];
+        let offset = (MAX_KERNEL_SIZE - self.size) / 2;
+        for (i, weight) in self.weights.iter().enumerate() {
+            let x = i % self.size + offset;
+            let y = i / self.size + offset;
+            data[x + y * MAX_KERNEL_SIZE] = *weight;
+        }
+        return data;
+    }
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        return Stencil::NinePoint.kernel();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stencil {
+    FivePoint,
+    // isotropic, what the simulation has always used
+    NinePoint,
+    // larger radius, more accurate on coarse grids
+    Wide,
+}
+
+impl Stencil {
+    pub fn all() -> [Stencil; 3] {
+        return [Stencil::FivePoint, Stencil::NinePoint, Stencil::Wide];
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Stencil::FivePoint => "5-point",
+            Stencil::NinePoint => "9-point",
+            Stencil::Wide => "5x5",
+        };
+    }
+
+    pub fn by_name(name: &str) -> Option<Stencil> {
+        return Stencil::all().into_iter().find(|stencil| stencil.name().eq_ignore_ascii_case(name));
+    }
+
+    // the stencil that kernel is, if it's one of them
+    pub fn from_kernel(kernel: &Kernel) -> Option<Stencil> {
+        return Stencil::all().into_iter().find(|stencil| stencil.kernel() == *kernel);
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        let weights = match self {
+            Stencil::FivePoint => FIVE_POINT.to_vec(),
+            Stencil::NinePoint => NINE_POINT.to_vec(),
+            Stencil::Wide => WIDE.to_vec(),
+        };
+        return Kernel::new(weights).unwrap();
+    }
+}
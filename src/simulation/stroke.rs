@@ -0,0 +1,195 @@
+use crate::simulation::brush::Stamp;
+
+// stamps are this many brush radii apart along a stroke
+const STAMP_SPACING: f32 = 0.25;
+// in cells, so tiny brushes don't put hundreds of stamps on every cell
+const MIN_STAMP_SPACING: f32 = 0.5;
+// pieces each stamp spacing is split into when measuring along the curve
+const CURVE_STEPS_PER_STAMP: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct StrokePoint {
+    position: (f32, f32),
+    pressure: f32,
+}
+
+/*
+    Turns the pointer positions of a drag into evenly spaced stamps, all in grid cells so a stroke looks the same
+    at any frame rate, window size or grid resolution. Between two positions it follows a catmull-rom curve through
+    the ones before, so a fast stroke that only gets a few positions a second bends instead of going in straight
+    lines. The newest segment has nothing after it yet, so its end is pointed straight on from the one before
+ */
+pub struct Stroke {
+    // the last two positions, oldest first
+    points: Vec<StrokePoint>,
+    // how far along the stroke it's been since the last stamp
+    distance_since_stamp: f32,
+}
+
+impl Stroke {
+    pub fn new() -> Self {
+        return Self {
+            points: Vec::with_capacity(2),
+            distance_since_stamp: 0.0,
+        };
+    }
+
+    pub fn spacing(radius: f32) -> f32 {
+        return (radius * STAMP_SPACING).max(MIN_STAMP_SPACING);
+    }
+
+    // the stamps from the end of the stroke so far up to position. the first position of a stroke is stamped right away
+    pub fn add_point(&mut self, position: (f32, f32), pressure: f32, radius: f32) -> Vec<Stamp> {
+        let point = StrokePoint { position, pressure };
+        let mut stamps = Vec::new();
+
+        let last = match self.points.last() {
+            Some(last) => *last,
+            None => {
+                self.points.push(point);
+                stamps.push(Stamp { center: position, pressure });
+                return stamps;
+            }
+        };
+        if last.position == position {
+            return stamps;
+        }
+
+        let before = if self.points.len() >= 2 { self.points[self.points.len() - 2].position } else { last.position };
+        // no point after this one yet, so the curve carries on the way the segment goes
+        let after = (position.0 * 2.0 - last.position.0, position.1 * 2.0 - last.position.1);
+
+        let spacing = Stroke::spacing(radius);
+        let chord = distance(last.position, position);
+        let steps = ((chord / spacing * CURVE_STEPS_PER_STAMP).ceil() as i32).max(1);
+
+        let mut previous = last.position;
+        for i in 1..=steps {
+            let t = i as f32 / steps as f32;
+            let current = catmull_rom(before, last.position, position, after, t);
+            let step = distance(previous, current);
+            // stamps can land anywhere inside a step, not just at its end
+            let mut travelled = 0.0;
+            while self.distance_since_stamp + step - travelled >= spacing {
+                travelled += spacing - self.distance_since_stamp;
+                self.distance_since_stamp = 0.0;
+                let s = if step > 0.0 { travelled / step } else { 1.0 };
+                let center = (previous.0 + (current.0 - previous.0) * s, previous.1 + (current.1 - previous.1) * s);
+                let t = (i as f32 - 1.0 + s) / steps as f32;
+                stamps.push(Stamp { center, pressure: last.pressure + (pressure - last.pressure) * t });
+            }
+            self.distance_since_stamp += step - travelled;
+            previous = current;
+        }
+
+        if self.points.len() == 2 {
+            self.points.remove(0);
+        }
+        self.points.push(point);
+        return stamps;
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        return Stroke::new();
+    }
+}
+
+// the point t of the way from p1 to p2 on a uniform catmull-rom curve
+fn catmull_rom(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), t: f32) -> (f32, f32) {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let point = |a: f32, b: f32, c: f32, d: f32| {
+        0.5 * (2.0 * b + (c - a) * t + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2 + (3.0 * b - a - 3.0 * c + d) * t3)
+    };
+    return (point(p0.0, p1.0, p2.0, p3.0), point(p0.1, p1.1, p2.1, p3.1));
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    return ((b.0 - a.0) * (b.0 - a.0) + (b.1 - a.1) * (b.1 - a.1)).sqrt();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::brush::Stamp;
+    use crate::simulation::stroke::*;
+
+    // every stamp of a stroke through positions, one position a frame
+    fn stroke_through(positions: &[(f32, f32)], radius: f32) -> Vec<Stamp> {
+        let mut stroke = Stroke::new();
+        return positions.iter().flat_map(|position| stroke.add_point(*position, 1.0, radius)).collect();
+    }
+
+    fn assert_same_centers(a: &[Stamp], b: &[Stamp]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert!(distance(a.center, b.center) < 1e-3, "{:?} and {:?}", a.center, b.center);
+        }
+    }
+
+    #[test]
+    fn same_stamps_however_many_frames_a_stroke_is_split_over() {
+        // spacing of 2 cells
+        let radius = 8.0;
+        // 50.8 cells long, so the last stamp isn't right on the end
+        let one_frame = stroke_through(&[(0.0, 0.0), (41.0, 30.0)], radius);
+        assert_eq!(one_frame.len(), 26);
+        for frames in [2, 5, 7, 50] {
+            let positions: Vec<(f32, f32)> = (0..=frames).map(|i| (i as f32 * 41.0 / frames as f32, i as f32 * 30.0 / frames as f32)).collect();
+            assert_same_centers(&stroke_through(&positions, radius), &one_frame);
+        }
+        for pair in one_frame.windows(2) {
+            assert!((distance(pair[0].center, pair[1].center) - 2.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn curves_are_stamped_evenly() {
+        let radius = 4.0;
+        let spacing = Stroke::spacing(radius);
+        for frames in [8, 20, 100] {
+            let positions: Vec<(f32, f32)> = (0..=frames).map(|i| {
+                let angle = i as f32 / frames as f32 * std::f32::consts::PI;
+                return (50.0 * angle.cos(), 50.0 * angle.sin());
+            }).collect();
+            let stamps = stroke_through(&positions, radius);
+            // half a circle is about 157 cells, the curve and the chords between stamps are a little shorter
+            assert!((stamps.len() as f32 - 157.0 / spacing).abs() < 3.0, "{} stamps over {} frames", stamps.len(), frames);
+            for pair in stamps.windows(2) {
+                let gap = distance(pair[0].center, pair[1].center);
+                assert!(gap <= spacing + 1e-3 && gap >= spacing * 0.95, "{} apart over {} frames", gap, frames);
+            }
+        }
+    }
+
+    #[test]
+    fn stamps_scale_with_the_grid() {
+        let positions = [(0.0, 0.0), (10.0, 5.0), (20.0, 0.0)];
+        let coarse = stroke_through(&positions, 8.0);
+        let fine = stroke_through(&positions.map(|(x, y)| (x * 2.0, y * 2.0)), 16.0);
+        let scaled: Vec<Stamp> = coarse.iter().map(|stamp| Stamp { center: (stamp.center.0 * 2.0, stamp.center.1 * 2.0), pressure: stamp.pressure }).collect();
+        assert_same_centers(&fine, &scaled);
+    }
+
+    #[test]
+    fn small_brushes_are_spaced_at_least_half_a_cell() {
+        assert_eq!(Stroke::spacing(8.0), 2.0);
+        assert_eq!(Stroke::spacing(1.0), 0.5);
+        assert_eq!(stroke_through(&[(0.0, 0.0), (10.0, 0.0)], 1.0).len(), 21);
+    }
+
+    #[test]
+    fn first_point_is_stamped_and_pressure_is_interpolated() {
+        let mut stroke = Stroke::new();
+        let first = stroke.add_point((0.0, 0.0), 0.2, 8.0);
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].center, first[0].pressure), ((0.0, 0.0), 0.2));
+        // staying still doesn't stamp again
+        assert!(stroke.add_point((0.0, 0.0), 0.2, 8.0).is_empty());
+
+        let stamps = stroke.add_point((5.0, 0.0), 0.6, 8.0);
+        assert_eq!(stamps.len(), 2);
+        assert!(0.2 < stamps[0].pressure && stamps[0].pressure < stamps[1].pressure && stamps[1].pressure < 0.6, "{} {}", stamps[0].pressure, stamps[1].pressure);
+    }
+}
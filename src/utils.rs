@@ -1,4 +1,7 @@
-use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlShader};
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::Closure;
+use web_sys::{Blob, BlobPropertyBag, Document, File, FileReader, HtmlAnchorElement, Url, WebGl2RenderingContext, WebGlProgram, WebGlShader};
 
 #[macro_export] macro_rules! console_log {
     ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
@@ -70,12 +73,35 @@ pub fn link_program(
     }
 }
 
-pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
-    return a + (b - a) * t;
-}
-
 pub fn distance(a: (i32, i32), b: (i32, i32)) -> f32 {
     let x_diff = b.0 - a.0;
     let y_diff = b.1 - a.1;
     return ((x_diff * x_diff + y_diff * y_diff) as f32).sqrt();
 }
+
+// makes the browser download bytes as a file
+pub fn download_bytes(document: &Document, file_name: &str, bytes: &[u8], mime_type: &str) {
+    let parts = js_sys::Array::new();
+    parts.push(&js_sys::Uint8Array::from(bytes));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options).unwrap();
+
+    let url = Url::create_object_url_with_blob(&blob).unwrap();
+    let anchor = document.create_element("a").unwrap().dyn_into::<HtmlAnchorElement>().unwrap();
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    Url::revoke_object_url(&url).unwrap();
+}
+
+pub fn read_file_bytes(file: &File, callback: impl FnOnce(Vec<u8>) + 'static) {
+    let reader = Rc::new(FileReader::new().unwrap());
+    let reader_inner = Rc::clone(&reader);
+    let onload = Closure::once_into_js(move || {
+        let bytes = js_sys::Uint8Array::new(&reader_inner.result().unwrap()).to_vec();
+        callback(bytes);
+    });
+    reader.set_onload(Some(onload.unchecked_ref()));
+    reader.read_as_array_buffer(file).unwrap();
+}